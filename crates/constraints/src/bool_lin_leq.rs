@@ -2,6 +2,7 @@ use limiga_core::{
     domains::{Conflict, DomainStore},
     integer::{BoundedInt, BoundedIntVar, Int, IntEvent},
     lit::Lit,
+    proof::PbConstraint,
     propagation::{
         Context, DomainEvent, LitEvent, LocalId, Propagator, PropagatorFactory, PropagatorVar,
         VariableRegistrar, Watchable, Explanation,
@@ -99,4 +100,16 @@ where
 
         Ok(())
     }
+
+    fn definition(&self, ctx: &mut Context<Domains, Event>) -> Option<PbConstraint> {
+        // y - \sum x_i >= 0
+        let mut definition = PbConstraint::at_least(0);
+        self.y.add_to_constraint(ctx, 1, &mut definition)?;
+
+        for x_i in self.x.iter() {
+            definition.add_term(-1, x_i.variable);
+        }
+
+        Some(definition)
+    }
 }
@@ -0,0 +1,113 @@
+use limiga_core::{
+    atom::Atom,
+    domains::Conflict,
+    lit::Lit,
+    proof::PbConstraint,
+    propagation::{
+        Context, Explanation, LitEvent, Propagator, PropagatorFactory, PropagatorVar, SDomainEvent,
+        VariableRegistrar,
+    },
+};
+
+pub struct AtMostFactory {
+    pub lits: Box<[Lit]>,
+    pub bound: usize,
+}
+
+impl<Domains, Event> PropagatorFactory<Domains, Event> for AtMostFactory
+where
+    Event: SDomainEvent<LitEvent>,
+{
+    fn create(
+        self,
+        registrar: &mut VariableRegistrar<'_, Event>,
+    ) -> Box<dyn Propagator<Domains, Event>> {
+        let lits = self
+            .lits
+            .iter()
+            .enumerate()
+            .map(|(i, &lit)| registrar.register(lit, (i as u32).into(), LitEvent::FixedTrue))
+            .collect();
+
+        Box::new(AtMost {
+            lits,
+            bound: self.bound,
+        })
+    }
+}
+
+/// A propagator for the constraint `\sum lits_i <= bound`. Once `bound` literals are true, the
+/// other literals are fixed to false. The explanation is the literals which are true.
+pub struct AtMost {
+    lits: Box<[PropagatorVar<Lit>]>,
+    bound: usize,
+}
+
+impl<Domains, Event> Propagator<Domains, Event> for AtMost {
+    fn propagate(&mut self, ctx: &mut Context<Domains, Event>) -> Result<(), Conflict<Domains>> {
+        let true_lits = self
+            .lits
+            .iter()
+            .filter(|&&lit| ctx.value(lit) == Some(true))
+            .map(|lit| lit.variable)
+            .take(self.bound)
+            .collect::<Vec<_>>();
+
+        if true_lits.len() < self.bound {
+            return Ok(());
+        }
+
+        let explanation = true_lits
+            .iter()
+            .map(|&lit| Box::new(lit) as Box<dyn Atom<Domains>>)
+            .collect::<Explanation<_>>();
+
+        // If more than `bound` literals are true, fixing one of the others to false is a
+        // conflict.
+        for &lit in self.lits.iter() {
+            if ctx.value(lit) != Some(false) && !true_lits.contains(&lit.variable) {
+                ctx.assign(lit, false, explanation.clone())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn definition(&self, _: &mut Context<Domains, Event>) -> Option<PbConstraint> {
+        // -\sum lits_i >= -bound
+        let mut definition = PbConstraint::at_least(-(self.bound as i64));
+
+        for lit in self.lits.iter() {
+            definition.add_term(-1, lit.variable);
+        }
+
+        Some(definition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use limiga_core::{solver::Solver, testing::count_solutions};
+
+    use crate::at_most;
+
+    use super::*;
+
+    #[test]
+    fn solutions_are_the_assignments_within_the_bound() {
+        let mut solver = Solver::<(), LitEvent>::default();
+        let lits = solver.new_lits().take(4).collect::<Box<[_]>>();
+        at_most(&mut solver, lits.clone(), 2);
+
+        let num_solutions = count_solutions(&mut solver, |solution| {
+            let num_true = lits
+                .iter()
+                .filter(|lit| solution.value(lit.var()) == lit.is_positive())
+                .count();
+            assert!(num_true <= 2);
+        });
+
+        // The assignments with none, one or two of the four literals true.
+        assert_eq!(1 + 4 + 6, num_solutions);
+    }
+}
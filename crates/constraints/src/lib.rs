@@ -1,17 +1,21 @@
 use std::fmt::Debug;
 
+use cardinality::AtMostFactory;
 use limiga_core::{
     domains::DomainStore,
     integer::{BoundedInt, BoundedIntVar, Int, IntEvent},
     lit::Lit,
-    propagation::{DomainEvent, LitEvent, Watchable},
+    propagation::{DomainEvent, LitEvent, SDomainEvent, Watchable},
     solver::{ExtendSolver, Solver},
     storage::StaticIndexer,
 };
 use linear_leq::LinearLeqFactory;
+use xor::{GaussJordanFactory, XorSystem};
 
 mod bool_lin_leq;
+mod cardinality;
 mod linear_leq;
+mod xor;
 
 pub fn bool_lin_leq<VY, Domains, Event>(
     solver: &mut impl ExtendSolver<Domains, Event>,
@@ -61,6 +65,31 @@ where
     true
 }
 
+/// Post the constraint that at most `bound` of the literals are true.
+pub fn at_most<Domains, Event>(
+    solver: &mut Solver<Domains, Event>,
+    lits: Box<[Lit]>,
+    bound: usize,
+) -> bool
+where
+    Event: Copy + Debug + StaticIndexer + SDomainEvent<LitEvent>,
+{
+    if lits.len() <= bound {
+        return true;
+    }
+
+    // The propagator explains its propagations by the true literals, so it needs at least one.
+    if bound == 0 {
+        for &lit in lits.iter() {
+            solver.add_clause([!lit]);
+        }
+
+        return true;
+    }
+
+    solver.add_propagator(AtMostFactory { lits, bound })
+}
+
 /// Post the constraint `\sum terms_i <= rhs`.
 pub fn linear_leq<Domains, Event, Var>(
     solver: &mut Solver<Domains, Event>,
@@ -78,3 +107,28 @@ where
         rhs,
     })
 }
+
+/// Post the constraints that an odd number of the literals of every XOR is true. The constraints
+/// are propagated together, by Gauss-Jordan elimination on the system they form.
+pub fn xors<Domains, Event>(
+    solver: &mut Solver<Domains, Event>,
+    xors: impl IntoIterator<Item = Box<[Lit]>>,
+) -> bool
+where
+    Event: Copy + Debug + StaticIndexer + SDomainEvent<LitEvent>,
+{
+    let Some(system) = XorSystem::new(xors) else {
+        solver.add_clause([]);
+        return false;
+    };
+
+    for &unit in system.units() {
+        solver.add_clause([unit]);
+    }
+
+    if system.num_rows() == 0 {
+        return true;
+    }
+
+    solver.add_propagator(GaussJordanFactory { system })
+}
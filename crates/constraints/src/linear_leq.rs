@@ -1,6 +1,7 @@
 use limiga_core::{
     domains::{Conflict, DomainStore},
     integer::{BoundedInt, BoundedIntVar, Int, IntEvent},
+    proof::PbConstraint,
     propagation::{
         Context, DomainEvent, Explanation, Propagator, PropagatorFactory, PropagatorVar,
         VariableRegistrar, Watchable,
@@ -65,7 +66,7 @@ where
 
         for (idx, term) in self.terms.iter().enumerate() {
             let term_lb = term.min(ctx);
-            let new_max = self.rhs - optimistic_lhs - term_lb;
+            let new_max = self.rhs - (optimistic_lhs - term_lb);
 
             let min_lit = explanation_base.swap_remove(idx);
 
@@ -80,6 +81,45 @@ where
             explanation_base.swap(idx, len);
         }
 
-        todo!()
+        Ok(())
+    }
+
+    fn definition(&self, ctx: &mut Context<Domains, Event>) -> Option<PbConstraint> {
+        // -\sum x_i >= -rhs
+        let mut definition = PbConstraint::at_least(-(self.rhs as i64));
+
+        for term in self.terms.iter() {
+            term.add_to_constraint(ctx, -1, &mut definition)?;
+        }
+
+        Some(definition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use limiga_core::{
+        domains::TypedDomainStore, integer::interval_domain::IntInterval, solver::Solver,
+        testing::count_solutions,
+    };
+
+    use crate::linear_leq;
+
+    use super::*;
+
+    #[test]
+    fn solutions_are_the_assignments_within_the_bound() {
+        let mut solver = Solver::<TypedDomainStore<IntInterval>, IntEvent>::default();
+        let x = solver.new_domain(IntInterval::factory(0, 3));
+        let y = solver.new_domain(IntInterval::factory(0, 3));
+        linear_leq(&mut solver, [x.clone(), y.clone()], 4);
+
+        let num_solutions = count_solutions(&mut solver, |solution| {
+            let sum = solution.domain_value(x.clone()) + solution.domain_value(y.clone());
+            assert!(sum <= 4);
+        });
+
+        // All 16 assignments except (2, 3), (3, 2) and (3, 3).
+        assert_eq!(13, num_solutions);
     }
 }
@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+
+use limiga_core::{
+    atom::Atom,
+    domains::Conflict,
+    lit::{Lit, Var},
+    propagation::{
+        Context, Explanation, LitEvent, Propagator, PropagatorFactory, PropagatorVar, SDomainEvent,
+        VariableRegistrar,
+    },
+};
+
+/// A system of XOR constraints as a matrix over GF(2). Every row is an XOR constraint over the
+/// variables of the columns whose bits are set, and its right-hand side is the parity of the
+/// number of true variables.
+pub struct XorSystem {
+    vars: Vec<Var>,
+    rows: Vec<Row>,
+    /// The literals which are implied by the system on their own. Their columns do not occur in
+    /// the rows.
+    units: Vec<Lit>,
+}
+
+#[derive(Clone)]
+struct Row {
+    bits: Box<[u64]>,
+    rhs: bool,
+    /// The column which occurs in this row only.
+    basic: usize,
+}
+
+impl XorSystem {
+    /// Bring the XOR constraints in reduced row echelon form. Every constraint states that an odd
+    /// number of its literals is true. Returns `None` if the constraints are inconsistent.
+    pub fn new(xors: impl IntoIterator<Item = Box<[Lit]>>) -> Option<XorSystem> {
+        let mut vars = vec![];
+        let mut columns = BTreeMap::new();
+        let mut rows = vec![];
+
+        for xor in xors {
+            let mut row = Row {
+                bits: Box::new([]),
+                rhs: true,
+                basic: 0,
+            };
+            let mut row_columns = vec![];
+
+            for lit in xor.iter() {
+                let column = *columns.entry(lit.var()).or_insert_with(|| {
+                    vars.push(lit.var());
+                    vars.len() - 1
+                });
+
+                row_columns.push(column);
+
+                // A negative literal is true when the variable is false.
+                row.rhs ^= !lit.is_positive();
+            }
+
+            rows.push((row, row_columns));
+        }
+
+        let num_words = vars.len().div_ceil(64);
+        let mut system = XorSystem {
+            vars,
+            rows: Vec::with_capacity(rows.len()),
+            units: vec![],
+        };
+
+        for (mut row, row_columns) in rows {
+            row.bits = vec![0; num_words].into();
+            for column in row_columns {
+                row.bits[column / 64] ^= 1 << (column % 64);
+            }
+
+            // Eliminate the basic columns of the previous rows from the new row.
+            for other in system.rows.iter() {
+                if row.contains(other.basic) {
+                    row.add(other);
+                }
+            }
+
+            let Some(basic) = row.columns().next() else {
+                if row.rhs {
+                    return None;
+                }
+
+                continue;
+            };
+
+            row.basic = basic;
+            for other in system.rows.iter_mut() {
+                if other.contains(basic) {
+                    other.add(&row);
+                }
+            }
+
+            system.rows.push(row);
+        }
+
+        // A row with a single column fixes its variable. Since the basic column does not occur in
+        // other rows, the row can be removed.
+        system.rows.retain(|row| {
+            let mut columns = row.columns();
+            match (columns.next(), columns.next()) {
+                (Some(column), None) => {
+                    let var = system.vars[column];
+                    system.units.push(if row.rhs {
+                        Lit::positive(var)
+                    } else {
+                        Lit::negative(var)
+                    });
+                    false
+                }
+                _ => true,
+            }
+        });
+
+        Some(system)
+    }
+
+    pub fn units(&self) -> &[Lit] {
+        &self.units
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Make the given column the basic column of the row, by eliminating it from the other rows.
+    fn pivot(&mut self, row_idx: usize, column: usize) {
+        self.rows[row_idx].basic = column;
+        let row = self.rows[row_idx].clone();
+
+        for (idx, other) in self.rows.iter_mut().enumerate() {
+            if idx != row_idx && other.contains(column) {
+                other.add(&row);
+            }
+        }
+    }
+}
+
+impl Row {
+    fn contains(&self, column: usize) -> bool {
+        self.bits[column / 64] & (1 << (column % 64)) != 0
+    }
+
+    fn add(&mut self, other: &Row) {
+        for (word, other_word) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word ^= other_word;
+        }
+
+        self.rhs ^= other.rhs;
+    }
+
+    fn columns(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bits.iter().enumerate().flat_map(|(idx, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| idx * 64 + bit)
+        })
+    }
+}
+
+pub struct GaussJordanFactory {
+    pub system: XorSystem,
+}
+
+impl<Domains, Event> PropagatorFactory<Domains, Event> for GaussJordanFactory
+where
+    Event: SDomainEvent<LitEvent>,
+{
+    fn create(
+        self,
+        registrar: &mut VariableRegistrar<'_, Event>,
+    ) -> Box<dyn Propagator<Domains, Event>> {
+        let vars = self
+            .system
+            .vars
+            .iter()
+            .enumerate()
+            .map(|(column, &var)| {
+                let lit = Lit::positive(var);
+                registrar.register(lit, (column as u32).into(), LitEvent::FixedFalse);
+                registrar.register(lit, (column as u32).into(), LitEvent::FixedTrue)
+            })
+            .collect();
+
+        let num_words = self.system.vars.len().div_ceil(64);
+
+        Box::new(GaussJordan {
+            system: self.system,
+            vars,
+            assigned: vec![0; num_words].into(),
+            values: vec![0; num_words].into(),
+        })
+    }
+}
+
+/// A propagator for a system of XOR constraints. The system is kept in reduced row echelon form,
+/// in which the basic column of a row is unassigned whenever the row has an unassigned column.
+/// When the basic column of a row is assigned, another unassigned column of the row replaces it
+/// by Gauss-Jordan elimination. The system stays equivalent to the original constraints, so it
+/// does not have to be restored when the solver backtracks.
+///
+/// A row whose only unassigned column is the basic column propagates it. The explanation is the
+/// assignment to the other columns of the row, which is implied by the original constraints.
+pub struct GaussJordan {
+    system: XorSystem,
+    vars: Box<[PropagatorVar<Lit>]>,
+    /// The columns which are assigned, and the columns which are true, as bitsets. They are
+    /// filled at the start of every propagation.
+    assigned: Box<[u64]>,
+    values: Box<[u64]>,
+}
+
+impl GaussJordan {
+    fn is_assigned(&self, column: usize) -> bool {
+        self.assigned[column / 64] & (1 << (column % 64)) != 0
+    }
+
+    /// The number of unassigned columns in the row, and the parity of the true columns.
+    fn count(&self, row: &Row) -> (u32, bool) {
+        let mut unassigned = 0;
+        let mut parity = 0;
+
+        for ((word, assigned), values) in row
+            .bits
+            .iter()
+            .zip(self.assigned.iter())
+            .zip(self.values.iter())
+        {
+            unassigned += (word & !assigned).count_ones();
+            parity += (word & values).count_ones();
+        }
+
+        (unassigned, parity % 2 == 1)
+    }
+
+    /// The literals which are true for the assigned columns of the row other than the given one.
+    fn explanation<Domains>(&self, row: &Row, column: usize) -> Explanation<Domains> {
+        row.columns()
+            .filter(|&other| other != column)
+            .map(|other| {
+                let var = self.vars[other].variable;
+                let is_true = self.values[other / 64] & (1 << (other % 64)) != 0;
+                Box::new(if is_true { var } else { !var }) as Box<dyn Atom<Domains>>
+            })
+            .collect()
+    }
+}
+
+impl<Domains, Event> Propagator<Domains, Event> for GaussJordan {
+    fn propagate(&mut self, ctx: &mut Context<Domains, Event>) -> Result<(), Conflict<Domains>> {
+        self.assigned.fill(0);
+        self.values.fill(0);
+
+        for (column, &var) in self.vars.iter().enumerate() {
+            if let Some(value) = ctx.value(var) {
+                self.assigned[column / 64] |= 1 << (column % 64);
+                self.values[column / 64] |= (value as u64) << (column % 64);
+            }
+        }
+
+        // Eliminating a column only changes rows which contain it, and those rows do not have
+        // their basic column replaced, so a single pass suffices.
+        for row_idx in 0..self.system.rows.len() {
+            let row = &self.system.rows[row_idx];
+            if !self.is_assigned(row.basic) {
+                continue;
+            }
+
+            let unassigned = row.columns().find(|&column| !self.is_assigned(column));
+            if let Some(column) = unassigned {
+                self.system.pivot(row_idx, column);
+            }
+        }
+
+        for row in self.system.rows.iter() {
+            let (unassigned, parity) = self.count(row);
+
+            // If the row is not fully assigned, its basic column is unassigned.
+            if unassigned > 1 || (unassigned == 0 && parity == row.rhs) {
+                continue;
+            }
+
+            let explanation = self.explanation(row, row.basic);
+
+            // The basic column is true if the parity of the other columns differs from the
+            // right-hand side. If it is assigned, this is a conflict.
+            let basic_is_true = self.values[row.basic / 64] & (1 << (row.basic % 64)) != 0;
+            let parity_of_others = parity ^ (unassigned == 0 && basic_is_true);
+
+            ctx.assign(
+                self.vars[row.basic],
+                parity_of_others != row.rhs,
+                explanation,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use limiga_core::{solver::Solver, testing::count_solutions};
+
+    use crate::xors;
+
+    use super::*;
+
+    #[test]
+    fn system_implies_the_units_of_its_xors() {
+        let mut solver = Solver::<(), LitEvent>::default();
+        let [a, b, c] = [(); 3].map(|_| solver.new_lits().next().unwrap());
+
+        // Adding up the rows gives the XOR `b`.
+        let system = XorSystem::new([[a, b, c].into(), [a, !c].into()]).expect("consistent xors");
+
+        assert!(system.units().contains(&b));
+    }
+
+    #[test]
+    fn inconsistent_xors_have_no_system() {
+        let mut solver = Solver::<(), LitEvent>::default();
+        let [a, b] = [(); 2].map(|_| solver.new_lits().next().unwrap());
+
+        assert!(XorSystem::new([[a, b].into(), [!a, b].into()]).is_none());
+    }
+
+    #[test]
+    fn solutions_are_the_assignments_with_odd_parities() {
+        let mut solver = Solver::<(), LitEvent>::default();
+        let lits = solver.new_lits().take(5).collect::<Vec<_>>();
+        let constraints: [Box<[Lit]>; 2] = [lits[0..3].into(), lits[2..5].into()];
+        xors(&mut solver, constraints.clone());
+
+        let num_solutions = count_solutions(&mut solver, |solution| {
+            for xor in &constraints {
+                let num_true = xor
+                    .iter()
+                    .filter(|lit| solution.value(lit.var()) == lit.is_positive())
+                    .count();
+                assert_eq!(1, num_true % 2);
+            }
+        });
+
+        // Every independent XOR halves the 32 assignments.
+        assert_eq!(8, num_solutions);
+    }
+}
@@ -2,11 +2,11 @@ use log::trace;
 
 use crate::{
     brancher::Brancher,
-    clause::ClauseDb,
+    clause::{ClauseDb, ClauseId, ClauseRef},
     domains::Conflict,
     implication_graph::ImplicationGraph,
     lit::{Lit, Var},
-    propagation::Reason,
+    propagation::{PropagatorId, Reason},
     search_tree::SearchTree,
     storage::KeyedVec,
     trail::Trail,
@@ -27,14 +27,46 @@ pub struct ConflictAnalyzer {
     to_clear: Vec<Var>,
     /// The DFS stack for clause minimization.
     stack: Vec<Lit>,
+    /// The buffer used to compute the literal block distance of the learned clause.
+    levels: Vec<usize>,
+    /// The clauses from the clause database that were resolved during the analysis.
+    used_clauses: Vec<ClauseRef>,
+    /// Whether the IDs of the clauses the learned clause is derived from should be recorded.
+    records_hints: bool,
+    /// The IDs of the clauses the learned clause is derived from.
+    hints: Vec<ClauseId>,
+    /// The DFS stack for recording hints. Every variable is accompanied by whether the reasons of
+    /// its antecedents have been recorded.
+    hint_stack: Vec<(Var, bool)>,
+    /// Whether the explanations of propagators the learned clause is derived from should be
+    /// recorded.
+    records_explanations: bool,
+    /// The explanations of propagators the learned clause is derived from, as clauses, along with
+    /// the propagators which gave them.
+    explanations: Vec<(PropagatorId, Vec<Lit>)>,
 }
 
 /// The result of conflict analysis.
 pub struct Analysis<'a> {
     /// The clause to learn.
     pub learned_clause: &'a [Lit],
+    /// The length of the learned clause before it was minimized.
+    pub unminimized_len: usize,
     /// The decision level to backjump to.
     pub backjump_level: usize,
+    /// The literal block distance of the learned clause, i.e. the number of distinct decision
+    /// levels among its literals.
+    pub lbd: u32,
+    /// The clauses from the clause database that were used to derive the learned clause.
+    pub used_clauses: &'a [ClauseRef],
+    /// The IDs of the clauses the learned clause is derived from, in the order they become unit
+    /// under the negation of the learned clause. Empty unless enabled with
+    /// [`ConflictAnalyzer::record_hints`].
+    pub hints: &'a [ClauseId],
+    /// The explanations of propagators the learned clause is derived from, as clauses, along with
+    /// the propagators which gave them. Only explanations of literals which are not assigned at the
+    /// root are included. Empty unless enabled with [`ConflictAnalyzer::record_explanations`].
+    pub explanations: &'a [(PropagatorId, Vec<Lit>)],
 }
 
 impl ConflictAnalyzer {
@@ -42,6 +74,17 @@ impl ConflictAnalyzer {
         self.seen.grow_to(var);
     }
 
+    /// Set whether the analysis records the IDs of the clauses the learned clause is derived from.
+    pub fn record_hints(&mut self, records_hints: bool) {
+        self.records_hints = records_hints;
+    }
+
+    /// Set whether the analysis records the explanations of propagators the learned clause is
+    /// derived from.
+    pub fn record_explanations(&mut self, records_explanations: bool) {
+        self.records_explanations = records_explanations;
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn analyze<Domains, SearchProc: Brancher>(
         &mut self,
@@ -55,6 +98,11 @@ impl ConflictAnalyzer {
     ) -> Analysis {
         self.current_level_count = 0;
         self.buffer.clear();
+        self.used_clauses.clear();
+
+        if let Conflict::Clause(clause_ref) = &conflict {
+            self.used_clauses.push(*clause_ref);
+        }
 
         let lits = conflict.lits(clauses, domains);
 
@@ -86,6 +134,13 @@ impl ConflictAnalyzer {
                 continue;
             }
 
+            if search_tree.decision_level(lit.var()) != search_tree.depth() {
+                // With chronological backtracking, literals from lower decision levels can come
+                // after literals from the current decision level on the trail. They stay in the
+                // learned clause.
+                continue;
+            }
+
             // We resolve on `lit`, which removes it from the working clause.
             self.current_level_count -= 1;
 
@@ -108,9 +163,12 @@ impl ConflictAnalyzer {
             // trail in reverse order, and there is at least one more literal on the trail that was
             // assigned at the current decision level, the current literal *MUST* have been
             // propagated.
-            let reason = implication_graph
-                .reason(lit.var())
-                .as_clause(clauses, domains);
+            let reason = implication_graph.reason(lit.var());
+            if let Some(clause_ref) = reason.clause_ref() {
+                self.used_clauses.push(clause_ref);
+            }
+
+            let reason = reason.as_clause(clauses, domains);
 
             assert_eq!(
                 lit, reason[0],
@@ -122,6 +180,7 @@ impl ConflictAnalyzer {
             }
         }
 
+        let unminimized_len = self.buffer.len();
         self.minimize_clause(clauses, implication_graph, search_tree, domains);
 
         // Reset the seen state for any variable we encountered during analysis.
@@ -129,6 +188,19 @@ impl ConflictAnalyzer {
             self.seen[var] = false;
         }
 
+        self.hints.clear();
+        self.explanations.clear();
+        if self.records_hints || self.records_explanations {
+            self.collect_antecedents(
+                &conflict,
+                &lits,
+                clauses,
+                implication_graph,
+                search_tree,
+                domains,
+            );
+        }
+
         let (idx, backjump_level) = self
             .buffer
             .iter()
@@ -145,10 +217,233 @@ impl ConflictAnalyzer {
 
         trace!("backtracing to {backjump_level}");
 
+        let lbd = self.compute_lbd(search_tree);
+
         Analysis {
             learned_clause: &self.buffer,
+            unminimized_len,
             backjump_level,
+            lbd,
+            used_clauses: &self.used_clauses,
+            hints: &self.hints,
+            explanations: &self.explanations,
+        }
+    }
+
+    /// Collect the hints and explanations the learned clause is derived from. Starting at the
+    /// conflict, the implication graph is traversed until the literals of the learned clause, or
+    /// literals assigned at the root, are reached. This covers both the resolution steps and the
+    /// literals removed by minimization.
+    ///
+    /// The reasons are recorded in post-order, so every reason comes after the reasons of the
+    /// literals it depends on. Only reasons which are clauses in the database have an ID to use as
+    /// a hint, the other reasons are explanations.
+    fn collect_antecedents<Domains>(
+        &mut self,
+        conflict: &Conflict<Domains>,
+        conflict_lits: &[Lit],
+        clauses: &ClauseDb,
+        implication_graph: &ImplicationGraph<Domains>,
+        search_tree: &SearchTree,
+        domains: &Domains,
+    ) {
+        // The negation of the learned clause is assumed when checking the hints.
+        for lit in self.buffer.iter() {
+            self.seen[lit.var()] = true;
+            self.to_clear.push(lit.var());
+        }
+
+        self.hint_stack
+            .extend(conflict_lits.iter().map(|lit| (lit.var(), false)));
+
+        while let Some((var, is_expanded)) = self.hint_stack.pop() {
+            if is_expanded {
+                match implication_graph.reason(var) {
+                    Reason::Clause(clause_ref) | Reason::BinaryClause { clause_ref, .. }
+                        if self.records_hints =>
+                    {
+                        self.hints.push(clauses[*clause_ref].id());
+                    }
+                    reason @ Reason::Explanation { propagator, .. }
+                        if self.records_explanations =>
+                    {
+                        let explanation = reason.as_clause(clauses, domains).into_owned();
+                        self.explanations.push((*propagator, explanation));
+                    }
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            if self.seen[var] {
+                continue;
+            }
+
+            self.seen[var] = true;
+            self.to_clear.push(var);
+
+            if search_tree.decision_level(var) == 0 {
+                if self.records_hints {
+                    self.hints.extend(clauses.unit_id(var));
+                }
+
+                continue;
+            }
+
+            self.hint_stack.push((var, true));
+
+            let reason = implication_graph.reason(var).as_clause(clauses, domains);
+            for p in reason.iter() {
+                if p.var() != var && !self.seen[p.var()] {
+                    self.hint_stack.push((p.var(), false));
+                }
+            }
+        }
+
+        match conflict {
+            Conflict::Clause(clause_ref) if self.records_hints => {
+                self.hints.push(clauses[*clause_ref].id());
+            }
+            Conflict::Propagator { propagator, .. } if self.records_explanations => {
+                self.explanations
+                    .push((*propagator, conflict_lits.to_vec()));
+            }
+            _ => {}
         }
+
+        for var in self.to_clear.drain(..) {
+            self.seen[var] = false;
+        }
+    }
+
+    /// Collect the hints for the clause `!decision \/ lit`, where `lit` is propagated by a clause
+    /// at the current decision level, and `decision` is the only decision at that level. Hints
+    /// have to be enabled with [`ConflictAnalyzer::record_hints`].
+    pub fn implication_hints<Domains>(
+        &mut self,
+        decision: Lit,
+        lit: Lit,
+        clauses: &ClauseDb,
+        implication_graph: &ImplicationGraph<Domains>,
+        search_tree: &SearchTree,
+        domains: &Domains,
+    ) -> &[ClauseId] {
+        // Under the negation of the clause, the reason of `lit` is the clause that becomes false.
+        let reason = implication_graph
+            .reason(lit.var())
+            .clause_ref()
+            .expect("the literal is propagated by a clause");
+
+        self.derivation_hints(
+            &[lit, !decision],
+            Conflict::Clause(reason),
+            clauses,
+            implication_graph,
+            search_tree,
+            domains,
+        )
+    }
+
+    /// Collect the hints for the given clause, whose negation is assigned by decisions and leads
+    /// to the conflict through propagation. Hints have to be enabled with
+    /// [`ConflictAnalyzer::record_hints`].
+    pub fn derivation_hints<Domains>(
+        &mut self,
+        clause: &[Lit],
+        conflict: Conflict<Domains>,
+        clauses: &ClauseDb,
+        implication_graph: &ImplicationGraph<Domains>,
+        search_tree: &SearchTree,
+        domains: &Domains,
+    ) -> &[ClauseId] {
+        self.buffer.clear();
+        self.buffer.extend_from_slice(clause);
+        self.hints.clear();
+        self.explanations.clear();
+
+        let lits = conflict.lits(clauses, domains);
+        self.collect_antecedents(
+            &conflict,
+            &lits,
+            clauses,
+            implication_graph,
+            search_tree,
+            domains,
+        );
+
+        &self.hints
+    }
+
+    fn compute_lbd(&mut self, search_tree: &SearchTree) -> u32 {
+        self.levels.clear();
+        self.levels.extend(
+            self.buffer
+                .iter()
+                .map(|lit| search_tree.decision_level(lit.var())),
+        );
+
+        self.levels.sort_unstable();
+        self.levels.dedup();
+
+        self.levels.len() as u32
+    }
+
+    /// Determine which assumptions are responsible for the given assumption being false. This
+    /// traverses the implication graph backwards from the negation of the assumption, and collects
+    /// the decisions it depends on. Since the search has not made any decisions other than the
+    /// assumptions yet, those are all assumptions.
+    ///
+    /// The given assumption is always part of the result.
+    pub fn analyze_final<Domains>(
+        &mut self,
+        assumption: Lit,
+        clauses: &ClauseDb,
+        implication_graph: &ImplicationGraph<Domains>,
+        search_tree: &SearchTree,
+        trail: &Trail,
+        domains: &Domains,
+    ) -> &[Lit] {
+        self.buffer.clear();
+        self.buffer.push(assumption);
+
+        if search_tree.decision_level(assumption.var()) == 0 {
+            // The assumption is falsified by the formula itself.
+            return &self.buffer;
+        }
+
+        self.seen[assumption.var()] = true;
+        self.to_clear.push(assumption.var());
+
+        for lit in trail.iter().rev() {
+            if !self.seen[lit.var()] {
+                continue;
+            }
+
+            let reason = implication_graph.reason(lit.var());
+            if matches!(reason, Reason::Decision) {
+                self.buffer.push(lit);
+                continue;
+            }
+
+            for p in reason.as_clause(clauses, domains).iter().skip(1) {
+                if search_tree.decision_level(p.var()) > 0 && !self.seen[p.var()] {
+                    self.seen[p.var()] = true;
+                    self.to_clear.push(p.var());
+                }
+            }
+        }
+
+        for var in self.to_clear.drain(..) {
+            self.seen[var] = false;
+        }
+
+        trace!(
+            "assumptions responsible for {assumption:?}: {:?}",
+            self.buffer
+        );
+
+        &self.buffer
     }
 
     fn add_literal<SearchProc: Brancher>(
@@ -1,9 +1,16 @@
 use crate::{
     assignment::Assignment,
     lit::{Lit, Var},
-    storage::KeyedVec,
+    mode::SearchMode,
+    phase::Phases,
+    random::Random,
+    storage::{Indexer, KeyedVec},
+    trail::Trail,
 };
 
+/// The largest decay of the activities in focused mode.
+const FOCUSED_DECAY: f64 = 0.85;
+
 pub trait Brancher {
     /// Called when search starts. Given the variable with the largest code, to set up
     /// datastructures that use a continuously indexed map (e.g. [`KeyedVec`]
@@ -16,24 +23,137 @@ pub trait Brancher {
     /// Called when the solver finishes conflict analysis.
     fn on_conflict(&mut self);
 
-    /// Add a variable back into contention if it had previously been assigned.
-    fn on_variable_unassigned(&mut self, var: Var);
+    /// Called before the solver backtracks. The trail holds the assignment that was reached by
+    /// the search, in the order the literals were assigned.
+    fn on_backtrack(&mut self, _trail: &Trail) {}
+
+    /// Called when a variable is assigned, by a decision or by propagation, as soon as it is
+    /// assigned. Literals which the solver assigns outside of the search, e.g. while probing, are
+    /// not reported here, nor to [`Brancher::on_variable_unassigned`].
+    fn on_variable_assigned(&mut self, _lit: Lit) {}
+
+    /// Add a variable back into contention if it had previously been assigned. The given literal
+    /// is the value the variable had before it was unassigned.
+    fn on_variable_unassigned(&mut self, lit: Lit);
+
+    /// Called when the solver restarts, after it has backtracked to the root.
+    fn on_restart(&mut self) {}
+
+    /// Called when the solver switches to the given mode, and when it starts searching in it. Only
+    /// called if the solver switches modes.
+    fn on_mode_switch(&mut self, _mode: SearchMode) {}
 
     /// Provide the solver with the next decision. The returned literal should be unassigned under
     /// the given assignment.
     fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit>;
+
+    /// The phases the brancher picks for its decisions, if it keeps track of them. The solver uses
+    /// them as the starting point of local search, and saves the result in them.
+    fn phases(&mut self) -> Option<&mut Phases> {
+        None
+    }
+}
+
+/// Allows a brancher to be lent to the solver, so its state carries over between calls to
+/// [`crate::solver::Solver::solve`].
+impl<B: Brancher + ?Sized> Brancher for &mut B {
+    fn initialize(&mut self, largest_var: Var) {
+        (**self).initialize(largest_var)
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        (**self).on_variable_activated(var)
+    }
+
+    fn on_conflict(&mut self) {
+        (**self).on_conflict()
+    }
+
+    fn on_backtrack(&mut self, trail: &Trail) {
+        (**self).on_backtrack(trail)
+    }
+
+    fn on_variable_assigned(&mut self, lit: Lit) {
+        (**self).on_variable_assigned(lit)
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        (**self).on_variable_unassigned(lit)
+    }
+
+    fn on_restart(&mut self) {
+        (**self).on_restart()
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        (**self).on_mode_switch(mode)
+    }
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        (**self).next_decision(assignment)
+    }
+
+    fn phases(&mut self) -> Option<&mut Phases> {
+        (**self).phases()
+    }
+}
+
+/// Allows branchers which are picked at runtime to be combined.
+impl<B: Brancher + ?Sized> Brancher for Box<B> {
+    fn initialize(&mut self, largest_var: Var) {
+        (**self).initialize(largest_var)
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        (**self).on_variable_activated(var)
+    }
+
+    fn on_conflict(&mut self) {
+        (**self).on_conflict()
+    }
+
+    fn on_backtrack(&mut self, trail: &Trail) {
+        (**self).on_backtrack(trail)
+    }
+
+    fn on_variable_assigned(&mut self, lit: Lit) {
+        (**self).on_variable_assigned(lit)
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        (**self).on_variable_unassigned(lit)
+    }
+
+    fn on_restart(&mut self) {
+        (**self).on_restart()
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        (**self).on_mode_switch(mode)
+    }
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        (**self).next_decision(assignment)
+    }
+
+    fn phases(&mut self) -> Option<&mut Phases> {
+        (**self).phases()
+    }
 }
 
 pub struct VsidsBrancher {
     /// The activity of each variable.
     activities: KeyedVec<Var, f64>,
-    /// A binary heap of the variables.
-    heap: Vec<Var>,
-    /// The position in the binary heap for each variable.
-    position: KeyedVec<Var, Option<usize>>,
+    /// The variables ordered by their activity.
+    heap: VarHeap,
 
     activity_increment: f64,
     decay: f64,
+    /// The decay which was configured. It is used outside of focused mode.
+    stable_decay: f64,
+
+    /// The polarity to pick for a variable when it is decided on.
+    phases: Phases,
 }
 
 impl VsidsBrancher {
@@ -41,29 +161,80 @@ impl VsidsBrancher {
         VsidsBrancher {
             activities: Default::default(),
             heap: Default::default(),
-            position: Default::default(),
             activity_increment: 1.0,
             decay,
+            stable_decay: decay,
+            phases: Default::default(),
         }
     }
 
-    fn reset_variable(&mut self, var: Var) {
+    fn rescale_activities(&mut self) {
+        self.activities
+            .iter_mut()
+            .for_each(|activity| *activity *= 1e-100);
+        self.activity_increment *= 1e-100;
+    }
+}
+
+/// A binary heap of variables, with the variable with the largest score at the root. The scores
+/// are kept by the brancher, which has to update the heap when the score of a variable changes.
+#[derive(Default)]
+struct VarHeap {
+    heap: Vec<Var>,
+    /// The position in the binary heap for each variable.
+    position: KeyedVec<Var, Option<usize>>,
+}
+
+impl VarHeap {
+    fn grow_to(&mut self, var: Var) {
+        self.position.grow_to(var);
+    }
+
+    /// Add a variable to the heap, if it is not in the heap already.
+    fn insert(&mut self, var: Var, scores: &KeyedVec<Var, f64>) {
         if self.position[var].is_none() {
             let position = self.heap.len();
             self.position[var] = Some(position);
             self.heap.push(var);
-            self.sift_up(position);
+            self.sift_up(position, scores);
         }
     }
 
-    fn rescale_activities(&mut self) {
-        self.activities
-            .iter_mut()
-            .for_each(|activity| *activity *= 1e-100);
+    /// The variable with the largest score.
+    fn peek(&self) -> Option<Var> {
+        self.heap.first().copied()
+    }
+
+    /// Remove the variable with the largest score from the heap.
+    fn pop(&mut self, scores: &KeyedVec<Var, f64>) -> Option<Var> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let var = self.heap.swap_remove(0);
+        if !self.heap.is_empty() {
+            let top_var = self.heap[0];
+            self.position[top_var] = Some(0);
+            self.sift_down(0, scores);
+        }
+        self.position[var] = None;
+
+        Some(var)
+    }
+
+    /// Restore the heap property after the score of the variable changed.
+    fn update(&mut self, var: Var, scores: &KeyedVec<Var, f64>) {
+        if let Some(pos) = self.position[var] {
+            self.sift_up(pos, scores);
+        }
+
+        if let Some(pos) = self.position[var] {
+            self.sift_down(pos, scores);
+        }
     }
 
     /// Move a variable closer to the root until the heap property is satisfied.
-    fn sift_up(&mut self, mut pos: usize) {
+    fn sift_up(&mut self, mut pos: usize, scores: &KeyedVec<Var, f64>) {
         let var = self.heap[pos];
         loop {
             if pos == 0 {
@@ -71,7 +242,7 @@ impl VsidsBrancher {
             }
             let parent_pos = (pos - 1) / 2;
             let parent_var = self.heap[parent_pos];
-            if self.activities[parent_var] >= self.activities[var] {
+            if scores[parent_var] >= scores[var] {
                 return;
             }
             self.position[var] = Some(parent_pos);
@@ -83,7 +254,7 @@ impl VsidsBrancher {
     }
 
     /// Move a variable away from the root until the heap property is satisfied.
-    fn sift_down(&mut self, mut pos: usize) {
+    fn sift_down(&mut self, mut pos: usize, scores: &KeyedVec<Var, f64>) {
         let var = self.heap[pos];
         loop {
             let mut largest_pos = pos;
@@ -93,7 +264,7 @@ impl VsidsBrancher {
             if left_pos < self.heap.len() {
                 let left_var = self.heap[left_pos];
 
-                if self.activities[largest_var] < self.activities[left_var] {
+                if scores[largest_var] < scores[left_var] {
                     largest_pos = left_pos;
                     largest_var = left_var;
                 }
@@ -103,7 +274,7 @@ impl VsidsBrancher {
             if right_pos < self.heap.len() {
                 let right_var = self.heap[right_pos];
 
-                if self.activities[largest_var] < self.activities[right_var] {
+                if scores[largest_var] < scores[right_var] {
                     largest_pos = right_pos;
                     largest_var = right_var;
                 }
@@ -125,11 +296,12 @@ impl VsidsBrancher {
 impl Brancher for VsidsBrancher {
     fn initialize(&mut self, largest_var: Var) {
         self.activities.grow_to(largest_var);
-        self.position.grow_to(largest_var);
+        self.heap.grow_to(largest_var);
+        self.phases.grow_to(largest_var);
 
         for code in 0..=largest_var.code() {
             let var = Var::try_from(code).expect("code is in a valid range");
-            self.reset_variable(var);
+            self.heap.insert(var, &self.activities);
         }
     }
 
@@ -141,30 +313,202 @@ impl Brancher for VsidsBrancher {
             self.rescale_activities();
         }
 
-        if let Some(pos) = self.position[var] {
-            self.sift_up(pos);
-        }
+        self.heap.update(var, &self.activities);
     }
 
     fn on_conflict(&mut self) {
-        self.activity_increment *= self.decay;
+        // Growing the increment makes the bumps of earlier conflicts decay relative to the
+        // bumps of later ones.
+        self.activity_increment /= self.decay;
+        self.phases.on_conflict();
     }
 
-    fn on_variable_unassigned(&mut self, var: Var) {
-        self.reset_variable(var);
+    fn on_backtrack(&mut self, trail: &Trail) {
+        self.phases.on_backtrack(trail);
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        self.phases.save(lit);
+        self.heap.insert(lit.var(), &self.activities);
+    }
+
+    fn on_restart(&mut self) {
+        self.phases.on_restart();
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        // In focused mode the activities decay faster, so the brancher follows the recent
+        // conflicts more closely, and the search is not steered towards the target phases.
+        let (decay, use_target) = match mode {
+            SearchMode::Focused => (self.stable_decay.min(FOCUSED_DECAY), false),
+            SearchMode::Stable => (self.stable_decay, true),
+        };
+
+        self.decay = decay;
+        self.phases.use_target_phases(use_target);
     }
 
     fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
-        while !self.heap.is_empty() {
-            let var = self.heap.swap_remove(0);
-            if !self.heap.is_empty() {
-                let top_var = self.heap[0];
-                self.position[top_var] = Some(0);
-                self.sift_down(0);
+        while let Some(var) = self.heap.pop(&self.activities) {
+            let lit = self.phases.decision(var);
+            if assignment.is_unassigned(lit) {
+                return Some(lit);
             }
-            self.position[var] = None;
+        }
 
-            let lit = Lit::positive(var);
+        None
+    }
+
+    fn phases(&mut self) -> Option<&mut Phases> {
+        Some(&mut self.phases)
+    }
+}
+
+/// A variable-move-to-front brancher. The variables are kept in a queue, and the variables seen
+/// during conflict analysis are moved to the back. Decisions are made on the unassigned variable
+/// closest to the back. See "Evaluating CDCL Variable Scoring Schemes" by Biere and Fröhlich
+/// (SAT 2015).
+#[derive(Default)]
+pub struct VmtfBrancher {
+    /// The neighbours of every variable in the queue. The front of the queue has no previous
+    /// variable, the back has no next variable.
+    links: KeyedVec<Var, Link>,
+    /// The time every variable was moved to the back of the queue. The timestamps increase from the
+    /// front to the back.
+    timestamps: KeyedVec<Var, u64>,
+    next_timestamp: u64,
+    last: Option<Var>,
+    num_vars: u32,
+
+    /// The variable from which the search for the next decision starts. Every variable after it in
+    /// the queue is assigned.
+    search: Option<Var>,
+
+    /// The variables seen during the analysis of the current conflict.
+    bumped: Vec<Var>,
+    is_bumped: KeyedVec<Var, bool>,
+    /// Whether a variable in `bumped` was unassigned after it was seen.
+    bumped_var_unassigned: bool,
+
+    /// The polarity to pick for a variable when it is decided on.
+    phases: Phases,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Link {
+    prev: Option<Var>,
+    next: Option<Var>,
+}
+
+impl VmtfBrancher {
+    /// Move the variable to the back of the queue.
+    fn move_to_back(&mut self, var: Var) {
+        if self.last == Some(var) {
+            return;
+        }
+
+        let Link { prev, next } = self.links[var];
+        if let Some(prev) = prev {
+            self.links[prev].next = next;
+        }
+        if let Some(next) = next {
+            self.links[next].prev = prev;
+        }
+
+        self.push_back(var);
+    }
+
+    fn push_back(&mut self, var: Var) {
+        self.links[var] = Link {
+            prev: self.last,
+            next: None,
+        };
+
+        if let Some(last) = self.last {
+            self.links[last].next = Some(var);
+        }
+
+        self.last = Some(var);
+        self.timestamps[var] = self.next_timestamp;
+        self.next_timestamp += 1;
+    }
+}
+
+impl Brancher for VmtfBrancher {
+    fn initialize(&mut self, largest_var: Var) {
+        self.links.grow_to(largest_var);
+        self.timestamps.grow_to(largest_var);
+        self.is_bumped.grow_to(largest_var);
+        self.phases.grow_to(largest_var);
+
+        for code in self.num_vars..=largest_var.code() {
+            let var = Var::try_from(code).expect("code is in a valid range");
+            self.push_back(var);
+        }
+
+        self.num_vars = self.num_vars.max(largest_var.code() + 1);
+        self.search = self.last;
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        if !self.is_bumped[var] {
+            self.is_bumped[var] = true;
+            self.bumped.push(var);
+        }
+    }
+
+    fn on_conflict(&mut self) {
+        // Moving the variables in the order of their timestamps keeps their relative order.
+        let mut bumped = std::mem::take(&mut self.bumped);
+        bumped.sort_by_key(|&var| self.timestamps[var]);
+
+        for &var in bumped.iter() {
+            self.is_bumped[var] = false;
+            self.move_to_back(var);
+        }
+
+        // The solver backtracks before the conflict is reported, so some of the moved variables
+        // may be unassigned.
+        if self.bumped_var_unassigned {
+            self.search = self.last;
+            self.bumped_var_unassigned = false;
+        }
+
+        bumped.clear();
+        self.bumped = bumped;
+        self.phases.on_conflict();
+    }
+
+    fn on_backtrack(&mut self, trail: &Trail) {
+        self.phases.on_backtrack(trail);
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        let var = lit.var();
+        self.phases.save(lit);
+        self.bumped_var_unassigned |= self.is_bumped[var];
+
+        let is_after_search = self
+            .search
+            .is_none_or(|search| self.timestamps[var] > self.timestamps[search]);
+        if is_after_search {
+            self.search = Some(var);
+        }
+    }
+
+    fn on_restart(&mut self) {
+        self.phases.on_restart();
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        self.phases.use_target_phases(mode == SearchMode::Stable);
+    }
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        while let Some(var) = self.search {
+            self.search = self.links[var].prev;
+
+            let lit = self.phases.decision(var);
             if assignment.is_unassigned(lit) {
                 return Some(lit);
             }
@@ -172,4 +516,581 @@ impl Brancher for VsidsBrancher {
 
         None
     }
+
+    fn phases(&mut self) -> Option<&mut Phases> {
+        Some(&mut self.phases)
+    }
+}
+
+/// The initial step size of the exponential recency weighted average of the rewards.
+const LRB_INITIAL_STEP_SIZE: f64 = 0.4;
+/// The amount by which the step size decreases after every conflict.
+const LRB_STEP_SIZE_DECREMENT: f64 = 1e-6;
+/// The smallest step size.
+const LRB_MIN_STEP_SIZE: f64 = 0.06;
+/// The factor by which the score of an unassigned variable decays for every conflict.
+const LRB_LOCALITY_DECAY: f64 = 0.95;
+
+/// A learning rate branching brancher. The reward of a variable is the fraction of the conflicts
+/// while it was assigned in which it took part. When a variable is unassigned, its score moves
+/// towards that reward as an exponential recency weighted average. Scores of unassigned variables
+/// decay with every conflict, so variables which took part in recent conflicts are preferred. See
+/// "Learning Rate Based Branching Heuristic for SAT Solvers" by Liang et al. (SAT 2016).
+pub struct LrbBrancher {
+    /// The score of each variable.
+    scores: KeyedVec<Var, f64>,
+    /// The variables ordered by their score.
+    heap: VarHeap,
+
+    /// The number of conflicts when each variable was assigned, if it is assigned.
+    assigned_at: KeyedVec<Var, Option<u64>>,
+    /// The number of conflicts each variable took part in since it was assigned.
+    participated: KeyedVec<Var, u64>,
+    /// The number of conflicts at which the locality decay was last applied to each variable.
+    decayed_at: KeyedVec<Var, u64>,
+
+    conflicts: u64,
+    step_size: f64,
+
+    /// The polarity to pick for a variable when it is decided on.
+    phases: Phases,
+}
+
+impl Default for LrbBrancher {
+    fn default() -> Self {
+        LrbBrancher {
+            scores: Default::default(),
+            heap: Default::default(),
+            assigned_at: Default::default(),
+            participated: Default::default(),
+            decayed_at: Default::default(),
+            conflicts: 0,
+            step_size: LRB_INITIAL_STEP_SIZE,
+            phases: Default::default(),
+        }
+    }
+}
+
+impl Brancher for LrbBrancher {
+    fn initialize(&mut self, largest_var: Var) {
+        self.scores.grow_to(largest_var);
+        self.heap.grow_to(largest_var);
+        self.assigned_at.grow_to(largest_var);
+        self.participated.grow_to(largest_var);
+        self.decayed_at.grow_to(largest_var);
+        self.phases.grow_to(largest_var);
+
+        for code in 0..=largest_var.code() {
+            let var = Var::try_from(code).expect("code is in a valid range");
+            self.heap.insert(var, &self.scores);
+        }
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        self.participated[var] += 1;
+    }
+
+    fn on_conflict(&mut self) {
+        self.conflicts += 1;
+        self.step_size = (self.step_size - LRB_STEP_SIZE_DECREMENT).max(LRB_MIN_STEP_SIZE);
+        self.phases.on_conflict();
+    }
+
+    fn on_backtrack(&mut self, trail: &Trail) {
+        self.phases.on_backtrack(trail);
+    }
+
+    fn on_variable_assigned(&mut self, lit: Lit) {
+        let var = lit.var();
+        self.assigned_at[var] = Some(self.conflicts);
+        self.participated[var] = 0;
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        let var = lit.var();
+        self.phases.save(lit);
+
+        // Variables which were assigned before the brancher was given to the solver get no
+        // reward.
+        if let Some(assigned_at) = self.assigned_at[var].take() {
+            let interval = self.conflicts - assigned_at;
+
+            if interval > 0 {
+                let reward = self.participated[var] as f64 / interval as f64;
+                self.scores[var] += self.step_size * (reward - self.scores[var]);
+            }
+        }
+
+        self.decayed_at[var] = self.conflicts;
+        self.heap.update(var, &self.scores);
+        self.heap.insert(var, &self.scores);
+    }
+
+    fn on_restart(&mut self) {
+        self.phases.on_restart();
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        self.phases.use_target_phases(mode == SearchMode::Stable);
+    }
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        while let Some(var) = self.heap.peek() {
+            let lit = self.phases.decision(var);
+
+            if !assignment.is_unassigned(lit) {
+                self.heap.pop(&self.scores);
+                continue;
+            }
+
+            // The locality decay is applied lazily, to the variables which are about to be decided
+            // on. If the score decays, another variable may come out on top.
+            let age = self.conflicts - self.decayed_at[var];
+            if age > 0 {
+                self.scores[var] *= LRB_LOCALITY_DECAY.powf(age as f64);
+                self.decayed_at[var] = self.conflicts;
+                self.heap.update(var, &self.scores);
+                continue;
+            }
+
+            self.heap.pop(&self.scores);
+            return Some(lit);
+        }
+
+        None
+    }
+
+    fn phases(&mut self) -> Option<&mut Phases> {
+        Some(&mut self.phases)
+    }
+}
+
+/// Decides with the first brancher, and with the second one whenever the first one has no
+/// decision. The first brancher is asked again for every decision, so after backtracking it
+/// decides again. Both branchers are told about everything that happens during search.
+pub struct Sequence<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Sequence<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Sequence { first, second }
+    }
+}
+
+impl<A: Brancher, B: Brancher> Brancher for Sequence<A, B> {
+    fn initialize(&mut self, largest_var: Var) {
+        self.first.initialize(largest_var);
+        self.second.initialize(largest_var);
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        self.first.on_variable_activated(var);
+        self.second.on_variable_activated(var);
+    }
+
+    fn on_conflict(&mut self) {
+        self.first.on_conflict();
+        self.second.on_conflict();
+    }
+
+    fn on_backtrack(&mut self, trail: &Trail) {
+        self.first.on_backtrack(trail);
+        self.second.on_backtrack(trail);
+    }
+
+    fn on_variable_assigned(&mut self, lit: Lit) {
+        self.first.on_variable_assigned(lit);
+        self.second.on_variable_assigned(lit);
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        self.first.on_variable_unassigned(lit);
+        self.second.on_variable_unassigned(lit);
+    }
+
+    fn on_restart(&mut self) {
+        self.first.on_restart();
+        self.second.on_restart();
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        self.first.on_mode_switch(mode);
+        self.second.on_mode_switch(mode);
+    }
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        self.first
+            .next_decision(assignment)
+            .or_else(|| self.second.next_decision(assignment))
+    }
+
+    fn phases(&mut self) -> Option<&mut Phases> {
+        match self.first.phases() {
+            Some(phases) => Some(phases),
+            None => self.second.phases(),
+        }
+    }
+}
+
+/// Switches between two branchers at every restart, starting with the first one. When the active
+/// brancher has no decision, the other one decides. Both branchers are told about everything that
+/// happens during search.
+pub struct Alternate<A, B> {
+    first: A,
+    second: B,
+    is_first_active: bool,
+}
+
+impl<A, B> Alternate<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Alternate {
+            first,
+            second,
+            is_first_active: true,
+        }
+    }
+}
+
+impl<A: Brancher, B: Brancher> Brancher for Alternate<A, B> {
+    fn initialize(&mut self, largest_var: Var) {
+        self.first.initialize(largest_var);
+        self.second.initialize(largest_var);
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        self.first.on_variable_activated(var);
+        self.second.on_variable_activated(var);
+    }
+
+    fn on_conflict(&mut self) {
+        self.first.on_conflict();
+        self.second.on_conflict();
+    }
+
+    fn on_backtrack(&mut self, trail: &Trail) {
+        self.first.on_backtrack(trail);
+        self.second.on_backtrack(trail);
+    }
+
+    fn on_variable_assigned(&mut self, lit: Lit) {
+        self.first.on_variable_assigned(lit);
+        self.second.on_variable_assigned(lit);
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        self.first.on_variable_unassigned(lit);
+        self.second.on_variable_unassigned(lit);
+    }
+
+    fn on_restart(&mut self) {
+        self.first.on_restart();
+        self.second.on_restart();
+        self.is_first_active = !self.is_first_active;
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        self.first.on_mode_switch(mode);
+        self.second.on_mode_switch(mode);
+    }
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        if self.is_first_active {
+            self.first
+                .next_decision(assignment)
+                .or_else(|| self.second.next_decision(assignment))
+        } else {
+            self.second
+                .next_decision(assignment)
+                .or_else(|| self.first.next_decision(assignment))
+        }
+    }
+
+    fn phases(&mut self) -> Option<&mut Phases> {
+        if self.is_first_active {
+            self.first.phases()
+        } else {
+            self.second.phases()
+        }
+    }
+}
+
+/// Replaces a fraction of the decisions of a brancher by a decision on a random variable, which
+/// diversifies the search. As in MiniSat, a random variable which is already assigned leaves the
+/// decision to the brancher. The polarity comes from the phases of the brancher if it has them,
+/// and is random otherwise.
+pub struct WithRandomDecisions<B> {
+    brancher: B,
+    /// The probability that a decision is random.
+    probability: f64,
+    random: Random,
+    num_vars: usize,
+}
+
+impl<B> WithRandomDecisions<B> {
+    pub fn new(brancher: B, probability: f64, seed: u64) -> Self {
+        WithRandomDecisions {
+            brancher,
+            probability,
+            random: Random::new(seed),
+            num_vars: 0,
+        }
+    }
+}
+
+impl<B: Brancher> Brancher for WithRandomDecisions<B> {
+    fn initialize(&mut self, largest_var: Var) {
+        self.num_vars = self.num_vars.max(largest_var.index() + 1);
+        self.brancher.initialize(largest_var);
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        self.brancher.on_variable_activated(var);
+    }
+
+    fn on_conflict(&mut self) {
+        self.brancher.on_conflict();
+    }
+
+    fn on_backtrack(&mut self, trail: &Trail) {
+        self.brancher.on_backtrack(trail);
+    }
+
+    fn on_variable_assigned(&mut self, lit: Lit) {
+        self.brancher.on_variable_assigned(lit);
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        self.brancher.on_variable_unassigned(lit);
+    }
+
+    fn on_restart(&mut self) {
+        self.brancher.on_restart();
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        self.brancher.on_mode_switch(mode);
+    }
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        if self.num_vars > 0 && self.random.next_f64() < self.probability {
+            let code = self.random.next_below(self.num_vars) as u32;
+            let var = Var::try_from(code).expect("valid var code");
+
+            if assignment.is_unassigned(Lit::positive(var)) {
+                let decision = match self.brancher.phases() {
+                    Some(phases) => phases.decision(var),
+                    None if self.random.next_bool() => Lit::positive(var),
+                    None => Lit::negative(var),
+                };
+
+                return Some(decision);
+            }
+        }
+
+        self.brancher.next_decision(assignment)
+    }
+
+    fn phases(&mut self) -> Option<&mut Phases> {
+        self.brancher.phases()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lit;
+
+    use super::*;
+
+    fn vars(brancher: &mut impl Brancher, assignment: &mut Assignment) -> Vec<Var> {
+        std::iter::from_fn(|| {
+            let lit = brancher.next_decision(assignment)?;
+            assignment.assign(lit);
+            Some(lit.var())
+        })
+        .collect()
+    }
+
+    /// Decides on the first unassigned literal among the given ones, and records the callbacks.
+    #[derive(Default)]
+    struct Scripted {
+        lits: Vec<Lit>,
+        activated: Vec<Var>,
+        conflicts: usize,
+        unassigned: Vec<Lit>,
+    }
+
+    impl Scripted {
+        fn new(lits: impl IntoIterator<Item = Lit>) -> Self {
+            Scripted {
+                lits: lits.into_iter().collect(),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl Brancher for Scripted {
+        fn initialize(&mut self, _: Var) {}
+
+        fn on_variable_activated(&mut self, var: Var) {
+            self.activated.push(var);
+        }
+
+        fn on_conflict(&mut self) {
+            self.conflicts += 1;
+        }
+
+        fn on_variable_unassigned(&mut self, lit: Lit) {
+            self.unassigned.push(lit);
+        }
+
+        fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+            self.lits
+                .iter()
+                .copied()
+                .find(|&lit| assignment.is_unassigned(lit))
+        }
+    }
+
+    #[test]
+    fn vmtf_decides_from_the_back_of_the_queue() {
+        let [a, b, c] = unsafe { [lit!(0), lit!(1), lit!(2)].map(|lit| lit.var()) };
+        let mut brancher = VmtfBrancher::default();
+        brancher.initialize(c);
+
+        let mut assignment = Assignment::default();
+        assignment.grow_to(c);
+
+        assert_eq!(vec![c, b, a], vars(&mut brancher, &mut assignment));
+    }
+
+    #[test]
+    fn vmtf_moves_bumped_variables_to_the_back_in_order() {
+        let [a, b, c, d] = unsafe { [lit!(0), lit!(1), lit!(2), lit!(3)].map(|lit| lit.var()) };
+        let mut brancher = VmtfBrancher::default();
+        brancher.initialize(d);
+
+        let mut assignment = Assignment::default();
+        assignment.grow_to(d);
+        assert_eq!(vec![d, c, b, a], vars(&mut brancher, &mut assignment));
+
+        brancher.on_variable_activated(b);
+        brancher.on_variable_activated(a);
+
+        for var in [a, b, c, d] {
+            let lit = Lit::positive(var);
+            assignment.unassign(lit);
+            brancher.on_variable_unassigned(lit);
+        }
+
+        brancher.on_conflict();
+
+        assert_eq!(vec![b, a, d, c], vars(&mut brancher, &mut assignment));
+    }
+
+    #[test]
+    fn lrb_prefers_variables_which_took_part_in_conflicts() {
+        let [a, b] = unsafe { [lit!(0), lit!(1)].map(|lit| lit.var()) };
+        let mut brancher = LrbBrancher::default();
+        brancher.initialize(b);
+
+        let mut assignment = Assignment::default();
+        assignment.grow_to(b);
+
+        for var in [a, b] {
+            let lit = Lit::positive(var);
+            assignment.assign(lit);
+            brancher.on_variable_assigned(lit);
+        }
+
+        brancher.on_variable_activated(b);
+        brancher.on_conflict();
+
+        for var in [a, b] {
+            let lit = Lit::positive(var);
+            assignment.unassign(lit);
+            brancher.on_variable_unassigned(lit);
+        }
+
+        assert_eq!(Some(Lit::positive(b)), brancher.next_decision(&assignment));
+    }
+
+    #[test]
+    fn sequence_decides_with_the_second_brancher_once_the_first_has_none() {
+        let [a, b, c] = unsafe { [lit!(0), lit!(1), lit!(2)] };
+        let mut brancher = Sequence::new(Scripted::new([!b]), Scripted::new([a, b, c]));
+        brancher.initialize(c.var());
+
+        let mut assignment = Assignment::default();
+        assignment.grow_to(c.var());
+        assert_eq!(
+            vec![b.var(), a.var(), c.var()],
+            vars(&mut brancher, &mut assignment)
+        );
+
+        brancher.on_variable_activated(a.var());
+        brancher.on_conflict();
+        brancher.on_variable_unassigned(a);
+
+        for child in [&brancher.first, &brancher.second] {
+            assert_eq!(vec![a.var()], child.activated);
+            assert_eq!(1, child.conflicts);
+            assert_eq!(vec![a], child.unassigned);
+        }
+    }
+
+    #[test]
+    fn alternate_switches_branchers_at_restarts() {
+        let [a, b] = unsafe { [lit!(0), lit!(1)] };
+        let mut brancher = Alternate::new(Scripted::new([a, b]), Scripted::new([b, a]));
+        brancher.initialize(b.var());
+
+        let mut assignment = Assignment::default();
+        assignment.grow_to(b.var());
+        assert_eq!(Some(a), brancher.next_decision(&assignment));
+
+        brancher.on_restart();
+        assert_eq!(Some(b), brancher.next_decision(&assignment));
+
+        // The other brancher decides when the active one has no decision.
+        let mut brancher = Alternate::new(Scripted::new([]), Scripted::new([b]));
+        assert_eq!(Some(b), brancher.next_decision(&assignment));
+
+        brancher.on_conflict();
+        assert_eq!(1, brancher.first.conflicts);
+        assert_eq!(1, brancher.second.conflicts);
+    }
+
+    #[test]
+    fn random_decisions_are_on_unassigned_variables() {
+        let lits = (0..10u32)
+            .rev()
+            .map(|code| Lit::positive(Var::try_from(code).unwrap()));
+        let last = unsafe { lit!(9) }.var();
+
+        let mut assignment = Assignment::default();
+        assignment.grow_to(last);
+        let expected = vars(&mut Scripted::new(lits.clone()), &mut assignment);
+
+        // Without random decisions, the decisions are those of the wrapped brancher.
+        let mut assignment = Assignment::default();
+        assignment.grow_to(last);
+        let mut brancher = WithRandomDecisions::new(Scripted::new(lits.clone()), 0.0, 0);
+        brancher.initialize(last);
+        assert_eq!(expected, vars(&mut brancher, &mut assignment));
+
+        let mut assignment = Assignment::default();
+        assignment.grow_to(last);
+        let mut brancher = WithRandomDecisions::new(Scripted::new(lits), 1.0, 0);
+        brancher.initialize(last);
+        let mut decided = vars(&mut brancher, &mut assignment);
+        assert_ne!(expected, decided);
+
+        // Every decision is on a variable which is still unassigned.
+        let num_decisions = decided.len();
+        decided.sort();
+        decided.dedup();
+        assert_eq!(num_decisions, decided.len());
+    }
 }
@@ -3,32 +3,136 @@ use std::{
     ops::{Index, IndexMut},
 };
 
-use crate::lit::Lit;
+use crate::{
+    lit::{Lit, Var},
+    storage::KeyedVec,
+};
+
+/// Learned clauses with an LBD of at most this value are kept indefinitely.
+const CORE_LBD: u32 = 2;
+/// Learned clauses with an LBD of at most this value are kept as long as they are used in conflict
+/// analysis between two reductions.
+const TIER2_LBD: u32 = 6;
+
+/// Clause activities are rescaled when one of them exceeds this value.
+const MAX_ACTIVITY: f64 = 1e20;
+
+/// The arena is compacted once the deleted clauses and the literals removed from shortened
+/// clauses take up this fraction of it.
+const MAX_GARBAGE_FRACTION: f64 = 0.5;
+
+/// The layout of the header in front of the literals of a clause in the arena. The flags word
+/// holds the flags in its lowest bits, and the LBD in the remaining bits.
+const SIZE: usize = 0;
+const FLAGS: usize = 1;
+const ACTIVITY: usize = 2;
+const ID_LOW: usize = 3;
+const ID_HIGH: usize = 4;
+const HEADER_LEN: usize = 5;
 
-pub struct LongClause(Box<[Lit]>);
+const LEARNED: u32 = 1;
+const DELETED: u32 = 1 << 1;
+/// Whether the clause has been used in conflict analysis since the last reduction.
+const USED: u32 = 1 << 2;
+const LBD_SHIFT: u32 = 3;
+const MAX_LBD: u32 = u32::MAX >> LBD_SHIFT;
+
+/// Fills the words which are left over when a clause is shortened, so they can be skipped when
+/// walking through the arena. It cannot be confused with the size of a clause.
+const PADDING: u32 = u32::MAX;
+
+/// A clause with at least two literals, as it is stored in the arena of the [`ClauseDb`]: a
+/// header with the size, flags, LBD, activity and ID of the clause, followed by its literals.
+#[repr(transparent)]
+pub struct LongClause {
+    words: [u32],
+}
 
 impl LongClause {
-    fn new(lits: impl AsRef<[Lit]>) -> LongClause {
-        let lits = lits.as_ref();
+    fn from_words(words: &[u32]) -> &LongClause {
+        // SAFETY: `LongClause` is a transparent wrapper around `[u32]`.
+        unsafe { &*(words as *const [u32] as *const LongClause) }
+    }
 
-        LongClause(lits.into())
+    fn from_words_mut(words: &mut [u32]) -> &mut LongClause {
+        // SAFETY: `LongClause` is a transparent wrapper around `[u32]`.
+        unsafe { &mut *(words as *mut [u32] as *mut LongClause) }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = &Lit> {
-        self.0.iter()
+        self.lits().iter()
     }
 
     pub fn lits(&self) -> &[Lit] {
-        &self.0
+        let words = &self.words[HEADER_LEN..];
+
+        // SAFETY: `Lit` is a transparent wrapper around `u32`, and every `u32` is a valid literal.
+        unsafe { std::slice::from_raw_parts(words.as_ptr().cast(), words.len()) }
+    }
+
+    fn lits_mut(&mut self) -> &mut [Lit] {
+        let words = &mut self.words[HEADER_LEN..];
+
+        // SAFETY: `Lit` is a transparent wrapper around `u32`, and every `u32` is a valid literal.
+        unsafe { std::slice::from_raw_parts_mut(words.as_mut_ptr().cast(), words.len()) }
+    }
+
+    pub fn id(&self) -> ClauseId {
+        ClauseId(self.words[ID_LOW] as u64 | (self.words[ID_HIGH] as u64) << 32)
+    }
+
+    fn set_id(&mut self, id: ClauseId) {
+        self.words[ID_LOW] = id.0 as u32;
+        self.words[ID_HIGH] = (id.0 >> 32) as u32;
     }
 
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.words.len() - HEADER_LEN
     }
 
     pub fn swap(&mut self, idx1: usize, idx2: usize) {
-        self.0.swap(idx1, idx2);
+        self.words.swap(HEADER_LEN + idx1, HEADER_LEN + idx2);
+    }
+
+    /// The literal block distance of the clause when it was learned. Only meaningful for learned
+    /// clauses.
+    pub fn lbd(&self) -> u32 {
+        self.words[FLAGS] >> LBD_SHIFT
+    }
+
+    fn set_lbd(&mut self, lbd: u32) {
+        let flags = self.words[FLAGS] & ((1 << LBD_SHIFT) - 1);
+        self.words[FLAGS] = lbd.min(MAX_LBD) << LBD_SHIFT | flags;
+    }
+
+    /// The activity of the clause in conflict analysis. Only meaningful for learned clauses.
+    fn activity(&self) -> f32 {
+        f32::from_bits(self.words[ACTIVITY])
+    }
+
+    fn set_activity(&mut self, activity: f32) {
+        self.words[ACTIVITY] = activity.to_bits();
+    }
+
+    fn has_flag(&self, flag: u32) -> bool {
+        self.words[FLAGS] & flag != 0
+    }
+
+    fn set_flag(&mut self, flag: u32, value: bool) {
+        if value {
+            self.words[FLAGS] |= flag;
+        } else {
+            self.words[FLAGS] &= !flag;
+        }
+    }
+
+    pub fn is_learned(&self) -> bool {
+        self.has_flag(LEARNED)
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.has_flag(DELETED)
     }
 }
 
@@ -36,54 +140,339 @@ impl Index<usize> for LongClause {
     type Output = Lit;
 
     fn index(&self, index: usize) -> &Self::Output {
-        &self.0[index]
+        &self.lits()[index]
     }
 }
 
 impl Debug for LongClause {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.0)
+        write!(f, "{:?}", self.lits())
     }
 }
 
-#[derive(Default)]
+/// The clauses with at least two literals. They are stored next to each other in a single arena,
+/// which keeps the clauses that are visited during propagation close together in memory.
+/// Deleted clauses stay in the arena until it is compacted by [`ClauseDb::collect_garbage`].
 pub struct ClauseDb {
-    clauses: Vec<LongClause>,
+    /// The clauses, each a header followed by its literals. See [`LongClause`].
+    arena: Vec<u32>,
+    /// The number of words in the arena which are taken up by deleted clauses, and by the
+    /// literals which were removed from shortened clauses.
+    garbage: usize,
     explanation_clauses: Vec<ClauseRef>,
+    /// The number of learned clauses which have not been deleted.
+    num_learned_clauses: usize,
+    activity_increment: f64,
+    activity_decay: f64,
+
+    /// The ID that is given to the next clause.
+    next_clause_id: u64,
+    /// The IDs of the unit clauses for the variables that are assigned at the root.
+    unit_ids: KeyedVec<Var, Option<ClauseId>>,
+}
+
+impl Default for ClauseDb {
+    fn default() -> Self {
+        ClauseDb {
+            arena: vec![],
+            garbage: 0,
+            explanation_clauses: vec![],
+            num_learned_clauses: 0,
+            activity_increment: 1.0,
+            activity_decay: 0.999,
+            next_clause_id: 1,
+            unit_ids: Default::default(),
+        }
+    }
 }
 
 impl ClauseDb {
-    pub fn add_clause(&mut self, lits: impl AsRef<[Lit]>) -> ClauseRef {
+    pub fn grow_to(&mut self, var: Var) {
+        self.unit_ids.grow_to(var);
+    }
+
+    /// Reserve the ID for a new clause. IDs are handed out in increasing order, also to clauses
+    /// which are not stored in the database, like unit clauses or clauses that are satisfied when
+    /// they are added.
+    pub fn new_clause_id(&mut self) -> ClauseId {
+        let id = ClauseId(self.next_clause_id);
+        self.next_clause_id += 1;
+        id
+    }
+
+    /// The ID of the unit clause which assigns the given variable at the root, if it is known.
+    pub fn unit_id(&self, var: Var) -> Option<ClauseId> {
+        self.unit_ids[var]
+    }
+
+    /// Record the ID of the unit clause which assigns the given variable at the root.
+    pub fn set_unit_id(&mut self, var: Var, id: ClauseId) {
+        self.unit_ids[var] = Some(id);
+    }
+
+    pub fn add_clause(&mut self, lits: impl AsRef<[Lit]>, id: ClauseId) -> ClauseRef {
+        let lits = lits.as_ref();
         assert!(
-            lits.as_ref().len() > 1,
+            lits.len() > 1,
             "The clause db cannot add the empty clause or a unit clause."
         );
 
-        let clause = LongClause::new(lits);
-        self.clauses.push(clause);
+        let offset = u32::try_from(self.arena.len()).expect("the clause arena is full");
 
-        ClauseRef {
-            index: self.clauses.len() as u32 - 1,
-            is_learned: false,
-        }
+        self.arena
+            .extend([lits.len() as u32, 0, 0, id.0 as u32, (id.0 >> 32) as u32]);
+        self.arena.extend(lits.iter().map(|lit| lit.code()));
+
+        ClauseRef(offset)
     }
 
-    pub fn add_learned_clause(&mut self, lits: impl AsRef<[Lit]>) -> ClauseRef {
-        let mut clause_ref = self.add_clause(lits);
-        clause_ref.is_learned = true;
+    pub fn add_learned_clause(&mut self, lits: impl AsRef<[Lit]>, lbd: u32) -> ClauseRef {
+        let id = self.new_clause_id();
+        let clause_ref = self.add_clause(lits, id);
+
+        let activity = self.activity_increment as f32;
+        let clause = &mut self[clause_ref];
+        clause.set_lbd(lbd);
+        clause.set_activity(activity);
+        clause.set_flag(LEARNED, true);
+
+        self.num_learned_clauses += 1;
 
         clause_ref
     }
 
     pub fn add_explanation_clause(&mut self, lits: impl AsRef<[Lit]>) -> ClauseRef {
-        let clause_ref = self.add_clause(lits);
+        let id = self.new_clause_id();
+        let clause_ref = self.add_clause(lits, id);
         self.explanation_clauses.push(clause_ref);
 
         clause_ref
     }
 
     pub fn is_learned(&self, clause_ref: ClauseRef) -> bool {
-        clause_ref.is_learned
+        self[clause_ref].is_learned()
+    }
+
+    /// The first clause in the arena at or after the given offset, including deleted clauses.
+    fn next_clause(&self, mut offset: usize) -> Option<ClauseRef> {
+        while self.arena.get(offset) == Some(&PADDING) {
+            offset += 1;
+        }
+
+        (offset < self.arena.len()).then_some(ClauseRef(offset as u32))
+    }
+
+    /// The offset in the arena right after the given clause.
+    fn end(&self, clause_ref: ClauseRef) -> usize {
+        let offset = clause_ref.0 as usize;
+        offset + HEADER_LEN + self.arena[offset + SIZE] as usize
+    }
+
+    /// Iterate over the clauses in the arena, including the deleted clauses.
+    fn clause_refs(&self) -> impl Iterator<Item = ClauseRef> + '_ {
+        let mut next = self.next_clause(0);
+
+        std::iter::from_fn(move || {
+            let clause_ref = next?;
+            next = self.next_clause(self.end(clause_ref));
+            Some(clause_ref)
+        })
+    }
+
+    /// Iterate over the clauses which have not been deleted.
+    pub fn iter(&self) -> impl Iterator<Item = (ClauseRef, &LongClause)> + '_ {
+        self.clause_refs()
+            .map(|clause_ref| (clause_ref, &self[clause_ref]))
+            .filter(|(_, clause)| !clause.is_deleted())
+    }
+
+    /// The number of learned clauses which have not been deleted.
+    pub fn num_learned_clauses(&self) -> usize {
+        self.num_learned_clauses
+    }
+
+    /// Indicate the given clause was used during conflict analysis. Has no effect on clauses that
+    /// are not learned.
+    pub fn bump_activity(&mut self, clause_ref: ClauseRef) {
+        let increment = self.activity_increment as f32;
+        let clause = &mut self[clause_ref];
+        if !clause.is_learned() {
+            return;
+        }
+
+        let activity = clause.activity() + increment;
+        clause.set_activity(activity);
+        clause.set_flag(USED, true);
+
+        if activity as f64 > MAX_ACTIVITY {
+            self.rescale_activities();
+        }
+    }
+
+    /// Decay the activities of all clauses. Should be called after every conflict.
+    pub fn decay_activities(&mut self) {
+        self.activity_increment /= self.activity_decay;
+
+        if self.activity_increment > MAX_ACTIVITY {
+            self.rescale_activities();
+        }
+    }
+
+    fn rescale_activities(&mut self) {
+        let mut next = self.next_clause(0);
+        while let Some(clause_ref) = next {
+            next = self.next_clause(self.end(clause_ref));
+
+            let clause = &mut self[clause_ref];
+            let activity = clause.activity() / MAX_ACTIVITY as f32;
+            clause.set_activity(activity);
+        }
+
+        self.activity_increment /= MAX_ACTIVITY;
+    }
+
+    /// Select the learned clauses which should be deleted in a reduction of the database. The
+    /// clauses are pushed onto `to_delete`, but are not deleted yet.
+    ///
+    /// Learned clauses are divided into three tiers based on their LBD:
+    ///  - core clauses are never deleted,
+    ///  - tier2 clauses are kept as long as they have been used since the previous reduction,
+    ///  - local clauses are sorted by activity, and the least active half is deleted.
+    ///
+    /// Clauses for which `is_locked` returns true, e.g. because they are the reason for an
+    /// assignment, are never selected.
+    pub fn select_clauses_to_delete(
+        &mut self,
+        is_locked: impl Fn(ClauseRef, &LongClause) -> bool,
+        to_delete: &mut Vec<ClauseRef>,
+    ) {
+        let mut local = vec![];
+
+        let mut next = self.next_clause(0);
+        while let Some(clause_ref) = next {
+            next = self.next_clause(self.end(clause_ref));
+
+            let clause = &mut self[clause_ref];
+            if !clause.is_learned() || clause.is_deleted() {
+                continue;
+            }
+
+            let is_used = clause.has_flag(USED);
+            clause.set_flag(USED, false);
+
+            if clause.lbd() <= CORE_LBD || (clause.lbd() <= TIER2_LBD && is_used) {
+                continue;
+            }
+
+            if !is_locked(clause_ref, clause) {
+                local.push(clause_ref);
+            }
+        }
+
+        local.sort_by(|&a, &b| {
+            let activity_a = self[a].activity();
+            let activity_b = self[b].activity();
+
+            activity_a.total_cmp(&activity_b)
+        });
+
+        to_delete.extend(local.iter().take(local.len() / 2));
+    }
+
+    /// Remove a literal from the clause, which gets the given ID. The clause should keep at least
+    /// two literals, and it should not be watched while it changes.
+    pub fn strengthen_clause(&mut self, clause_ref: ClauseRef, lit: Lit, id: ClauseId) {
+        let lits = self[clause_ref]
+            .iter()
+            .copied()
+            .filter(|&other| other != lit)
+            .collect::<Vec<_>>();
+
+        self.shorten_clause(clause_ref, &lits, id);
+    }
+
+    /// Replace the literals of the clause by a subset of them, and give it the given ID. The
+    /// clause should keep at least two literals, and it should not be watched while it changes.
+    /// The clause stays in place, and the words of the removed literals become garbage.
+    pub fn shorten_clause(&mut self, clause_ref: ClauseRef, lits: &[Lit], id: ClauseId) {
+        let clause = &mut self[clause_ref];
+        assert!(lits.len() > 1, "shortening {clause_ref:?} leaves a unit");
+        assert!(
+            lits.len() <= clause.len(),
+            "shortening {clause_ref:?} adds literals"
+        );
+
+        let removed = clause.len() - lits.len();
+        clause.lits_mut()[..lits.len()].copy_from_slice(lits);
+        clause.set_id(id);
+        clause.set_lbd(clause.lbd().min(lits.len() as u32));
+
+        let offset = clause_ref.0 as usize;
+        self.arena[offset + SIZE] = lits.len() as u32;
+
+        let end = self.end(clause_ref);
+        self.arena[end..end + removed].fill(PADDING);
+        self.garbage += removed;
+    }
+
+    /// Delete the given clause from the database. The clause reference should not be used after
+    /// this, and it should not be watched anymore.
+    pub fn delete_clause(&mut self, clause_ref: ClauseRef) {
+        let clause = &mut self[clause_ref];
+        assert!(
+            !clause.is_deleted(),
+            "clause {clause_ref:?} is deleted twice"
+        );
+
+        clause.set_flag(DELETED, true);
+        let is_learned = clause.is_learned();
+
+        self.garbage += self.end(clause_ref) - clause_ref.0 as usize;
+
+        if is_learned {
+            self.num_learned_clauses -= 1;
+        }
+    }
+
+    /// Whether enough of the arena is garbage that it should be compacted.
+    pub fn needs_garbage_collection(&self) -> bool {
+        self.garbage as f64 > MAX_GARBAGE_FRACTION * self.arena.len() as f64
+    }
+
+    /// Compact the arena by moving the clauses which are not deleted to the front, in the same
+    /// order. This invalidates every clause reference: the returned relocation maps the old
+    /// references of the remaining clauses to their new references, and has to be applied
+    /// wherever clauses are referenced.
+    pub fn collect_garbage(&mut self) -> Relocation {
+        let mut moves = vec![];
+        let mut len = 0;
+
+        let mut next = self.next_clause(0);
+        while let Some(clause_ref) = next {
+            let (start, end) = (clause_ref.0 as usize, self.end(clause_ref));
+            next = self.next_clause(end);
+
+            if self[clause_ref].is_deleted() {
+                continue;
+            }
+
+            // The clause only moves to the front, so the clauses after it are not overwritten.
+            self.arena.copy_within(start..end, len);
+            moves.push((clause_ref, ClauseRef(len as u32)));
+            len += end - start;
+        }
+
+        self.arena.truncate(len);
+        self.garbage = 0;
+
+        let relocation = Relocation { moves };
+        self.explanation_clauses = self
+            .explanation_clauses
+            .iter()
+            .filter_map(|&clause_ref| relocation.get(clause_ref))
+            .collect();
+
+        relocation
     }
 }
 
@@ -91,33 +480,169 @@ impl Index<ClauseRef> for ClauseDb {
     type Output = LongClause;
 
     fn index(&self, clause_ref: ClauseRef) -> &Self::Output {
-        &self.clauses[clause_ref.index as usize]
+        let offset = clause_ref.0 as usize;
+        LongClause::from_words(&self.arena[offset..self.end(clause_ref)])
     }
 }
 
 impl IndexMut<ClauseRef> for ClauseDb {
     fn index_mut(&mut self, clause_ref: ClauseRef) -> &mut Self::Output {
-        &mut self.clauses[clause_ref.index as usize]
+        let (offset, end) = (clause_ref.0 as usize, self.end(clause_ref));
+        LongClause::from_words_mut(&mut self.arena[offset..end])
     }
 }
 
+/// A reference to a clause in the [`ClauseDb`], which is the offset of the clause in the arena.
+/// References stay valid until the arena is compacted by [`ClauseDb::collect_garbage`].
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-pub struct ClauseRef {
-    index: u32,
-    is_learned: bool,
+pub struct ClauseRef(u32);
+
+/// The identifier of a clause in a proof. Unlike a [`ClauseRef`], an ID is never reused after the
+/// clause is deleted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClauseId(u64);
+
+impl ClauseId {
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+/// The new references of the clauses which were moved by [`ClauseDb::collect_garbage`].
+pub struct Relocation {
+    /// The old and new reference of every clause which was kept, ordered by the old reference.
+    moves: Vec<(ClauseRef, ClauseRef)>,
+}
+
+impl Relocation {
+    /// The new reference of the clause with the given old reference, or `None` if the clause was
+    /// deleted.
+    pub fn get(&self, clause_ref: ClauseRef) -> Option<ClauseRef> {
+        self.moves
+            .binary_search_by_key(&clause_ref.0, |(old, _)| old.0)
+            .ok()
+            .map(|idx| self.moves[idx].1)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::lit;
+    use crate::{lit, lit::Var};
 
     use super::*;
 
     #[test]
     fn a_long_clause_is_correctly_iterated() {
+        let mut clauses = ClauseDb::default();
         let lits = unsafe { [lit!(1), lit!(2), lit!(-3)] };
-        let clause = LongClause::new(lits);
+        let id = clauses.new_clause_id();
+        let clause_ref = clauses.add_clause(lits, id);
 
+        let clause = &clauses[clause_ref];
         assert_eq!(lits.to_vec(), clause.iter().copied().collect::<Vec<_>>());
+        assert_eq!(id, clause.id());
+        assert!(!clause.is_learned());
+    }
+
+    #[test]
+    fn shortened_clauses_are_skipped_when_iterating() {
+        let mut clauses = ClauseDb::default();
+
+        let first = clauses.add_learned_clause(unsafe { [lit!(1), lit!(2), lit!(3), lit!(4)] }, 4);
+        let second = clauses.add_learned_clause(unsafe { [lit!(5), lit!(6)] }, 2);
+
+        let id = clauses.new_clause_id();
+        clauses.shorten_clause(first, unsafe { &[lit!(4), lit!(1)] }, id);
+        assert_eq!(unsafe { [lit!(4), lit!(1)] }, clauses[first].lits());
+        assert_eq!(id, clauses[first].id());
+        assert_eq!(2, clauses[first].lbd());
+
+        let refs = clauses
+            .iter()
+            .map(|(clause_ref, _)| clause_ref)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![first, second], refs);
+    }
+    #[test]
+    fn reduction_keeps_core_and_used_tier2_clauses() {
+        let mut clauses = ClauseDb::default();
+
+        let core = clauses.add_learned_clause(unsafe { [lit!(1), lit!(2)] }, 2);
+        let tier2 = clauses.add_learned_clause(unsafe { [lit!(1), lit!(3)] }, 5);
+        let local = clauses.add_learned_clause(unsafe { [lit!(1), lit!(4)] }, 8);
+        clauses.bump_activity(tier2);
+
+        let mut to_delete = vec![];
+        clauses.select_clauses_to_delete(|_, _| false, &mut to_delete);
+        assert!(to_delete.is_empty());
+
+        clauses.add_learned_clause(unsafe { [lit!(1), lit!(5)] }, 8);
+
+        // The tier2 clause is not used since the previous reduction, so it becomes a candidate.
+        clauses.select_clauses_to_delete(|_, _| false, &mut to_delete);
+        assert!(!to_delete.contains(&core));
+        assert_eq!(1, to_delete.len());
+        assert!(to_delete.contains(&tier2) || to_delete.contains(&local));
+    }
+
+    #[test]
+    fn reduction_deletes_least_active_unlocked_local_clauses() {
+        let mut clauses = ClauseDb::default();
+
+        let refs = (0..4)
+            .map(|code| unsafe {
+                clauses.add_learned_clause([Lit::positive(Var::new_unchecked(code)), lit!(10)], 10)
+            })
+            .collect::<Vec<_>>();
+
+        // Bump in increasing order, so the later clauses are more active.
+        for (i, &clause_ref) in refs.iter().enumerate() {
+            for _ in 0..i {
+                clauses.bump_activity(clause_ref);
+            }
+        }
+
+        let mut to_delete = vec![];
+        clauses.select_clauses_to_delete(|clause_ref, _| clause_ref == refs[0], &mut to_delete);
+
+        assert_eq!(vec![refs[1]], to_delete);
+    }
+
+    #[test]
+    fn garbage_collection_compacts_the_remaining_clauses() {
+        let mut clauses = ClauseDb::default();
+
+        let first = clauses.add_learned_clause(unsafe { [lit!(1), lit!(2), lit!(3)] }, 3);
+        let deleted = clauses.add_learned_clause(unsafe { [lit!(4), lit!(5), lit!(6)] }, 3);
+        let id = clauses.new_clause_id();
+        let last = clauses.add_clause(unsafe { [lit!(7), lit!(8)] }, id);
+        let also_deleted = clauses.add_learned_clause(unsafe { [lit!(9), lit!(10)] }, 2);
+
+        clauses.delete_clause(deleted);
+        clauses.delete_clause(also_deleted);
+        assert_eq!(1, clauses.num_learned_clauses());
+
+        let id = clauses.new_clause_id();
+        clauses.shorten_clause(first, unsafe { &[lit!(1), lit!(3)] }, id);
+        assert!(clauses.needs_garbage_collection());
+
+        let relocation = clauses.collect_garbage();
+        assert!(!clauses.needs_garbage_collection());
+        assert_eq!(None, relocation.get(deleted));
+        assert_eq!(None, relocation.get(also_deleted));
+
+        let first = relocation.get(first).unwrap();
+        let last = relocation.get(last).unwrap();
+        assert_eq!(unsafe { [lit!(1), lit!(3)] }, clauses[first].lits());
+        assert_eq!(id, clauses[first].id());
+        assert!(clauses[first].is_learned());
+        assert_eq!(unsafe { [lit!(7), lit!(8)] }, clauses[last].lits());
+        assert!(!clauses[last].is_learned());
+
+        let refs = clauses
+            .iter()
+            .map(|(clause_ref, _)| clause_ref)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![first, last], refs);
     }
 }
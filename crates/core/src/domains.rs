@@ -8,7 +8,7 @@ use std::{
 use crate::{
     clause::{ClauseDb, ClauseRef},
     lit::Lit,
-    propagation::Explanation,
+    propagation::{Explanation, PropagatorId},
     solver::ExtendClausalSolver,
     storage::Indexer,
 };
@@ -135,6 +135,8 @@ pub enum Conflict<Domains> {
     Propagator {
         lit: Lit,
         explanation: Explanation<Domains>,
+        /// The propagator which found the conflict.
+        propagator: PropagatorId,
     },
 }
 
@@ -146,7 +148,9 @@ impl<Domains> Conflict<Domains> {
     ) -> Cow<'clauses, [Lit]> {
         match self {
             Conflict::Clause(clause_ref) => Cow::Borrowed(clauses[*clause_ref].lits()),
-            Conflict::Propagator { lit, explanation } => {
+            Conflict::Propagator {
+                lit, explanation, ..
+            } => {
                 let mut clause = vec![*lit];
                 clause.extend(explanation.iter().map(|atom| !atom.as_lit(domains)));
 
@@ -168,10 +172,15 @@ impl<Domains> Debug for Conflict<Domains> {
 
         match self {
             Conflict::Clause(clause_ref) => write!(f, "Clause({clause_ref:?})"),
-            Conflict::Propagator { lit, explanation } => {
+            Conflict::Propagator {
+                lit,
+                explanation,
+                propagator,
+            } => {
                 write!(
                     f,
-                    "Propagator {{ lit: {lit:?}, explanation: {explanation:?} }}"
+                    "Propagator {{ lit: {lit:?}, explanation: {explanation:?}, propagator: \
+                     {propagator:?} }}"
                 )
             }
         }
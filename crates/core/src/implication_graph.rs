@@ -1,4 +1,4 @@
-use crate::{lit::Var, propagation::Reason, storage::KeyedVec};
+use crate::{clause::Relocation, lit::Var, propagation::Reason, storage::KeyedVec};
 
 #[derive(Default)]
 pub struct ImplicationGraph<Domains> {
@@ -19,4 +19,27 @@ impl<Domains> ImplicationGraph<Domains> {
     pub fn add(&mut self, var: Var, reason: Reason<Domains>) {
         self.reasons[var] = reason;
     }
+
+    /// Update the reasons of the given variables to the references the clauses got when the
+    /// clause database was compacted. A reason which was deleted can only be the reason for an
+    /// assignment at the root, which does not need to be explained, so it becomes a decision.
+    pub fn relocate_clauses(
+        &mut self,
+        vars: impl IntoIterator<Item = Var>,
+        relocation: &Relocation,
+    ) {
+        for var in vars {
+            let reason = &mut self.reasons[var];
+
+            match reason {
+                Reason::Clause(clause_ref) | Reason::BinaryClause { clause_ref, .. } => {
+                    match relocation.get(*clause_ref) {
+                        Some(new_ref) => *clause_ref = new_ref,
+                        None => *reason = Reason::Decision,
+                    }
+                }
+                Reason::Decision | Reason::Explanation { .. } => {}
+            }
+        }
+    }
 }
@@ -1,5 +1,6 @@
 use crate::{
     domains::Conflict,
+    proof::PbConstraint,
     propagation::{Context, Explanation},
     variable::Variable, atom::Atom, 
 };
@@ -82,6 +83,17 @@ where
         }
     }
 
+    fn add_to_constraint(
+        &self,
+        ctx: &mut Context<Domains, Event>,
+        coefficient: i64,
+        constraint: &mut PbConstraint,
+    ) -> Option<()> {
+        constraint.add_constant(coefficient * self.offset as i64);
+        self.inner
+            .add_to_constraint(ctx, coefficient * self.scale as i64, constraint)
+    }
+
     fn set_min(
         &self,
         ctx: &mut Context<Domains, Event>,
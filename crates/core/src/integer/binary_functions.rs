@@ -2,6 +2,7 @@
 
 use crate::{
     domains::{Conflict, Domain},
+    proof::PbConstraint,
     propagation::{Context, Explanation},
     variable::Variable, atom::Atom,
 };
@@ -64,6 +65,16 @@ where
         todo!()
     }
 
+    fn add_to_constraint(
+        &self,
+        ctx: &mut Context<Domains, Event>,
+        coefficient: i64,
+        constraint: &mut PbConstraint,
+    ) -> Option<()> {
+        // The minimum of two variables is not linear in their literals.
+        None
+    }
+
     fn set_min(
         &self,
         ctx: &mut Context<Domains, Event>,
@@ -0,0 +1,334 @@
+use crate::{
+    assignment::Assignment,
+    brancher::Brancher,
+    lit::{Lit, Var},
+    storage::KeyedVec,
+    trail::Trail,
+};
+
+use super::{BoundedInt, Int};
+
+/// How the next domain to branch on is selected among the domains which are not fixed. Ties are
+/// broken by the order in which the domains were given.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VariableSelection {
+    /// The first domain.
+    #[default]
+    InputOrder,
+    /// The domain with the fewest values.
+    FirstFail,
+    /// The domain with the smallest lower bound.
+    Smallest,
+    /// The domain with the largest upper bound.
+    Largest,
+    /// The domain with the fewest values relative to its weight, see [`ConflictWeights`].
+    DomWDeg,
+}
+
+impl VariableSelection {
+    /// The score of a domain with the given bounds, and the given weight for
+    /// [`VariableSelection::DomWDeg`]. The domain with the smallest score is selected.
+    pub fn score(self, min: Int, max: Int, weight: u64) -> f64 {
+        let size = max as f64 - min as f64 + 1.0;
+
+        match self {
+            VariableSelection::InputOrder => 0.0,
+            VariableSelection::FirstFail => size,
+            VariableSelection::Smallest => min as f64,
+            VariableSelection::Largest => -(max as f64),
+            VariableSelection::DomWDeg => size / weight as f64,
+        }
+    }
+}
+
+/// Which values are tried for the selected domain.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ValueSelection {
+    /// Assign the lower bound.
+    #[default]
+    IndomainMin,
+    /// Assign the upper bound.
+    IndomainMax,
+    /// Exclude the upper half of the domain.
+    IndomainSplit,
+    /// Assign the middle value of the domain. For an even number of values, this is the smaller of
+    /// the two middle values.
+    IndomainMedian,
+}
+
+/// The weights of a number of domains in [`VariableSelection::DomWDeg`]. The weight of a domain is
+/// one plus the number of conflicts in which a variable of the domain was seen during conflict
+/// analysis.
+pub struct ConflictWeights {
+    /// The index of the domain of every variable, if any.
+    domain_of: KeyedVec<Var, Option<usize>>,
+    weights: Box<[u64]>,
+    /// The domains whose weight is increased at the end of the current conflict analysis, so they
+    /// are increased only once per conflict.
+    weighted: Vec<usize>,
+    is_weighted: Box<[bool]>,
+}
+
+impl ConflictWeights {
+    /// Create the weights for domains with the given variables.
+    pub fn new<Vars: IntoIterator<Item = Var>>(domains: impl IntoIterator<Item = Vars>) -> Self {
+        let mut domain_of = KeyedVec::default();
+        let mut num_domains = 0;
+
+        for (idx, vars) in domains.into_iter().enumerate() {
+            for var in vars {
+                domain_of.grow_to(var);
+                domain_of[var] = Some(idx);
+            }
+
+            num_domains += 1;
+        }
+
+        ConflictWeights {
+            domain_of,
+            weights: vec![1; num_domains].into(),
+            weighted: vec![],
+            is_weighted: vec![false; num_domains].into(),
+        }
+    }
+
+    pub fn grow_to(&mut self, var: Var) {
+        self.domain_of.grow_to(var);
+    }
+
+    pub fn weight(&self, domain: usize) -> u64 {
+        self.weights[domain]
+    }
+
+    pub fn on_variable_activated(&mut self, var: Var) {
+        if let Some(idx) = self.domain_of[var] {
+            if !self.is_weighted[idx] {
+                self.is_weighted[idx] = true;
+                self.weighted.push(idx);
+            }
+        }
+    }
+
+    pub fn on_conflict(&mut self) {
+        for idx in self.weighted.drain(..) {
+            self.weights[idx] += 1;
+            self.is_weighted[idx] = false;
+        }
+    }
+}
+
+/// Branches on integer domains rather than on individual literals. The decisions are the bound
+/// literals of the domains, so a single decision can fix a domain or halve it. Once every domain
+/// is fixed no decision is returned, even if literals outside the domains are unassigned. The
+/// brancher therefore has to be followed by one which decides on the remaining literals, unless
+/// the domains are all there is to decide on.
+pub struct DomainBrancher<Dom> {
+    domains: Box<[Dom]>,
+    variable_selection: VariableSelection,
+    value_selection: ValueSelection,
+    weights: ConflictWeights,
+
+    /// The decision which completes assigning a domain to a value, which takes two decisions.
+    pending_decision: Option<Lit>,
+}
+
+impl<Dom: BoundedInt> DomainBrancher<Dom> {
+    pub fn new(
+        domains: impl IntoIterator<Item = Dom>,
+        variable_selection: VariableSelection,
+        value_selection: ValueSelection,
+    ) -> Self {
+        let domains = domains.into_iter().collect::<Box<[_]>>();
+        let weights = ConflictWeights::new(
+            domains
+                .iter()
+                .map(|domain| domain.order_encoding().1.iter().map(|lit| lit.var())),
+        );
+
+        DomainBrancher {
+            domains,
+            variable_selection,
+            value_selection,
+            weights,
+            pending_decision: None,
+        }
+    }
+
+    /// The index of the domain to branch on, or `None` if every domain is fixed.
+    fn select_domain(&self, assignment: &Assignment) -> Option<usize> {
+        let mut selected: Option<(usize, f64)> = None;
+
+        for (idx, domain) in self.domains.iter().enumerate() {
+            let (min, max) = (domain.min(assignment), domain.max(assignment));
+            if min == max {
+                continue;
+            }
+
+            if self.variable_selection == VariableSelection::InputOrder {
+                return Some(idx);
+            }
+
+            let score = self
+                .variable_selection
+                .score(min, max, self.weights.weight(idx));
+            if selected.is_none_or(|(_, best)| score < best) {
+                selected = Some((idx, score));
+            }
+        }
+
+        selected.map(|(idx, _)| idx)
+    }
+}
+
+impl<Dom: BoundedInt> Brancher for DomainBrancher<Dom> {
+    fn initialize(&mut self, largest_var: Var) {
+        self.weights.grow_to(largest_var);
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        self.weights.on_variable_activated(var);
+    }
+
+    fn on_conflict(&mut self) {
+        self.weights.on_conflict();
+    }
+
+    fn on_backtrack(&mut self, _: &Trail) {
+        self.pending_decision = None;
+    }
+
+    fn on_variable_unassigned(&mut self, _: Lit) {}
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        if let Some(lit) = self.pending_decision.take() {
+            if assignment.is_unassigned(lit) {
+                return Some(lit);
+            }
+        }
+
+        let domain = &self.domains[self.select_domain(assignment)?];
+        let (min, max) = (domain.min(assignment), domain.max(assignment));
+        // The middle of the domain, rounded down. It is smaller than the upper bound.
+        let middle = (min as i64 + (max as i64 - min as i64) / 2) as Int;
+
+        let decision = match self.value_selection {
+            ValueSelection::IndomainMin => domain.upper_bound_lit(min),
+            ValueSelection::IndomainMax => domain.lower_bound_lit(max),
+            ValueSelection::IndomainSplit => domain.upper_bound_lit(middle),
+            ValueSelection::IndomainMedian if middle == min => domain.upper_bound_lit(min),
+            ValueSelection::IndomainMedian => {
+                self.pending_decision = Some(domain.upper_bound_lit(middle));
+                domain.lower_bound_lit(middle)
+            }
+        };
+
+        Some(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domains::TypedDomainStore,
+        integer::{interval_domain::IntInterval, IntEvent},
+        restart::NeverRestart,
+        solver::{SolveResult, Solver},
+        termination::Indefinite,
+    };
+
+    use super::*;
+
+    type IntSolver = Solver<TypedDomainStore<IntInterval>, IntEvent>;
+
+    #[test]
+    fn value_selection_determines_the_assigned_value() {
+        for (value_selection, expected) in [
+            (ValueSelection::IndomainMin, -2),
+            (ValueSelection::IndomainMax, 3),
+            (ValueSelection::IndomainSplit, -2),
+            (ValueSelection::IndomainMedian, 0),
+        ] {
+            let mut solver = IntSolver::default();
+            let x = solver.new_domain(IntInterval::factory(-2, 3));
+
+            let brancher = DomainBrancher::new(
+                [solver.domain(x.clone()).clone()],
+                VariableSelection::InputOrder,
+                value_selection,
+            );
+
+            match solver.solve(Indefinite, brancher, NeverRestart) {
+                SolveResult::Satisfiable(solution) => {
+                    assert_eq!(expected, solution.domain_value(x), "{value_selection:?}");
+                }
+                _ => panic!("expected a solution"),
+            }
+        }
+    }
+
+    #[test]
+    fn variable_selection_determines_the_selected_domain() {
+        let mut solver = IntSolver::default();
+        let x = solver.new_domain(IntInterval::factory(0, 5));
+        let y = solver.new_domain(IntInterval::factory(-2, 0));
+        let domains = [x, y].map(|id| solver.domain(id).clone());
+
+        // The literal `[y >= 1]` is the last literal of the domains.
+        let mut assignment = Assignment::default();
+        assignment.grow_to(domains[1].upper_bound_lit(0).var());
+
+        for (variable_selection, expected) in [
+            (VariableSelection::InputOrder, 0),
+            (VariableSelection::FirstFail, 1),
+            (VariableSelection::Smallest, 1),
+            (VariableSelection::Largest, 0),
+        ] {
+            let mut brancher = DomainBrancher::new(
+                domains.clone(),
+                variable_selection,
+                ValueSelection::default(),
+            );
+
+            assert_eq!(
+                Some(domains[expected].upper_bound_lit(domains[expected].min(&assignment))),
+                brancher.next_decision(&assignment),
+                "{variable_selection:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn dom_wdeg_prefers_domains_in_conflicts() {
+        let mut solver = IntSolver::default();
+        let x = solver.new_domain(IntInterval::factory(0, 5));
+        let y = solver.new_domain(IntInterval::factory(0, 1));
+        let domains = [x, y].map(|id| solver.domain(id).clone());
+
+        // The literal `[y >= 2]` is the last literal of the domains.
+        let mut assignment = Assignment::default();
+        assignment.grow_to(domains[1].upper_bound_lit(1).var());
+
+        let mut brancher = DomainBrancher::new(
+            domains.clone(),
+            VariableSelection::DomWDeg,
+            ValueSelection::default(),
+        );
+        assert_eq!(
+            Some(domains[1].upper_bound_lit(0)),
+            brancher.next_decision(&assignment)
+        );
+
+        // Every conflict only counts once for a domain.
+        let (_, lits) = domains[0].order_encoding();
+        for _ in 0..3 {
+            brancher.on_variable_activated(lits[0].var());
+            brancher.on_variable_activated(lits[1].var());
+            brancher.on_conflict();
+        }
+
+        assert_eq!(
+            Some(domains[0].upper_bound_lit(0)),
+            brancher.next_decision(&assignment)
+        );
+    }
+}
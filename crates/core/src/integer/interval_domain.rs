@@ -1,4 +1,5 @@
 use crate::{
+    assignment::Assignment,
     domains::{Conflict, Domain, DomainFactory, EnqueueDomainLit},
     lit::Lit,
     propagation::Explanation,
@@ -9,10 +10,17 @@ use super::{BoundedInt, Int, IntEvent};
 
 /// An integer domain defined by an upper and lower bound. This domain does not support removing
 /// individual values. Only operations on the bounds of the domain are supported.
+///
+/// The domain is order encoded: for every value `v` there is a literal `[x >= v]`. The current
+/// bounds follow from which of these literals are assigned, so they are restored when the solver
+/// backtracks.
+#[derive(Clone)]
 pub struct IntInterval {
+    /// The lower bound the domain was created with.
     lower_bound: Int,
-    upper_bound: Int,
 
+    /// The literal `[x >= lower_bound + i]` at index `i`, up to and including the literal for
+    /// `upper_bound + 1`.
     literals: Box<[Lit]>,
 }
 
@@ -24,14 +32,13 @@ impl IntInterval {
         }
     }
 
-    /// Get the literal corresponding to the atomic constraint `self <= value`.
+    /// Get the literal corresponding to the atomic constraint `self >= value`. Values outside the
+    /// domain map to the literal of the nearest bound, which is fixed at the root.
     #[inline]
     fn literal(&self, value: Int) -> Lit {
-        let idx = value
-            .abs_diff(self.lower_bound)
-            .min(self.literals.len() as u32 - 1) as usize;
+        let idx = (value as i64 - self.lower_bound as i64).clamp(0, self.literals.len() as i64 - 1);
 
-        self.literals[idx]
+        self.literals[idx as usize]
     }
 }
 
@@ -40,7 +47,10 @@ pub struct IntIntervalFactory {
     upper_bound: Int,
 }
 
-impl<Event> DomainFactory<Event> for IntIntervalFactory {
+impl<Event> DomainFactory<Event> for IntIntervalFactory
+where
+    Event: From<IntEvent>,
+{
     type Domain = IntInterval;
 
     fn create(self, clausal_solver: &mut impl ExtendClausalSolver<Event>) -> Self::Domain {
@@ -50,7 +60,6 @@ impl<Event> DomainFactory<Event> for IntIntervalFactory {
             .collect::<Box<[_]>>();
         let domain = IntInterval {
             lower_bound: self.lower_bound,
-            upper_bound: self.upper_bound,
             literals: lb_lits,
         };
 
@@ -65,6 +74,12 @@ impl<Event> DomainFactory<Event> for IntIntervalFactory {
         // [x >= lower_bound]
         clausal_solver.add_clause([domain.literal(self.lower_bound)]);
 
+        // A domain watch is triggered when the watched literal becomes false.
+        for v in (self.lower_bound + 1)..=self.upper_bound {
+            clausal_solver.add_domain_watch(!domain.literal(v), IntEvent::LowerBound.into());
+            clausal_solver.add_domain_watch(domain.literal(v), IntEvent::UpperBound.into());
+        }
+
         domain
     }
 }
@@ -74,12 +89,41 @@ impl Domain for IntInterval {
 }
 
 impl BoundedInt for IntInterval {
-    fn max(&self) -> Int {
-        self.upper_bound
+    fn max(&self, assignment: &Assignment) -> Int {
+        // The upper bound is one less than the value of the first false literal. The last literal
+        // is false at the root.
+        let (mut low, mut high) = (0, self.literals.len() - 1);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if assignment.value(self.literals[mid]) == Some(false) {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+
+        self.lower_bound + high as Int - 1
     }
 
-    fn min(&self) -> Int {
-        self.lower_bound
+    fn min(&self, assignment: &Assignment) -> Int {
+        // The lower bound is the value of the last true literal. The first literal is true at the
+        // root.
+        let (mut low, mut high) = (0, self.literals.len() - 1);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if assignment.value(self.literals[mid]) == Some(true) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        self.lower_bound + low as Int
+    }
+
+    fn order_encoding(&self) -> (Int, &[Lit]) {
+        // The first literal is true and the last literal is false at the root.
+        (self.lower_bound, &self.literals[1..self.literals.len() - 1])
     }
 
     fn upper_bound_lit(&self, bound: Int) -> Lit {
@@ -96,14 +140,7 @@ impl BoundedInt for IntInterval {
         explanation: Explanation<Domains>,
         mut enqueue_lit: impl EnqueueDomainLit<Domains>,
     ) -> Result<(), Conflict<Domains>> {
-        if bound > self.lower_bound {
-            enqueue_lit.enqueue(self.literal(bound), explanation)?;
-            self.lower_bound = bound;
-        }
-
-        assert!(self.lower_bound <= self.upper_bound);
-
-        Ok(())
+        enqueue_lit.enqueue(self.literal(bound), explanation)
     }
 
     fn set_max<Domains>(
@@ -112,13 +149,132 @@ impl BoundedInt for IntInterval {
         explanation: Explanation<Domains>,
         mut enqueue_lit: impl EnqueueDomainLit<Domains>,
     ) -> Result<(), Conflict<Domains>> {
-        if bound < self.upper_bound {
-            enqueue_lit.enqueue(!self.literal(bound + 1), explanation)?;
-            self.upper_bound = bound;
+        enqueue_lit.enqueue(!self.literal(bound + 1), explanation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lit::Var;
+
+    use super::*;
+
+    /// Records the domain watches of the domains it creates.
+    #[derive(Default)]
+    struct WatchRecorder {
+        num_lits: u32,
+        watches: Vec<(Lit, IntEvent)>,
+    }
+
+    impl ExtendClausalSolver<IntEvent> for WatchRecorder {
+        type NewLits<'a> = Box<dyn Iterator<Item = Lit> + 'a>;
+
+        fn new_lits(&mut self) -> Self::NewLits<'_> {
+            Box::new(std::iter::repeat_with(|| {
+                self.num_lits += 1;
+                Lit::positive(Var::try_from(self.num_lits - 1).unwrap())
+            }))
+        }
+
+        fn add_clause(&mut self, _: impl IntoIterator<Item = Lit>) {}
+
+        fn add_domain_watch(&mut self, lit: Lit, event: IntEvent) {
+            self.watches.push((lit, event));
+        }
+    }
+
+    fn domain(lower_bound: Int, upper_bound: Int) -> IntInterval {
+        let num_literals = upper_bound.abs_diff(lower_bound) + 2;
+
+        IntInterval {
+            lower_bound,
+            literals: (0..num_literals)
+                .map(|code| Lit::positive(Var::try_from(code).unwrap()))
+                .collect(),
         }
+    }
+
+    #[test]
+    fn values_outside_the_domain_map_to_the_literals_of_the_bounds() {
+        let domain = domain(-2, 3);
+
+        assert_eq!(domain.literals[0], domain.lower_bound_lit(-3));
+        assert_eq!(domain.literals[0], domain.lower_bound_lit(-10));
+        assert_eq!(domain.literals[6], !domain.upper_bound_lit(4));
+        assert_eq!(domain.literals[6], !domain.upper_bound_lit(10));
+    }
+
+    #[test]
+    fn values_inside_the_domain_map_to_their_own_literals() {
+        let domain = domain(-2, 3);
+
+        for (idx, value) in (-2..=3).enumerate() {
+            assert_eq!(domain.literals[idx], domain.lower_bound_lit(value));
+            assert_eq!(domain.literals[idx + 1], !domain.upper_bound_lit(value));
+        }
+    }
+
+    #[test]
+    fn tightened_bounds_raise_events() {
+        let mut recorder = WatchRecorder::default();
+        let domain = IntInterval::factory(0, 3).create(&mut recorder);
 
-        assert!(self.lower_bound <= self.upper_bound);
+        // The watches are on the negations of the bound literals, as they are triggered when the
+        // watched literal becomes false.
+        for value in 1..=3 {
+            let lower_bound = (!domain.lower_bound_lit(value), IntEvent::LowerBound);
+            assert!(recorder.watches.contains(&lower_bound));
 
-        Ok(())
+            let upper_bound = (!domain.upper_bound_lit(value - 1), IntEvent::UpperBound);
+            assert!(recorder.watches.contains(&upper_bound));
+        }
+    }
+
+    #[test]
+    fn bounds_follow_the_assignment() {
+        let domain = domain(0, 3);
+        let mut assignment = Assignment::default();
+        assignment.grow_to(domain.literals[4].var());
+        assert_eq!((0, 3), (domain.min(&assignment), domain.max(&assignment)));
+
+        // 1 <= x <= 2
+        let lits = [
+            domain.literals[0],
+            domain.literals[1],
+            !domain.literals[3],
+            !domain.literals[4],
+        ];
+        for lit in lits {
+            assignment.assign(lit);
+        }
+        assert_eq!((1, 2), (domain.min(&assignment), domain.max(&assignment)));
+
+        // Backtracking restores the bounds.
+        for lit in lits {
+            assignment.unassign(lit);
+        }
+        assert_eq!((0, 3), (domain.min(&assignment), domain.max(&assignment)));
+    }
+
+    #[test]
+    fn bounds_are_found_in_any_domain_size() {
+        for upper_bound in 0..20 {
+            let domain = domain(-5, upper_bound);
+            let last_var = domain.literals.last().unwrap().var();
+
+            let mut assignment = Assignment::default();
+            assignment.grow_to(last_var);
+            for value in -5..=upper_bound {
+                assignment.assign(domain.lower_bound_lit(value));
+                assert_eq!(value, domain.min(&assignment));
+            }
+
+            let mut assignment = Assignment::default();
+            assignment.grow_to(last_var);
+            for value in (-5..=upper_bound).rev() {
+                assignment.assign(domain.upper_bound_lit(value));
+                assert_eq!(value, domain.max(&assignment));
+            }
+        }
     }
 }
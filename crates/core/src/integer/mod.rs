@@ -1,13 +1,17 @@
 pub mod affine_view;
 pub mod atoms;
 mod binary_functions;
+pub mod brancher;
 pub mod interval_domain;
 
 use crate::{
+    assignment::Assignment,
     atom::Atom,
     domains::{Conflict, Domain, DomainId, DomainStore, EnqueueDomainLit},
     lit::Lit,
-    propagation::{Context, Explanation},
+    proof::PbConstraint,
+    propagation::{Context, Explanation, SDomainEvent},
+    storage::{Indexer, StaticIndexer},
     variable::Variable,
 };
 
@@ -22,12 +26,39 @@ pub enum IntEvent {
     UpperBound,
 }
 
+/// Solvers whose propagators only watch integer domains can use the integer events as their
+/// events.
+impl Indexer for IntEvent {
+    fn index(&self) -> usize {
+        match self {
+            IntEvent::LowerBound => 0,
+            IntEvent::UpperBound => 1,
+        }
+    }
+}
+
+impl StaticIndexer for IntEvent {
+    fn get_len() -> usize {
+        2
+    }
+}
+
+impl SDomainEvent<IntEvent> for IntEvent {
+    fn is(self, event: IntEvent) -> bool {
+        self == event
+    }
+}
+
 pub trait BoundedInt: Domain<ProducedEvent = IntEvent> {
-    /// Get the upper bound of the domain.
-    fn max(&self) -> Int;
+    /// Get the upper bound of the domain under the given assignment.
+    fn max(&self, assignment: &Assignment) -> Int;
 
-    /// Get the lower bound of the domain.
-    fn min(&self) -> Int;
+    /// Get the lower bound of the domain under the given assignment.
+    fn min(&self, assignment: &Assignment) -> Int;
+
+    /// Get the literals which encode the domain. The value of the domain is the returned offset
+    /// plus the number of the returned literals which are true.
+    fn order_encoding(&self) -> (Int, &[Lit]);
 
     /// Get the literal that asserts the given upper bound for this domain.
     fn upper_bound_lit(&self, bound: Int) -> Lit;
@@ -67,6 +98,16 @@ pub trait BoundedIntVar<Domains, Event>: Variable {
     /// Get the atom asserting the given bound as the lower bound of this variable.
     fn lower_bound_atom(&self, bound: Int) -> Box<dyn Atom<Domains>>;
 
+    /// Add the variable multiplied by the coefficient to the left-hand side of the constraint,
+    /// expressed in the literals which encode the variable. Returns `None` if the variable is not
+    /// a linear expression of its literals, in which case the constraint has to be discarded.
+    fn add_to_constraint(
+        &self,
+        ctx: &mut Context<Domains, Event>,
+        coefficient: i64,
+        constraint: &mut PbConstraint,
+    ) -> Option<()>;
+
     /// Tighten the lower bound of the domain to the new bound. If the given bound is smaller than
     /// the current lower bound, this is a no-op.
     fn set_min(
@@ -92,11 +133,11 @@ where
     Domains: DomainStore<Dom>,
 {
     fn max(&self, ctx: &mut Context<Domains, Event>) -> Int {
-        ctx.read(self.clone()).max()
+        ctx.read(self.clone()).max(ctx.assignment())
     }
 
     fn min(&self, ctx: &mut Context<Domains, Event>) -> Int {
-        ctx.read(self.clone()).min()
+        ctx.read(self.clone()).min(ctx.assignment())
     }
 
     fn upper_bound_atom(&self, bound: Int) -> Box<dyn Atom<Domains>> {
@@ -107,6 +148,18 @@ where
         Box::new(AtLeast { domain: self.clone(), bound })
     }
 
+    fn add_to_constraint(
+        &self,
+        ctx: &mut Context<Domains, Event>,
+        coefficient: i64,
+        constraint: &mut PbConstraint,
+    ) -> Option<()> {
+        let (offset, lits) = ctx.read(self.clone()).order_encoding();
+        constraint.add_order_encoding(coefficient, offset as i64, lits);
+
+        Some(())
+    }
+
     fn set_min(
         &self,
         ctx: &mut Context<Domains, Event>,
@@ -10,11 +10,22 @@ pub mod domains;
 pub mod implication_graph;
 pub mod integer;
 pub mod lit;
+pub mod local_search;
+pub mod mode;
+pub mod phase;
 pub mod preprocessor;
+pub mod proof;
 pub mod propagation;
+pub mod random;
+pub mod restart;
 pub mod search_tree;
+pub mod simplifier;
 pub mod solver;
+pub mod statistics;
 pub mod storage;
+pub mod subsumption;
 pub mod termination;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod trail;
 pub mod variable;
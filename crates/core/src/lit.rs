@@ -88,6 +88,12 @@ impl Lit {
     pub fn var(self) -> Var {
         Var(self.0 >> 1)
     }
+
+    /// The code of the literal: twice the code of its variable, plus one if it is positive.
+    #[inline]
+    pub fn code(self) -> u32 {
+        self.0
+    }
 }
 
 impl Debug for Lit {
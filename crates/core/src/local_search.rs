@@ -0,0 +1,305 @@
+use crate::{
+    lit::{Lit, Var},
+    random::Random,
+    storage::KeyedVec,
+};
+
+/// The constant added to the break count of a variable when computing how likely it is to be
+/// flipped. The constants are those of ProbSAT for 3-SAT.
+const EPSILON: f64 = 0.9;
+/// The exponent of the break count of a variable when computing how likely it is to be flipped.
+const BREAK_EXPONENT: f64 = 2.06;
+/// The largest break count with its own flip probability. Larger break counts are so unlikely to
+/// be picked that they are treated the same.
+const MAX_BREAK_COUNT: usize = 64;
+
+/// A stochastic local search for an assignment which satisfies a set of clauses, following ProbSAT
+/// by Balint and Schöning (SAT 2012). Starting from a complete assignment, it repeatedly picks a
+/// random falsified clause and flips one of its variables. The fewer satisfied clauses a flip would
+/// falsify, the more likely the variable is to be picked.
+pub struct LocalSearch {
+    /// The literals of all clauses, one clause after the other.
+    lits: Vec<Lit>,
+    /// The index in `lits` at which every clause starts, followed by the number of literals.
+    starts: Vec<usize>,
+    /// The clauses in which every literal occurs.
+    occurrences: KeyedVec<Lit, Vec<u32>>,
+
+    values: KeyedVec<Var, bool>,
+    /// The number of true literals in every clause.
+    num_true: Vec<u32>,
+    /// The clauses without a true literal.
+    falsified: Vec<u32>,
+    /// The position of every clause in `falsified`, if it is falsified.
+    falsified_idx: Vec<Option<u32>>,
+
+    /// The variables flipped since the assignment with the fewest falsified clauses, which is
+    /// restored by undoing these flips.
+    flips_since_best: Vec<Var>,
+    /// The weight of every break count in the choice of the variable to flip.
+    break_weights: Box<[f64]>,
+    /// The weights of the literals of the clause being repaired.
+    weights: Vec<f64>,
+    random: Random,
+}
+
+impl LocalSearch {
+    pub fn new(seed: u64) -> Self {
+        LocalSearch {
+            lits: vec![],
+            starts: vec![0],
+            occurrences: Default::default(),
+            values: Default::default(),
+            num_true: vec![],
+            falsified: vec![],
+            falsified_idx: vec![],
+            flips_since_best: vec![],
+            break_weights: (0..=MAX_BREAK_COUNT)
+                .map(|count| (EPSILON + count as f64).powf(-BREAK_EXPONENT))
+                .collect(),
+            weights: vec![],
+            random: Random::new(seed),
+        }
+    }
+
+    pub fn grow_to(&mut self, var: Var) {
+        self.occurrences.grow_to(Lit::positive(var));
+        self.occurrences.grow_to(Lit::negative(var));
+        self.values.grow_to(var);
+    }
+
+    /// Add a clause which has to be satisfied. The variables of its literals should have been
+    /// passed to [`LocalSearch::grow_to`].
+    pub fn add_clause(&mut self, lits: &[Lit]) {
+        let clause = self.num_clauses() as u32;
+
+        for &lit in lits {
+            self.occurrences[lit].push(clause);
+        }
+
+        self.lits.extend_from_slice(lits);
+        self.starts.push(self.lits.len());
+    }
+
+    pub fn num_clauses(&self) -> usize {
+        self.starts.len() - 1
+    }
+
+    /// The value of a variable in the best assignment found by the last call to
+    /// [`LocalSearch::run`], or its initial value before that.
+    pub fn value(&self, var: Var) -> bool {
+        self.values[var]
+    }
+
+    /// Search for at most `max_flips` flips, starting from the given values of the variables.
+    /// Afterwards the variables have the values in the assignment with the fewest falsified clauses
+    /// that was found. Returns the number of flips, and whether that assignment satisfies every
+    /// clause.
+    pub fn run(&mut self, initial_value: impl Fn(Var) -> bool, max_flips: u64) -> (u64, bool) {
+        for (code, value) in self.values.iter_mut().enumerate() {
+            *value = initial_value(Var::try_from(code as u32).expect("valid variable"));
+        }
+
+        self.num_true.clear();
+        self.falsified.clear();
+        self.falsified_idx.clear();
+
+        for clause in 0..self.num_clauses() {
+            let num_true = self
+                .clause(clause as u32)
+                .filter(|&lit| self.is_true(lit))
+                .count();
+            self.num_true.push(num_true as u32);
+            self.falsified_idx.push(None);
+
+            if num_true == 0 {
+                self.mark_falsified(clause as u32);
+            }
+        }
+
+        self.flips_since_best.clear();
+        let mut fewest_falsified = self.falsified.len();
+        let mut flips = 0;
+
+        while !self.falsified.is_empty() && flips < max_flips {
+            let clause = self.falsified[self.random.next_below(self.falsified.len())];
+            let var = self.pick_var(clause);
+
+            self.flip(var);
+            flips += 1;
+
+            self.flips_since_best.push(var);
+            if self.falsified.len() < fewest_falsified {
+                fewest_falsified = self.falsified.len();
+                self.flips_since_best.clear();
+            }
+        }
+
+        for var in std::mem::take(&mut self.flips_since_best) {
+            self.values[var] = !self.values[var];
+        }
+
+        (flips, fewest_falsified == 0)
+    }
+
+    fn clause(&self, clause: u32) -> impl Iterator<Item = Lit> + '_ {
+        let clause = clause as usize;
+        self.lits[self.starts[clause]..self.starts[clause + 1]]
+            .iter()
+            .copied()
+    }
+
+    fn is_true(&self, lit: Lit) -> bool {
+        self.values[lit.var()] == lit.is_positive()
+    }
+
+    /// Pick the variable to flip in a falsified clause. A variable is picked with a probability
+    /// which decreases with its break count, the number of clauses it alone satisfies.
+    fn pick_var(&mut self, clause: u32) -> Var {
+        let mut weights = std::mem::take(&mut self.weights);
+        weights.clear();
+
+        for lit in self.clause(clause) {
+            let break_count = self.occurrences[!lit]
+                .iter()
+                .filter(|&&other| self.num_true[other as usize] == 1)
+                .count();
+            weights.push(self.break_weights[break_count.min(MAX_BREAK_COUNT)]);
+        }
+
+        let mut remaining = self.random.next_f64() * weights.iter().sum::<f64>();
+        let idx = weights
+            .iter()
+            .position(|&weight| {
+                remaining -= weight;
+                remaining < 0.0
+            })
+            .unwrap_or(weights.len() - 1);
+
+        self.weights = weights;
+        self.lits[self.starts[clause as usize] + idx].var()
+    }
+
+    fn flip(&mut self, var: Var) {
+        self.values[var] = !self.values[var];
+
+        let true_lit = if self.values[var] {
+            Lit::positive(var)
+        } else {
+            Lit::negative(var)
+        };
+
+        for idx in 0..self.occurrences[true_lit].len() {
+            let clause = self.occurrences[true_lit][idx];
+            self.num_true[clause as usize] += 1;
+
+            if self.num_true[clause as usize] == 1 {
+                self.mark_satisfied(clause);
+            }
+        }
+
+        for idx in 0..self.occurrences[!true_lit].len() {
+            let clause = self.occurrences[!true_lit][idx];
+            self.num_true[clause as usize] -= 1;
+
+            if self.num_true[clause as usize] == 0 {
+                self.mark_falsified(clause);
+            }
+        }
+    }
+
+    fn mark_falsified(&mut self, clause: u32) {
+        self.falsified_idx[clause as usize] = Some(self.falsified.len() as u32);
+        self.falsified.push(clause);
+    }
+
+    fn mark_satisfied(&mut self, clause: u32) {
+        let idx = self.falsified_idx[clause as usize]
+            .take()
+            .expect("satisfied clause was falsified") as usize;
+
+        self.falsified.swap_remove(idx);
+        if let Some(&moved) = self.falsified.get(idx) {
+            self.falsified_idx[moved as usize] = Some(idx as u32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lit;
+
+    use super::*;
+
+    fn local_search(clauses: &[&[Lit]]) -> LocalSearch {
+        let mut local_search = LocalSearch::new(0);
+
+        for lit in clauses.iter().flat_map(|clause| clause.iter()) {
+            local_search.grow_to(lit.var());
+        }
+
+        for clause in clauses {
+            local_search.add_clause(clause);
+        }
+
+        local_search
+    }
+
+    fn satisfies(local_search: &LocalSearch, clauses: &[&[Lit]]) -> bool {
+        clauses.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|lit| local_search.value(lit.var()) == lit.is_positive())
+        })
+    }
+
+    #[test]
+    fn satisfiable_clauses_are_satisfied() {
+        let clauses: &[&[Lit]] = unsafe {
+            &[
+                &[lit!(1), lit!(2), lit!(3)],
+                &[lit!(-1), lit!(2)],
+                &[lit!(-2), lit!(3)],
+                &[lit!(-3), lit!(-1)],
+                &[lit!(-3), lit!(4)],
+                &[lit!(-4), lit!(-2), lit!(1)],
+            ]
+        };
+
+        let mut local_search = local_search(clauses);
+        let (_, is_satisfied) = local_search.run(|_| false, 1000);
+
+        assert!(is_satisfied);
+        assert!(satisfies(&local_search, clauses));
+    }
+
+    #[test]
+    fn the_best_assignment_is_kept() {
+        // The clauses cannot all be satisfied, but every assignment with exactly one true variable
+        // falsifies only one of them.
+        let clauses: &[&[Lit]] = unsafe {
+            &[
+                &[lit!(1), lit!(2)],
+                &[lit!(-1), lit!(-2)],
+                &[lit!(1), lit!(-2)],
+                &[lit!(-1), lit!(2)],
+            ]
+        };
+
+        let mut local_search = local_search(clauses);
+        let (flips, is_satisfied) = local_search.run(|_| true, 100);
+
+        assert_eq!(100, flips);
+        assert!(!is_satisfied);
+
+        let falsified = clauses
+            .iter()
+            .filter(|clause| {
+                clause
+                    .iter()
+                    .all(|lit| local_search.value(lit.var()) != lit.is_positive())
+            })
+            .count();
+        assert_eq!(1, falsified);
+    }
+}
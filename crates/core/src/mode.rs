@@ -0,0 +1,139 @@
+use std::fmt::Display;
+
+use crate::restart::{LubyRestarts, RestartPolicy};
+
+/// The factor by which the number of conflicts in a mode grows after every stable mode.
+const MODE_INTERVAL_FACTOR: f64 = 2.0;
+/// The unit of the Luby sequence which determines the restarts in stable mode.
+const STABLE_RESTART_UNIT: u64 = 1024;
+
+/// The modes the search alternates between when mode switching is enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Restarts often, and quickly moves the focus of the brancher to the variables in recent
+    /// conflicts. This is good at refuting formulas.
+    #[default]
+    Focused,
+    /// Rarely restarts, and steers the brancher towards the largest assignments found so far. This
+    /// is good at finding solutions.
+    Stable,
+}
+
+impl Display for SearchMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchMode::Focused => write!(f, "focused"),
+            SearchMode::Stable => write!(f, "stable"),
+        }
+    }
+}
+
+/// Decides when the search switches between focused and stable mode. The search starts in focused
+/// mode, and both modes get the same number of conflicts before switching. That number grows
+/// geometrically every time the stable mode ends, as in CaDiCaL.
+pub struct ModeSwitching {
+    mode: SearchMode,
+    interval: f64,
+    conflicts_until_switch: u64,
+    /// The restart policy in stable mode. It keeps its state between stable modes, so the
+    /// intervals between restarts keep growing.
+    stable_restarts: LubyRestarts,
+}
+
+impl ModeSwitching {
+    pub fn new(first_interval: u64) -> Self {
+        ModeSwitching {
+            mode: SearchMode::Focused,
+            interval: first_interval as f64,
+            conflicts_until_switch: first_interval,
+            stable_restarts: LubyRestarts::new(STABLE_RESTART_UNIT),
+        }
+    }
+
+    pub fn mode(&self) -> SearchMode {
+        self.mode
+    }
+
+    pub fn on_conflict(&mut self) {
+        self.conflicts_until_switch = self.conflicts_until_switch.saturating_sub(1);
+    }
+
+    /// Indicates whether the search should switch modes at its next restart.
+    pub fn should_switch(&self) -> bool {
+        self.conflicts_until_switch == 0
+    }
+
+    /// Switch to the other mode, and return it.
+    pub fn switch(&mut self) -> SearchMode {
+        self.mode = match self.mode {
+            SearchMode::Focused => SearchMode::Stable,
+            SearchMode::Stable => {
+                self.interval *= MODE_INTERVAL_FACTOR;
+                SearchMode::Focused
+            }
+        };
+
+        self.conflicts_until_switch = self.interval as u64;
+        self.mode
+    }
+
+    /// The restart policy for the current mode. In focused mode this is the given policy.
+    pub fn restart_policy<'a>(
+        &'a mut self,
+        focused: &'a mut dyn RestartPolicy,
+    ) -> &'a mut dyn RestartPolicy {
+        match self.mode {
+            SearchMode::Focused => focused,
+            SearchMode::Stable => &mut self.stable_restarts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::restart::NeverRestart;
+
+    use super::*;
+
+    #[test]
+    fn modes_alternate_with_growing_intervals() {
+        let mut switching = ModeSwitching::new(10);
+        let mut intervals = vec![];
+
+        for _ in 0..4 {
+            let mut conflicts = 0;
+            while !switching.should_switch() {
+                switching.on_conflict();
+                conflicts += 1;
+            }
+
+            intervals.push((switching.mode(), conflicts));
+            switching.switch();
+        }
+
+        assert_eq!(
+            vec![
+                (SearchMode::Focused, 10),
+                (SearchMode::Stable, 10),
+                (SearchMode::Focused, 20),
+                (SearchMode::Stable, 20),
+            ],
+            intervals
+        );
+    }
+
+    #[test]
+    fn stable_mode_has_its_own_restarts() {
+        let mut switching = ModeSwitching::new(1);
+        let mut focused = NeverRestart;
+
+        switching.switch();
+        let restarts = switching.restart_policy(&mut focused);
+        for _ in 0..STABLE_RESTART_UNIT {
+            assert!(!restarts.should_restart());
+            restarts.on_conflict(1);
+        }
+
+        assert!(restarts.should_restart());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{
+    lit::{Lit, Var},
+    random::Random,
+    storage::KeyedVec,
+    trail::Trail,
+};
+
+/// The number of conflicts before the first rephase. The interval between two rephases grows by
+/// this amount every time.
+const REPHASE_INTERVAL: u64 = 1000;
+
+/// The order in which the saved phases are reset. The best phases are interleaved with the others,
+/// so the search regularly returns to the most promising region it has seen.
+const REPHASE_SCHEDULE: [Rephase; 6] = [
+    Rephase::Best,
+    Rephase::Original,
+    Rephase::Best,
+    Rephase::Inverted,
+    Rephase::Best,
+    Rephase::Random,
+];
+
+/// The phase to use for a variable when nothing else is known about it.
+const ORIGINAL_PHASE: bool = true;
+
+/// The ways in which the saved phases can be reset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rephase {
+    /// Every variable gets the original phase.
+    Original,
+    /// Every variable gets the negation of the original phase.
+    Inverted,
+    /// Every variable gets the value it had in the largest assignment since the previous rephase.
+    Best,
+    /// Every variable gets a random phase.
+    Random,
+}
+
+/// Keeps track of the polarity a brancher should pick for a variable. This implements phase
+/// saving, target and best phase tracking, and periodic rephasing.
+pub struct Phases {
+    /// The value every variable had when it was last unassigned.
+    saved: KeyedVec<Var, bool>,
+
+    /// The values in the largest assignment reached since the last restart.
+    target: KeyedVec<Var, Option<bool>>,
+    /// The number of assigned literals when the target phases were recorded.
+    target_assigned: usize,
+    /// Whether decisions should use the target phase if one is known. Branchers only enable this
+    /// in stable mode.
+    use_target: bool,
+
+    /// The values in the largest assignment reached since the last rephase.
+    best: KeyedVec<Var, Option<bool>>,
+    /// The number of assigned literals when the best phases were recorded.
+    best_assigned: usize,
+
+    /// Whether the saved phases were reset since they were last improved, see
+    /// [`Phases::take_rephased`].
+    is_rephased: bool,
+
+    conflicts: u64,
+    next_rephase: u64,
+    num_rephases: u64,
+    random: Random,
+}
+
+impl Default for Phases {
+    fn default() -> Self {
+        Phases {
+            saved: Default::default(),
+            target: Default::default(),
+            target_assigned: 0,
+            use_target: false,
+            best: Default::default(),
+            best_assigned: 0,
+            is_rephased: true,
+            conflicts: 0,
+            next_rephase: REPHASE_INTERVAL,
+            num_rephases: 0,
+            random: Random::new(0),
+        }
+    }
+}
+
+impl Phases {
+    pub fn grow_to(&mut self, var: Var) {
+        self.saved.grow_to_with(var, ORIGINAL_PHASE);
+        self.target.grow_to(var);
+        self.best.grow_to(var);
+    }
+
+    /// Set whether decisions use the target phase of a variable over its saved phase.
+    pub fn use_target_phases(&mut self, use_target: bool) {
+        self.use_target = use_target;
+    }
+
+    /// Get the literal to decide on for the given variable.
+    pub fn decision(&self, var: Var) -> Lit {
+        let target = if self.use_target {
+            self.target[var]
+        } else {
+            None
+        };
+
+        let phase = target.unwrap_or(self.saved[var]);
+
+        if phase {
+            Lit::positive(var)
+        } else {
+            Lit::negative(var)
+        }
+    }
+
+    /// The saved phase of the given variable.
+    pub fn saved(&self, var: Var) -> bool {
+        self.saved[var]
+    }
+
+    /// Whether the saved phases were reset since the previous call, or were never used before. The
+    /// solver then has a chance to improve them, e.g. by local search.
+    pub fn take_rephased(&mut self) -> bool {
+        std::mem::replace(&mut self.is_rephased, false)
+    }
+
+    /// Record the value of a literal that is being unassigned.
+    pub fn save(&mut self, lit: Lit) {
+        self.saved[lit.var()] = lit.is_positive();
+    }
+
+    /// Update the target and best phases with the assignment on the trail, if the trail is larger
+    /// than the previously recorded assignments.
+    pub fn on_backtrack(&mut self, trail: &Trail) {
+        let assigned = trail.len();
+
+        if assigned > self.target_assigned {
+            for lit in trail.iter() {
+                self.target[lit.var()] = Some(lit.is_positive());
+            }
+
+            self.target_assigned = assigned;
+        }
+
+        if assigned > self.best_assigned {
+            for lit in trail.iter() {
+                self.best[lit.var()] = Some(lit.is_positive());
+            }
+
+            self.best_assigned = assigned;
+        }
+    }
+
+    pub fn on_restart(&mut self) {
+        self.target_assigned = 0;
+    }
+
+    /// Count a conflict, and rephase when it is time to do so.
+    pub fn on_conflict(&mut self) {
+        self.conflicts += 1;
+
+        if self.conflicts >= self.next_rephase {
+            let rephase = REPHASE_SCHEDULE[self.num_rephases as usize % REPHASE_SCHEDULE.len()];
+            self.rephase(rephase);
+
+            self.num_rephases += 1;
+            self.next_rephase = self.conflicts + REPHASE_INTERVAL * (self.num_rephases + 1);
+        }
+    }
+
+    /// Reset the saved phases of all variables. The target and best phases are cleared afterwards.
+    pub fn rephase(&mut self, rephase: Rephase) {
+        for (saved, best) in self.saved.iter_mut().zip(self.best.iter_mut()) {
+            *saved = match rephase {
+                Rephase::Original => ORIGINAL_PHASE,
+                Rephase::Inverted => !ORIGINAL_PHASE,
+                Rephase::Best => best.unwrap_or(*saved),
+                Rephase::Random => self.random.next_bool(),
+            };
+
+            *best = None;
+        }
+
+        self.target.iter_mut().for_each(|target| *target = None);
+        self.target_assigned = 0;
+        self.best_assigned = 0;
+        self.is_rephased = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lit;
+
+    use super::*;
+
+    #[test]
+    fn unassigned_literals_are_saved() {
+        let mut phases = Phases::default();
+        let var = unsafe { lit!(2).var() };
+        phases.grow_to(var);
+
+        assert_eq!(Lit::positive(var), phases.decision(var));
+
+        phases.save(Lit::negative(var));
+        assert_eq!(Lit::negative(var), phases.decision(var));
+    }
+
+    #[test]
+    fn target_phase_takes_precedence_over_saved_phase() {
+        let mut phases = Phases::default();
+        phases.use_target_phases(true);
+        let var = unsafe { lit!(1).var() };
+        phases.grow_to(var);
+
+        let mut trail = Trail::default();
+        trail.enqueue(unsafe { lit!(0) });
+        trail.enqueue(unsafe { lit!(-1) });
+        phases.on_backtrack(&trail);
+
+        phases.save(unsafe { lit!(1) });
+        assert_eq!(unsafe { lit!(-1) }, phases.decision(var));
+    }
+
+    #[test]
+    fn smaller_assignments_do_not_overwrite_target() {
+        let mut phases = Phases::default();
+        phases.use_target_phases(true);
+        let var = unsafe { lit!(1).var() };
+        phases.grow_to(var);
+
+        let mut trail = Trail::default();
+        trail.enqueue(unsafe { lit!(0) });
+        trail.enqueue(unsafe { lit!(-1) });
+        phases.on_backtrack(&trail);
+
+        let mut trail = Trail::default();
+        trail.enqueue(unsafe { lit!(1) });
+        phases.on_backtrack(&trail);
+
+        assert_eq!(unsafe { lit!(-1) }, phases.decision(var));
+    }
+
+    #[test]
+    fn rephasing_to_best_restores_the_largest_assignment() {
+        let mut phases = Phases::default();
+        let var = unsafe { lit!(1).var() };
+        phases.grow_to(var);
+
+        let mut trail = Trail::default();
+        trail.enqueue(unsafe { lit!(0) });
+        trail.enqueue(unsafe { lit!(-1) });
+        phases.on_backtrack(&trail);
+        phases.save(unsafe { lit!(1) });
+
+        phases.rephase(Rephase::Best);
+        assert_eq!(unsafe { lit!(-1) }, phases.decision(var));
+
+        phases.rephase(Rephase::Inverted);
+        assert_eq!(unsafe { lit!(-1) }, phases.decision(var));
+
+        phases.rephase(Rephase::Original);
+        assert_eq!(unsafe { lit!(1) }, phases.decision(var));
+    }
+
+    #[test]
+    fn rephasing_is_reported_once() {
+        let mut phases = Phases::default();
+
+        assert!(phases.take_rephased());
+        assert!(!phases.take_rephased());
+
+        phases.rephase(Rephase::Random);
+        assert!(phases.take_rephased());
+        assert!(!phases.take_rephased());
+    }
+}
@@ -2,7 +2,8 @@ use log::trace;
 
 use crate::{assignment::Assignment, lit::Lit};
 
-/// Performs pre-processing on clauses that are added to the solver.
+/// Performs pre-processing on clauses that are added to the solver. Clauses are only added at the
+/// root of the search tree, so the given assignment contains the root-level assignments.
 #[derive(Default)]
 pub struct ClausePreProcessor {
     /// The buffer on which preprocessing operates.
@@ -14,8 +15,9 @@ pub enum PreProcessedClause<'a> {
     /// The clause is already satisfied. Either because it contained a literal already assigned to
     /// true, or because it contained literals of opposite polarity.
     Satisfiable,
-    /// The remaining literals after preprocessing. Note: there might be 0 literals remaining, in
-    /// which case the problem is unsatisfiable.
+    /// The remaining literals after preprocessing. Literals that are false under the assignment are
+    /// placed at the end. Note: there might be 0 literals remaining, in which case the problem is
+    /// unsatisfiable.
     Lits(&'a [Lit]),
 }
 
@@ -57,6 +59,10 @@ impl ClausePreProcessor {
             original_len - self.buffer.len()
         );
 
+        // The literals that can still become true are the ones that should be watched.
+        self.buffer
+            .sort_by_key(|&lit| assignment.value(lit) == Some(false));
+
         PreProcessedClause::Lits(&self.buffer)
     }
 }
@@ -104,4 +110,19 @@ mod tests {
 
         assert_eq!(PreProcessedClause::Satisfiable, result);
     }
+
+    #[test]
+    fn false_literals_are_placed_last() {
+        let mut preprocessor = ClausePreProcessor::default();
+        let mut assignment = Assignment::default();
+        assignment.grow_to(unsafe { lit!(3).var() });
+        assignment.assign(unsafe { lit!(2) });
+
+        let result = preprocessor.preprocess(unsafe { [lit!(-2), lit!(1), lit!(3)] }, &assignment);
+
+        assert_eq!(
+            PreProcessedClause::Lits(unsafe { &[lit!(1), lit!(3), lit!(-2)] }),
+            result
+        );
+    }
 }
@@ -0,0 +1,914 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    io::{self, BufWriter, Write},
+    ops::Range,
+};
+
+use crate::{
+    clause::ClauseId,
+    lit::{Lit, Var},
+};
+
+/// Receives the steps of a proof for the clauses the solver derives.
+///
+/// A literal is identified with the DIMACS code `var.code() + 1`, so the proof refers to the
+/// variables of a CNF formula when they were created in the order they appear in the formula. The
+/// clauses of such a formula have the IDs 1 to n, in the order they are added.
+pub trait ProofLog {
+    /// Whether the proof uses the hints given to [`ProofLog::add_clause`]. If not, the solver
+    /// does not compute them.
+    fn needs_hints(&self) -> bool;
+
+    /// Whether the proof can justify the explanations of propagators. If so, the explanations
+    /// which the learned clauses are derived from are logged as clauses, before the learned
+    /// clauses themselves.
+    fn needs_explanations(&self) -> bool;
+
+    /// Log a constraint of the formula, which is either a clause added to the solver or the
+    /// definition of a propagator.
+    fn add_axiom(&mut self, id: ClauseId, constraint: &PbConstraint);
+
+    /// Log the addition of a clause which is implied by the formula. Under the negation of the
+    /// clause, every hint in turn becomes unit, except for the last one which becomes false.
+    fn add_clause(&mut self, id: ClauseId, lits: &[Lit], hints: &[ClauseId]);
+
+    /// Log the addition of an explanation of a propagator, as a clause. The definition is the ID
+    /// of the axiom which defines the propagator, if it has one, from which the proof can derive
+    /// the explanation. By default the explanation is logged like any other clause.
+    fn add_explanation(&mut self, id: ClauseId, lits: &[Lit], _definition: Option<ClauseId>) {
+        self.add_clause(id, lits, &[]);
+    }
+
+    /// Log that a clause will no longer be used by the solver.
+    fn delete_clause(&mut self, id: ClauseId, lits: &[Lit]);
+
+    /// Flush the proof to the underlying writer. Writing a proof stops at the first I/O error,
+    /// which is reported here.
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+/// The encodings of the proof formats which are understood by drat-trim and the LRAT checkers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofEncoding {
+    /// Every step is a line of numbers, where every list of numbers is terminated by 0.
+    Text,
+    /// Every step starts with `a` or `d`, followed by the numbers as variable-length integers.
+    Binary,
+}
+
+/// Writes a DRAT proof, in which every added clause has to be RUP or RAT. Hints are not used.
+pub struct DratProof {
+    encoder: Encoder,
+}
+
+impl DratProof {
+    pub fn new(writer: impl Write + 'static, encoding: ProofEncoding) -> Self {
+        DratProof {
+            encoder: Encoder::new(writer, encoding),
+        }
+    }
+}
+
+impl ProofLog for DratProof {
+    fn needs_hints(&self) -> bool {
+        false
+    }
+
+    fn needs_explanations(&self) -> bool {
+        false
+    }
+
+    fn add_axiom(&mut self, _: ClauseId, _: &PbConstraint) {
+        // The checker reads the formula from the CNF file.
+    }
+
+    fn add_clause(&mut self, _: ClauseId, lits: &[Lit], _: &[ClauseId]) {
+        self.encoder.log(|encoder| {
+            encoder.binary_marker(b'a')?;
+            encoder.lits(lits)?;
+            encoder.end_step()
+        });
+    }
+
+    fn delete_clause(&mut self, _: ClauseId, lits: &[Lit]) {
+        self.encoder.log(|encoder| {
+            encoder.marker(b'd')?;
+            encoder.lits(lits)?;
+            encoder.end_step()
+        });
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        self.encoder.finish()
+    }
+}
+
+/// Writes an LRAT proof, in which every added clause carries the IDs of the clauses it is derived
+/// from.
+pub struct LratProof {
+    encoder: Encoder,
+    /// The ID of the last clause which was added. In the text format, deletions are labeled with
+    /// it.
+    last_id: u64,
+}
+
+impl LratProof {
+    pub fn new(writer: impl Write + 'static, encoding: ProofEncoding) -> Self {
+        LratProof {
+            encoder: Encoder::new(writer, encoding),
+            last_id: 0,
+        }
+    }
+}
+
+impl ProofLog for LratProof {
+    fn needs_hints(&self) -> bool {
+        true
+    }
+
+    fn needs_explanations(&self) -> bool {
+        false
+    }
+
+    fn add_axiom(&mut self, _: ClauseId, _: &PbConstraint) {
+        // The checker reads the formula from the CNF file.
+    }
+
+    fn add_clause(&mut self, id: ClauseId, lits: &[Lit], hints: &[ClauseId]) {
+        self.last_id = id.get();
+
+        self.encoder.log(|encoder| {
+            encoder.binary_marker(b'a')?;
+            encoder.number(id.get())?;
+            encoder.lits(lits)?;
+            encoder.ids(hints)?;
+            encoder.end_step()
+        });
+    }
+
+    fn delete_clause(&mut self, id: ClauseId, _: &[Lit]) {
+        let last_id = self.last_id;
+
+        self.encoder.log(|encoder| {
+            encoder.text_label(last_id)?;
+            encoder.marker(b'd')?;
+            encoder.ids(&[id])?;
+            encoder.end_step()
+        });
+    }
+
+    fn finish(self: Box<Self>) -> io::Result<()> {
+        self.encoder.finish()
+    }
+}
+
+/// Writes a VeriPB proof, in which every added clause has to follow by reverse unit propagation
+/// over pseudo-Boolean constraints. Unlike DRAT and LRAT proofs, which are checked against a CNF
+/// file, the proof is checked against a pseudo-Boolean model in the OPB format which is written
+/// alongside it. The model contains the clauses of the formula and the definitions of the
+/// propagators. The explanations of a propagator are derived from its definition by cutting
+/// planes, see [`Justification`].
+///
+/// VeriPB needs the complete model before the first step of the proof. Extending the model after
+/// a clause has been derived is an error, which is reported by [`ProofLog::finish`].
+pub struct VeriPbProof {
+    writer: BufWriter<Box<dyn Write>>,
+    /// The writer for the model, until the model is written at the first step of the proof.
+    model_writer: Option<Box<dyn Write>>,
+    model: Vec<PbConstraint>,
+    /// The constraints of the model which are not clauses, as they were given.
+    definitions: HashMap<ClauseId, PbConstraint>,
+    /// The VeriPB IDs of the constraints which can still be referred to.
+    constraint_ids: HashMap<ClauseId, u64>,
+    /// The number of constraints in the model and the proof so far. The constraints are numbered
+    /// from 1 in the order they are written.
+    num_constraints: u64,
+    /// The ID of the empty clause, once it is derived.
+    contradiction: Option<u64>,
+    error: Option<io::Error>,
+}
+
+impl VeriPbProof {
+    pub fn new(model_writer: impl Write + 'static, writer: impl Write + 'static) -> Self {
+        VeriPbProof {
+            writer: BufWriter::new(Box::new(writer)),
+            model_writer: Some(Box::new(model_writer)),
+            model: vec![],
+            definitions: HashMap::new(),
+            constraint_ids: HashMap::new(),
+            num_constraints: 0,
+            contradiction: None,
+            error: None,
+        }
+    }
+
+    fn log(&mut self, step: impl FnOnce(&mut Self) -> io::Result<()>) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if let Err(error) = step(self) {
+            self.error = Some(error);
+        }
+    }
+
+    /// Write the model and the header of the proof, if that has not happened yet.
+    fn start_proof(&mut self) -> io::Result<()> {
+        let Some(model_writer) = self.model_writer.take() else {
+            return Ok(());
+        };
+
+        let mut model_writer = BufWriter::new(model_writer);
+        let num_variables = self
+            .model
+            .iter()
+            .flat_map(|constraint| constraint.terms())
+            .map(|&(_, lit)| lit.var().code() + 1)
+            .max()
+            .unwrap_or(0);
+
+        writeln!(
+            model_writer,
+            "* #variable= {num_variables} #constraint= {}",
+            self.model.len()
+        )?;
+
+        for constraint in self.model.drain(..) {
+            for &(coefficient, lit) in constraint.terms() {
+                write!(model_writer, "{coefficient} {} ", VeriPbLit(lit))?;
+            }
+
+            writeln!(model_writer, ">= {} ;", constraint.degree())?;
+        }
+
+        model_writer.flush()?;
+
+        writeln!(self.writer, "pseudo-Boolean proof version 2.0")?;
+        writeln!(self.writer, "f {} ;", self.num_constraints)
+    }
+}
+
+impl ProofLog for VeriPbProof {
+    fn needs_hints(&self) -> bool {
+        false
+    }
+
+    fn needs_explanations(&self) -> bool {
+        true
+    }
+
+    fn add_axiom(&mut self, id: ClauseId, constraint: &PbConstraint) {
+        if self.model_writer.is_none() {
+            self.log(|_| {
+                Err(io::Error::other(
+                    "the model cannot be extended after the proof has started",
+                ))
+            });
+
+            return;
+        }
+
+        if !constraint.is_clause() {
+            self.definitions.insert(id, constraint.clone());
+        }
+
+        let mut constraint = constraint.clone();
+        constraint.normalize();
+        self.model.push(constraint);
+
+        self.num_constraints += 1;
+        self.constraint_ids.insert(id, self.num_constraints);
+    }
+
+    fn add_clause(&mut self, id: ClauseId, lits: &[Lit], _: &[ClauseId]) {
+        self.log(|proof| {
+            proof.start_proof()?;
+
+            write!(proof.writer, "rup")?;
+            for &lit in lits {
+                write!(proof.writer, " 1 {}", VeriPbLit(lit))?;
+            }
+
+            writeln!(proof.writer, " >= 1 ;")
+        });
+
+        self.num_constraints += 1;
+        self.constraint_ids.insert(id, self.num_constraints);
+
+        if lits.is_empty() {
+            self.contradiction = Some(self.num_constraints);
+        }
+    }
+
+    fn add_explanation(&mut self, id: ClauseId, lits: &[Lit], definition: Option<ClauseId>) {
+        let justification = definition.and_then(|definition| {
+            let definition_id = *self.constraint_ids.get(&definition)?;
+            let justification = Justification::derive(&self.definitions[&definition], lits)?;
+
+            Some((definition_id, justification))
+        });
+
+        let Some((definition_id, justification)) = justification else {
+            self.add_clause(id, lits, &[]);
+            return;
+        };
+
+        let first_lemma_id = self.num_constraints + 1;
+        let num_lemmas = justification.lemmas.len() as u64;
+
+        self.log(|proof| {
+            proof.start_proof()?;
+
+            for (lemma, _) in justification.lemmas.iter() {
+                write!(proof.writer, "rup")?;
+                for &(coefficient, lit) in lemma.terms() {
+                    write!(proof.writer, " {coefficient} {}", VeriPbLit(lit))?;
+                }
+
+                writeln!(proof.writer, " >= {} ;", lemma.degree())?;
+            }
+
+            write!(proof.writer, "pol {definition_id}")?;
+            for (lemma_id, (_, multiplier)) in (first_lemma_id..).zip(justification.lemmas.iter()) {
+                write!(proof.writer, " {lemma_id} {multiplier} * +")?;
+            }
+
+            for var in justification.weakened.iter() {
+                write!(proof.writer, " x{} w", var.code() + 1)?;
+            }
+
+            write!(proof.writer, " s")?;
+            if justification.degree > 1 {
+                write!(proof.writer, " {} d", justification.degree)?;
+            }
+
+            writeln!(proof.writer, " ;")?;
+
+            if num_lemmas > 0 {
+                write!(proof.writer, "del id")?;
+                for lemma_id in first_lemma_id..first_lemma_id + num_lemmas {
+                    write!(proof.writer, " {lemma_id}")?;
+                }
+
+                writeln!(proof.writer, " ;")?;
+            }
+
+            Ok(())
+        });
+
+        self.num_constraints += num_lemmas + 1;
+        self.constraint_ids.insert(id, self.num_constraints);
+
+        if justification.clause_len == 0 {
+            self.contradiction = Some(self.num_constraints);
+        }
+    }
+
+    fn delete_clause(&mut self, id: ClauseId, _: &[Lit]) {
+        let Some(constraint_id) = self.constraint_ids.remove(&id) else {
+            return;
+        };
+
+        self.log(|proof| {
+            proof.start_proof()?;
+            writeln!(proof.writer, "del id {constraint_id} ;")
+        });
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        let contradiction = self.contradiction;
+
+        self.log(|proof| {
+            proof.start_proof()?;
+
+            writeln!(proof.writer, "output NONE ;")?;
+            match contradiction {
+                Some(id) => writeln!(proof.writer, "conclusion UNSAT : {id} ;")?,
+                None => writeln!(proof.writer, "conclusion NONE ;")?,
+            }
+            writeln!(proof.writer, "end pseudo-Boolean proof ;")
+        });
+
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+/// The derivation of a clause from a pseudo-Boolean constraint by cutting planes, which applies if
+/// the constraint cannot be satisfied under the negation of the clause.
+///
+/// The literals of the constraint which are not in the clause are weakened away, after which the
+/// constraint only holds if one of the literals of the clause is true. Saturating and dividing by
+/// the degree then gives the clause. Weakening assumes the literals are free, which loses what is
+/// known about the literals of an order encoding: the negation of the clause fixes some of them,
+/// and through the order others as well. This is restored first by adding lemmas of the form
+/// `m * l + l_1 + ... + l_m >= m`, where `l` is a literal of the clause and the `l_i` are the
+/// literals which follow from its negation. The lemmas follow from the order encoding by reverse
+/// unit propagation.
+struct Justification {
+    /// The lemmas, with the multiplier by which they are added to the constraint.
+    lemmas: Vec<(PbConstraint, i64)>,
+    /// The variables which are weakened away.
+    weakened: Vec<Var>,
+    /// The degree of the constraint once the variables are weakened away.
+    degree: i64,
+    /// The number of literals in the derived clause, which can be fewer than in the given one.
+    clause_len: usize,
+}
+
+impl Justification {
+    /// Derive the clause from the constraint, if the constraint implies the clause in this way.
+    fn derive(constraint: &PbConstraint, clause: &[Lit]) -> Option<Justification> {
+        let mut sum = constraint.clone();
+        let mut lemmas = vec![];
+
+        for range in constraint.encodings.iter() {
+            let encoding = &constraint.terms[range.clone()];
+            let Some(&(coefficient, _)) = encoding.first() else {
+                continue;
+            };
+
+            // Only the implied literals which are false in the constraint weaken it.
+            let lemma = if coefficient < 0 {
+                encoding
+                    .iter()
+                    .rposition(|&(_, lit)| clause.contains(&!lit))
+                    .map(|idx| {
+                        let implied = encoding[..idx].iter().map(|&(_, lit)| lit);
+                        order_lemma(!encoding[idx].1, implied)
+                    })
+            } else {
+                encoding
+                    .iter()
+                    .position(|&(_, lit)| clause.contains(&lit))
+                    .map(|idx| {
+                        let implied = encoding[idx + 1..].iter().map(|&(_, lit)| !lit);
+                        order_lemma(encoding[idx].1, implied)
+                    })
+            };
+
+            if let Some(lemma) = lemma.filter(|lemma| lemma.degree > 0) {
+                let multiplier = coefficient.abs();
+                for &(lemma_coefficient, lit) in lemma.terms() {
+                    sum.add_term(multiplier * lemma_coefficient, lit);
+                }
+                sum.add_constant(-multiplier * lemma.degree);
+
+                lemmas.push((lemma, multiplier));
+            }
+        }
+
+        sum.normalize();
+
+        let mut weakened = vec![];
+        let mut degree = sum.degree;
+        let mut clause_len = 0;
+
+        for &(coefficient, lit) in sum.terms() {
+            if clause.contains(&lit) {
+                clause_len += 1;
+            } else {
+                weakened.push(lit.var());
+                degree -= coefficient;
+            }
+        }
+
+        (degree > 0).then_some(Justification {
+            lemmas,
+            weakened,
+            degree,
+            clause_len,
+        })
+    }
+}
+
+/// The constraint `m * lit + l_1 + ... + l_m >= m` for the `m` implied literals.
+fn order_lemma(lit: Lit, implied: impl Iterator<Item = Lit>) -> PbConstraint {
+    let mut lemma = PbConstraint::at_least(0);
+    for implied in implied {
+        lemma.add_term(1, implied);
+    }
+
+    let num_implied = lemma.terms.len() as i64;
+    lemma.add_term(num_implied, lit);
+    lemma.degree = num_implied;
+
+    lemma
+}
+
+/// Formats a literal as a variable of a pseudo-Boolean model, where the negation is marked by `~`.
+struct VeriPbLit(Lit);
+
+impl Display for VeriPbLit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0.is_negative() { "~" } else { "" };
+        write!(f, "{sign}x{}", self.0.var().code() + 1)
+    }
+}
+
+/// A pseudo-Boolean constraint `sum_i c_i * l_i >= degree`, where a literal counts as 1 when it is
+/// true and as 0 otherwise.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PbConstraint {
+    terms: Vec<(i64, Lit)>,
+    degree: i64,
+    /// The ranges of the terms which are the order encodings of integer variables, see
+    /// [`PbConstraint::add_order_encoding`].
+    encodings: Vec<Range<usize>>,
+}
+
+impl PbConstraint {
+    /// Create the constraint `0 >= degree`, to which terms can be added.
+    pub fn at_least(degree: i64) -> Self {
+        PbConstraint {
+            terms: vec![],
+            degree,
+            encodings: vec![],
+        }
+    }
+
+    /// Create the constraint which requires at least one of the literals to be true.
+    pub fn clause(lits: &[Lit]) -> Self {
+        PbConstraint {
+            terms: lits.iter().map(|&lit| (1, lit)).collect(),
+            degree: 1,
+            encodings: vec![],
+        }
+    }
+
+    pub fn terms(&self) -> &[(i64, Lit)] {
+        &self.terms
+    }
+
+    pub fn degree(&self) -> i64 {
+        self.degree
+    }
+
+    /// Add `coefficient * lit` to the left-hand side of the constraint.
+    pub fn add_term(&mut self, coefficient: i64, lit: Lit) {
+        self.terms.push((coefficient, lit));
+    }
+
+    /// Add a constant to the left-hand side of the constraint.
+    pub fn add_constant(&mut self, constant: i64) {
+        self.degree -= constant;
+    }
+
+    /// Add `coefficient * x` to the left-hand side of the constraint, where the integer `x` is the
+    /// offset plus the number of true literals. Every literal implies the literals before it,
+    /// which a proof can use to derive the explanations of a propagator from its definition.
+    pub fn add_order_encoding(&mut self, coefficient: i64, offset: i64, lits: &[Lit]) {
+        self.add_constant(coefficient * offset);
+
+        let start = self.terms.len();
+        self.terms
+            .extend(lits.iter().map(|&lit| (coefficient, lit)));
+        self.encodings.push(start..self.terms.len());
+    }
+
+    /// Whether the constraint is a clause, i.e. requires at least one of its literals to be true.
+    fn is_clause(&self) -> bool {
+        self.degree == 1 && self.terms.iter().all(|&(coefficient, _)| coefficient == 1)
+    }
+
+    /// Rewrite the constraint so every variable occurs at most once, with a positive coefficient.
+    /// This uses `c * l = c - c * !l` for literals with a negative coefficient. The order encodings
+    /// in the constraint are no longer known afterwards.
+    pub fn normalize(&mut self) {
+        self.encodings.clear();
+
+        // Express all terms over the positive literals first.
+        for (coefficient, lit) in self.terms.iter_mut() {
+            if lit.is_negative() {
+                self.degree -= *coefficient;
+                *coefficient = -*coefficient;
+                *lit = !*lit;
+            }
+        }
+
+        self.terms.sort_by_key(|&(_, lit)| lit.var());
+
+        let mut normalized: Vec<(i64, Lit)> = Vec::with_capacity(self.terms.len());
+        for &(coefficient, lit) in self.terms.iter() {
+            match normalized.last_mut() {
+                Some((last_coefficient, last_lit)) if *last_lit == lit => {
+                    *last_coefficient += coefficient
+                }
+                _ => normalized.push((coefficient, lit)),
+            }
+        }
+
+        normalized.retain(|&(coefficient, _)| coefficient != 0);
+
+        for (coefficient, lit) in normalized.iter_mut() {
+            if *coefficient < 0 {
+                self.degree -= *coefficient;
+                *coefficient = -*coefficient;
+                *lit = !*lit;
+            }
+        }
+
+        self.terms = normalized;
+    }
+}
+
+/// Writes the steps of a proof in one of the [`ProofEncoding`]s.
+struct Encoder {
+    writer: BufWriter<Box<dyn Write>>,
+    encoding: ProofEncoding,
+    /// Whether nothing has been written yet for the current step in the text encoding.
+    is_line_start: bool,
+    error: Option<io::Error>,
+}
+
+impl Encoder {
+    fn new(writer: impl Write + 'static, encoding: ProofEncoding) -> Self {
+        Encoder {
+            writer: BufWriter::new(Box::new(writer)),
+            encoding,
+            is_line_start: true,
+            error: None,
+        }
+    }
+
+    fn log(&mut self, step: impl FnOnce(&mut Self) -> io::Result<()>) {
+        if self.error.is_some() {
+            return;
+        }
+
+        if let Err(error) = step(self) {
+            self.error = Some(error);
+        }
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => self.writer.flush(),
+        }
+    }
+
+    /// Write a marker which is used in both encodings.
+    fn marker(&mut self, marker: u8) -> io::Result<()> {
+        match self.encoding {
+            ProofEncoding::Text => self.text_token(marker as char),
+            ProofEncoding::Binary => self.writer.write_all(&[marker]),
+        }
+    }
+
+    /// Write a marker which is only used in the binary encoding.
+    fn binary_marker(&mut self, marker: u8) -> io::Result<()> {
+        match self.encoding {
+            ProofEncoding::Text => Ok(()),
+            ProofEncoding::Binary => self.writer.write_all(&[marker]),
+        }
+    }
+
+    /// Write a number which is only used in the text encoding.
+    fn text_label(&mut self, label: u64) -> io::Result<()> {
+        match self.encoding {
+            ProofEncoding::Text => self.text_token(label),
+            ProofEncoding::Binary => Ok(()),
+        }
+    }
+
+    /// Write the literals followed by the terminating 0.
+    fn lits(&mut self, lits: &[Lit]) -> io::Result<()> {
+        for &lit in lits {
+            let code = lit.var().code() as u64 + 1;
+
+            match self.encoding {
+                ProofEncoding::Text if lit.is_negative() => {
+                    self.text_token(format_args!("-{code}"))?
+                }
+                ProofEncoding::Text => self.text_token(code)?,
+                ProofEncoding::Binary => self.varint(2 * code + lit.is_negative() as u64)?,
+            }
+        }
+
+        self.number(0)
+    }
+
+    /// Write the clause IDs followed by the terminating 0.
+    fn ids(&mut self, ids: &[ClauseId]) -> io::Result<()> {
+        for &id in ids {
+            self.number(id.get())?;
+        }
+
+        self.number(0)
+    }
+
+    /// Write a non-negative number.
+    fn number(&mut self, number: u64) -> io::Result<()> {
+        match self.encoding {
+            ProofEncoding::Text => self.text_token(number),
+            ProofEncoding::Binary => self.varint(2 * number),
+        }
+    }
+
+    fn end_step(&mut self) -> io::Result<()> {
+        match self.encoding {
+            ProofEncoding::Text => {
+                self.is_line_start = true;
+                writeln!(self.writer)
+            }
+            ProofEncoding::Binary => Ok(()),
+        }
+    }
+
+    fn text_token(&mut self, token: impl Display) -> io::Result<()> {
+        if !self.is_line_start {
+            write!(self.writer, " ")?;
+        }
+
+        self.is_line_start = false;
+        write!(self.writer, "{token}")
+    }
+
+    /// Write the number 7 bits at a time starting with the least significant bits. The highest bit
+    /// of a byte indicates more bytes follow.
+    fn varint(&mut self, mut number: u64) -> io::Result<()> {
+        while number > 127 {
+            self.writer.write_all(&[(number & 127) as u8 | 128])?;
+            number >>= 7;
+        }
+
+        self.writer.write_all(&[number as u8])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{clause::ClauseDb, lit};
+
+    use super::*;
+
+    /// A writer which can still be inspected after it is moved into the proof.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn clause_ids<const N: usize>() -> [ClauseId; N] {
+        let mut clauses = ClauseDb::default();
+        [(); N].map(|_| clauses.new_clause_id())
+    }
+
+    #[test]
+    fn drat_text_proof() {
+        let buffer = SharedBuffer::default();
+        let mut proof = Box::new(DratProof::new(buffer.clone(), ProofEncoding::Text));
+        let [a, b, c] = clause_ids();
+
+        proof.add_clause(a, unsafe { &[lit!(0), lit!(-1)] }, &[]);
+        proof.delete_clause(b, unsafe { &[lit!(2)] });
+        proof.add_clause(c, &[], &[]);
+        proof.finish().unwrap();
+
+        assert_eq!(
+            "1 -2 0\nd 3 0\n0\n",
+            String::from_utf8_lossy(&buffer.0.borrow())
+        );
+    }
+
+    #[test]
+    fn drat_binary_proof() {
+        let buffer = SharedBuffer::default();
+        let mut proof = Box::new(DratProof::new(buffer.clone(), ProofEncoding::Binary));
+        let [a, b] = clause_ids();
+
+        proof.add_clause(a, unsafe { &[lit!(0), lit!(-1)] }, &[]);
+        proof.delete_clause(b, unsafe { &[lit!(-99)] });
+        proof.finish().unwrap();
+
+        // -100 is encoded as 201 = 0b1_1001001.
+        assert_eq!(
+            vec![b'a', 2, 5, 0, b'd', 0b1100_1001, 0b0000_0001, 0],
+            *buffer.0.borrow()
+        );
+    }
+
+    #[test]
+    fn lrat_text_proof() {
+        let buffer = SharedBuffer::default();
+        let mut proof = Box::new(LratProof::new(buffer.clone(), ProofEncoding::Text));
+        let [a, b, c, d] = clause_ids();
+
+        proof.add_clause(c, unsafe { &[lit!(0), lit!(-1)] }, &[a, b]);
+        proof.delete_clause(a, unsafe { &[lit!(0), lit!(2)] });
+        proof.add_clause(d, &[], &[c, b]);
+        proof.finish().unwrap();
+
+        assert_eq!(
+            "3 1 -2 0 1 2 0\n3 d 1 0\n4 0 3 2 0\n",
+            String::from_utf8_lossy(&buffer.0.borrow())
+        );
+    }
+
+    #[test]
+    fn lrat_binary_proof() {
+        let buffer = SharedBuffer::default();
+        let mut proof = Box::new(LratProof::new(buffer.clone(), ProofEncoding::Binary));
+        let [a, b, c] = clause_ids();
+
+        proof.add_clause(c, unsafe { &[lit!(-1)] }, &[a, b]);
+        proof.delete_clause(a, unsafe { &[lit!(0), lit!(2)] });
+        proof.finish().unwrap();
+
+        assert_eq!(vec![b'a', 6, 5, 0, 2, 4, 0, b'd', 2, 0], *buffer.0.borrow());
+    }
+
+    #[test]
+    fn veripb_proof() {
+        let model = SharedBuffer::default();
+        let buffer = SharedBuffer::default();
+        let mut proof = Box::new(VeriPbProof::new(model.clone(), buffer.clone()));
+        let [a, b, c, d] = clause_ids();
+
+        let mut definition = PbConstraint::at_least(1);
+        definition.add_term(2, unsafe { lit!(1) });
+        definition.add_term(-1, unsafe { lit!(2) });
+
+        proof.add_axiom(a, &PbConstraint::clause(unsafe { &[lit!(0), lit!(-1)] }));
+        proof.add_axiom(b, &definition);
+        proof.add_clause(c, unsafe { &[lit!(0)] }, &[]);
+        proof.delete_clause(c, unsafe { &[lit!(0)] });
+        proof.add_clause(d, &[], &[]);
+        proof.finish().unwrap();
+
+        assert_eq!(
+            "* #variable= 3 #constraint= 2\n1 x1 1 ~x2 >= 1 ;\n2 x2 1 ~x3 >= 2 ;\n",
+            String::from_utf8_lossy(&model.0.borrow())
+        );
+        assert_eq!(
+            "pseudo-Boolean proof version 2.0\nf 2 ;\nrup 1 x1 >= 1 ;\ndel id 3 ;\nrup >= 1 ;\n\
+             output NONE ;\nconclusion UNSAT : 4 ;\nend pseudo-Boolean proof ;\n",
+            String::from_utf8_lossy(&buffer.0.borrow())
+        );
+    }
+
+    #[test]
+    fn veripb_explanation_is_derived_from_the_definition() {
+        let model = SharedBuffer::default();
+        let buffer = SharedBuffer::default();
+        let mut proof = Box::new(VeriPbProof::new(model.clone(), buffer.clone()));
+        let [a, b] = clause_ids();
+
+        // x + z <= 2, where x in [0, 3] is encoded by x1, x2 and x3.
+        let mut definition = PbConstraint::at_least(-2);
+        definition.add_order_encoding(-1, 0, unsafe { &[lit!(0), lit!(1), lit!(2)] });
+        definition.add_term(-1, unsafe { lit!(3) });
+
+        proof.add_axiom(a, &definition);
+        // [x >= 2] -> !z
+        proof.add_explanation(b, unsafe { &[lit!(-1), lit!(-3)] }, Some(a));
+        proof.finish().unwrap();
+
+        // The lemma restores [x >= 2] -> [x >= 1] before x3 is weakened away.
+        assert_eq!(
+            "pseudo-Boolean proof version 2.0\nf 1 ;\nrup 1 x1 1 ~x2 >= 1 ;\n\
+             pol 1 2 1 * + x3 w s ;\ndel id 2 ;\noutput NONE ;\nconclusion NONE ;\n\
+             end pseudo-Boolean proof ;\n",
+            String::from_utf8_lossy(&buffer.0.borrow())
+        );
+    }
+
+    #[test]
+    fn veripb_model_cannot_be_extended_once_the_proof_started() {
+        let mut proof = Box::new(VeriPbProof::new(io::sink(), io::sink()));
+        let [a, b] = clause_ids();
+
+        proof.add_clause(a, unsafe { &[lit!(0)] }, &[]);
+        proof.add_axiom(b, &PbConstraint::clause(unsafe { &[lit!(1)] }));
+
+        assert!(proof.finish().is_err());
+    }
+
+    #[test]
+    fn normalized_constraint_has_positive_coefficients_and_distinct_variables() {
+        // -2 x1 + !x1 + 3 x2 - 3 x2 >= 0
+        let mut constraint = PbConstraint::at_least(0);
+        constraint.add_term(-2, unsafe { lit!(0) });
+        constraint.add_term(1, unsafe { lit!(-0) });
+        constraint.add_term(3, unsafe { lit!(1) });
+        constraint.add_term(-3, unsafe { lit!(1) });
+
+        constraint.normalize();
+
+        assert_eq!(&[(3, unsafe { lit!(-0) })], constraint.terms());
+        assert_eq!(2, constraint.degree());
+    }
+}
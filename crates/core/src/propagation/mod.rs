@@ -6,9 +6,11 @@ use std::{marker::PhantomData, ops::Deref};
 
 use crate::{
     assignment::Assignment,
+    brancher::Brancher,
     domains::{Conflict, DomainId, DomainStore, EnqueueDomainLit},
     implication_graph::ImplicationGraph,
     lit::Lit,
+    proof::PbConstraint,
     search_tree::SearchTree,
     storage::{Indexer, StaticIndexer},
     trail::Trail,
@@ -54,6 +56,16 @@ pub trait Propagator<Domains, Event> {
 
     /// Should remove as many values from the domains it is registered for as it can.
     fn propagate(&mut self, ctx: &mut Context<Domains, Event>) -> Result<(), Conflict<Domains>>;
+
+    /// The constraint enforced by the propagator, as a pseudo-Boolean constraint over the literals
+    /// of the solver. A proof which covers propagators derives the explanations of the propagator
+    /// from this definition.
+    ///
+    /// By default the propagator has no definition, in which case its explanations cannot be
+    /// checked.
+    fn definition(&self, _ctx: &mut Context<Domains, Event>) -> Option<PbConstraint> {
+        None
+    }
 }
 
 pub struct Context<'a, Domains, Event> {
@@ -62,6 +74,10 @@ pub struct Context<'a, Domains, Event> {
     implication_graph: &'a mut ImplicationGraph<Domains>,
     domains: &'a mut Domains,
     search_tree: &'a mut SearchTree,
+    /// The propagator which is given the context, to which its explanations are attributed.
+    propagator: PropagatorId,
+    /// The brancher, which is told about the literals the propagator assigns.
+    brancher: &'a mut dyn Brancher,
     event: PhantomData<Event>,
 }
 
@@ -72,6 +88,8 @@ impl<Domains, Event> Context<'_, Domains, Event> {
         implication_graph: &'a mut ImplicationGraph<Domains>,
         search_tree: &'a mut SearchTree,
         domains: &'a mut Domains,
+        propagator: PropagatorId,
+        brancher: &'a mut dyn Brancher,
     ) -> Context<'a, Domains, Event> {
         Context {
             assignment,
@@ -79,6 +97,8 @@ impl<Domains, Event> Context<'_, Domains, Event> {
             implication_graph,
             domains,
             search_tree,
+            propagator,
+            brancher,
             event: PhantomData,
         }
     }
@@ -87,6 +107,10 @@ impl<Domains, Event> Context<'_, Domains, Event> {
         self.assignment.value(lit.variable)
     }
 
+    pub(crate) fn assignment(&self) -> &Assignment {
+        self.assignment
+    }
+
     pub fn assign(
         &mut self,
         lit: PropagatorVar<Lit>,
@@ -100,6 +124,8 @@ impl<Domains, Event> Context<'_, Domains, Event> {
             trail: self.trail,
             implication_graph: self.implication_graph,
             search_tree: self.search_tree,
+            propagator: self.propagator,
+            brancher: &mut *self.brancher,
         };
 
         enqueue_lit.enqueue(lit, explanation.into())
@@ -124,6 +150,8 @@ impl<Domains, Event> Context<'_, Domains, Event> {
             trail: self.trail,
             implication_graph: self.implication_graph,
             search_tree: self.search_tree,
+            propagator: self.propagator,
+            brancher: &mut *self.brancher,
         };
         (&mut self.domains[domain_id], enqueue_lit)
     }
@@ -134,12 +162,22 @@ pub struct PropositionalState<'a, Domains> {
     trail: &'a mut Trail,
     implication_graph: &'a mut ImplicationGraph<Domains>,
     search_tree: &'a mut SearchTree,
+    propagator: PropagatorId,
+    brancher: &'a mut dyn Brancher,
 }
 
 impl<Domains> EnqueueDomainLit<Domains> for PropositionalState<'_, Domains> {
     fn enqueue(&mut self, lit: Lit, explanation: Explanation<Domains>) -> Result<(), Conflict<Domains>> {
-        if self.assignment.value(lit) == Some(false) {
-            return Err(Conflict::Propagator { lit, explanation });
+        match self.assignment.value(lit) {
+            Some(true) => return Ok(()),
+            Some(false) => {
+                return Err(Conflict::Propagator {
+                    lit,
+                    explanation,
+                    propagator: self.propagator,
+                })
+            }
+            None => {}
         }
 
         self.trail.enqueue(lit);
@@ -148,10 +186,12 @@ impl<Domains> EnqueueDomainLit<Domains> for PropositionalState<'_, Domains> {
             Reason::Explanation {
                 propagated_lit: lit,
                 explanation,
+                propagator: self.propagator,
             },
         );
         self.search_tree.register_assignment(lit);
         self.assignment.assign(lit);
+        self.brancher.on_variable_assigned(lit);
 
         Ok(())
     }
@@ -202,3 +242,25 @@ pub enum LitEvent {
     FixedTrue,
     FixedFalse,
 }
+
+/// Solvers whose propagators only watch literals can use the literal events as their events.
+impl Indexer for LitEvent {
+    fn index(&self) -> usize {
+        match self {
+            LitEvent::FixedTrue => 0,
+            LitEvent::FixedFalse => 1,
+        }
+    }
+}
+
+impl StaticIndexer for LitEvent {
+    fn get_len() -> usize {
+        2
+    }
+}
+
+impl SDomainEvent<LitEvent> for LitEvent {
+    fn is(self, event: LitEvent) -> bool {
+        self == event
+    }
+}
@@ -7,31 +7,50 @@ use crate::{
     lit::Lit,
 };
 
+use super::PropagatorId;
+
 #[derive(Debug, Default)]
 pub enum Reason<Domains> {
     #[default]
     Decision,
     Clause(ClauseRef),
+    /// A propagation by a binary clause. The literals are stored with the reason, so the clause
+    /// database does not have to be accessed to explain the propagation.
+    BinaryClause {
+        clause_ref: ClauseRef,
+        /// The literals of the clause, with the propagated literal first.
+        lits: [Lit; 2],
+    },
     Explanation {
         /// The literal which was propagated.
         propagated_lit: Lit,
         /// The explanation for the propagation.
         explanation: Explanation<Domains>,
+        /// The propagator which explained the propagation.
+        propagator: PropagatorId,
     },
 }
 
 impl<Domains> Reason<Domains> {
-    pub fn as_clause<'clauses>(
-        &self,
-        clauses: &'clauses ClauseDb,
-        domains: &Domains,
-    ) -> Cow<'clauses, [Lit]> {
+    /// The clause in the clause database which caused the propagation, if any.
+    pub fn clause_ref(&self) -> Option<ClauseRef> {
+        match self {
+            Reason::Clause(clause_ref) | Reason::BinaryClause { clause_ref, .. } => {
+                Some(*clause_ref)
+            }
+            Reason::Decision | Reason::Explanation { .. } => None,
+        }
+    }
+
+    pub fn as_clause<'a>(&'a self, clauses: &'a ClauseDb, domains: &Domains) -> Cow<'a, [Lit]> {
         match self {
             Reason::Decision => Cow::Owned([].into()),
             Reason::Clause(clause_ref) => Cow::Borrowed(clauses[*clause_ref].lits()),
+            Reason::BinaryClause { lits, .. } => Cow::Borrowed(lits),
             Reason::Explanation {
                 propagated_lit,
                 explanation,
+                ..
             } => {
                 let mut clause = vec![*propagated_lit];
                 clause.extend(explanation.iter().map(|atom| !atom.as_lit(domains)));
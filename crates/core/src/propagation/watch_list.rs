@@ -1,7 +1,7 @@
 use std::ops::{Index, IndexMut};
 
 use crate::{
-    clause::ClauseRef,
+    clause::{ClauseRef, Relocation},
     domains::{Domain, DomainId, UntypedDomainId},
     lit::Lit,
     storage::{Indexer, KeyedVec, StaticIndexer},
@@ -43,6 +43,34 @@ impl<Event: StaticIndexer> WatchList<Event> {
     }
 }
 
+impl<Event> WatchList<Event> {
+    /// Stop watching all clauses. The watches of propagators and domains are kept.
+    pub fn remove_clause_watches(&mut self) {
+        for watches in self.literal_watches.iter_mut() {
+            watches.retain(|watch| watch.clause_ref().is_none());
+        }
+    }
+
+    /// Stop watching the given clause on the given literal.
+    pub fn remove_clause_watch(&mut self, lit: Lit, clause_ref: ClauseRef) {
+        self.literal_watches[lit].retain(|watch| watch.clause_ref() != Some(clause_ref));
+    }
+
+    /// Update the watches of clauses to the references the clauses got when the clause database
+    /// was compacted.
+    pub fn relocate_clauses(&mut self, relocation: &Relocation) {
+        for watch in self.literal_watches.iter_mut().flatten() {
+            if let LitWatch::Clause { clause_ref, .. } | LitWatch::BinaryClause { clause_ref, .. } =
+                watch
+            {
+                *clause_ref = relocation
+                    .get(*clause_ref)
+                    .expect("watched clauses are not deleted");
+            }
+        }
+    }
+}
+
 impl<Event: Indexer> WatchList<Event> {
     pub fn add_event_watch(
         &mut self,
@@ -78,7 +106,20 @@ impl<Event: Indexer> Index<(UntypedDomainId, Event)> for WatchList<Event> {
 
 #[derive(Clone, Copy, Debug)]
 pub enum LitWatch<Event> {
-    Clause(ClauseRef),
+    /// A clause with at least three literals.
+    Clause {
+        clause_ref: ClauseRef,
+        /// A literal of the clause other than the watched literal. If it is true the clause is
+        /// satisfied, and the clause does not have to be visited.
+        blocker: Lit,
+    },
+
+    /// A clause with two literals.
+    BinaryClause {
+        clause_ref: ClauseRef,
+        /// The literal which is implied when the watched literal becomes false.
+        other: Lit,
+    },
 
     Propagator {
         propagator_id: PropagatorId,
@@ -91,9 +132,15 @@ pub enum LitWatch<Event> {
     },
 }
 
-impl<Event> From<ClauseRef> for LitWatch<Event> {
-    fn from(value: ClauseRef) -> Self {
-        LitWatch::Clause(value)
+impl<Event> LitWatch<Event> {
+    /// The watched clause, if the watch is for a clause.
+    pub fn clause_ref(&self) -> Option<ClauseRef> {
+        match self {
+            LitWatch::Clause { clause_ref, .. } | LitWatch::BinaryClause { clause_ref, .. } => {
+                Some(*clause_ref)
+            }
+            LitWatch::Propagator { .. } | LitWatch::DomainEvent { .. } => None,
+        }
     }
 }
 
@@ -0,0 +1,63 @@
+/// A small, fast pseudo-random number generator based on xorshift64*. It is not suitable for
+/// cryptographic purposes, but it is deterministic given the seed, which keeps solver runs
+/// reproducible.
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        // The state of a xorshift generator may never be zero.
+        Random {
+            state: seed ^ 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    /// Get a number in the range [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Get a number in the range [0, bound).
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "cannot generate a number in an empty range");
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = Random::new(42);
+        let mut b = Random::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn floats_are_in_the_unit_interval() {
+        let mut random = Random::new(0);
+
+        for _ in 0..1000 {
+            let value = random.next_f64();
+            assert!((0.0..1.0).contains(&value));
+        }
+    }
+}
@@ -0,0 +1,290 @@
+/// A restart policy decides when the solver should abandon the current branch of the search tree
+/// and backtrack to the root. Learned clauses and brancher state are kept across restarts.
+pub trait RestartPolicy {
+    /// Called after every conflict, with the literal block distance of the clause that was learned
+    /// from it.
+    fn on_conflict(&mut self, lbd: u32);
+
+    /// Indicates whether the solver should restart before making its next decision.
+    fn should_restart(&mut self) -> bool;
+
+    /// Called when the solver has restarted.
+    fn on_restart(&mut self);
+}
+
+impl<R: RestartPolicy + ?Sized> RestartPolicy for &mut R {
+    fn on_conflict(&mut self, lbd: u32) {
+        (**self).on_conflict(lbd)
+    }
+
+    fn should_restart(&mut self) -> bool {
+        (**self).should_restart()
+    }
+
+    fn on_restart(&mut self) {
+        (**self).on_restart()
+    }
+}
+
+/// A restart policy that never restarts. The search only backjumps after conflicts.
+pub struct NeverRestart;
+
+impl RestartPolicy for NeverRestart {
+    fn on_conflict(&mut self, _: u32) {}
+
+    fn should_restart(&mut self) -> bool {
+        false
+    }
+
+    fn on_restart(&mut self) {}
+}
+
+/// Restarts according to the Luby sequence (1, 1, 2, 1, 1, 2, 4, 1, ...). The n-th restart happens
+/// after `unit * luby(n)` conflicts since the previous restart.
+pub struct LubyRestarts {
+    unit: u64,
+    /// The index in the luby sequence of the current restart interval.
+    index: u64,
+    conflicts_until_restart: u64,
+}
+
+impl LubyRestarts {
+    pub fn new(unit: u64) -> Self {
+        assert!(unit > 0, "the restart unit must be positive");
+
+        LubyRestarts {
+            unit,
+            index: 1,
+            conflicts_until_restart: unit,
+        }
+    }
+}
+
+impl RestartPolicy for LubyRestarts {
+    fn on_conflict(&mut self, _: u32) {
+        self.conflicts_until_restart = self.conflicts_until_restart.saturating_sub(1);
+    }
+
+    fn should_restart(&mut self) -> bool {
+        self.conflicts_until_restart == 0
+    }
+
+    fn on_restart(&mut self) {
+        self.index += 1;
+        self.conflicts_until_restart = self.unit * luby(self.index);
+    }
+}
+
+/// Compute the i-th element (starting at 1) of the Luby sequence.
+fn luby(i: u64) -> u64 {
+    let mut i = i;
+
+    loop {
+        // Find the smallest k such that i <= 2^k - 1.
+        let k = u64::BITS - i.leading_zeros();
+
+        if i == (1 << k) - 1 {
+            return 1 << (k - 1);
+        }
+
+        // i lies in the repeated prefix of the sequence, i.e. 2^(k-1) <= i < 2^k - 1.
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+/// Restarts after a geometrically growing number of conflicts. The first restart happens after
+/// `first_interval` conflicts, and every interval after that is `factor` times as long as the
+/// previous one.
+pub struct GeometricRestarts {
+    interval: f64,
+    factor: f64,
+    conflicts_until_restart: u64,
+}
+
+impl GeometricRestarts {
+    pub fn new(first_interval: u64, factor: f64) -> Self {
+        assert!(first_interval > 0, "the first restart interval must be positive");
+        assert!(factor > 1.0, "the restart interval must grow");
+
+        GeometricRestarts {
+            interval: first_interval as f64,
+            factor,
+            conflicts_until_restart: first_interval,
+        }
+    }
+}
+
+impl RestartPolicy for GeometricRestarts {
+    fn on_conflict(&mut self, _: u32) {
+        self.conflicts_until_restart = self.conflicts_until_restart.saturating_sub(1);
+    }
+
+    fn should_restart(&mut self) -> bool {
+        self.conflicts_until_restart == 0
+    }
+
+    fn on_restart(&mut self) {
+        self.interval *= self.factor;
+        self.conflicts_until_restart = self.interval as u64;
+    }
+}
+
+/// Glucose-style dynamic restarts. The policy keeps a fast and a slow exponential moving average of
+/// the LBD of learned clauses. When the recently learned clauses are of worse quality than those
+/// over the longer term, i.e. the fast average exceeds the slow average by some margin, the solver
+/// restarts.
+pub struct GlucoseRestarts {
+    fast: Ema,
+    slow: Ema,
+    /// How much larger the fast average has to be than the slow average before restarting.
+    margin: f64,
+    /// The minimum number of conflicts between two restarts.
+    min_conflicts: u64,
+    conflicts_since_restart: u64,
+}
+
+impl GlucoseRestarts {
+    pub fn new(margin: f64, min_conflicts: u64) -> Self {
+        GlucoseRestarts {
+            fast: Ema::new(1.0 / 32.0),
+            slow: Ema::new(1.0 / 4096.0),
+            margin,
+            min_conflicts,
+            conflicts_since_restart: 0,
+        }
+    }
+}
+
+impl Default for GlucoseRestarts {
+    fn default() -> Self {
+        GlucoseRestarts::new(1.25, 50)
+    }
+}
+
+impl RestartPolicy for GlucoseRestarts {
+    fn on_conflict(&mut self, lbd: u32) {
+        self.fast.update(lbd as f64);
+        self.slow.update(lbd as f64);
+        self.conflicts_since_restart += 1;
+    }
+
+    fn should_restart(&mut self) -> bool {
+        self.conflicts_since_restart >= self.min_conflicts
+            && self.fast.value() > self.margin * self.slow.value()
+    }
+
+    fn on_restart(&mut self) {
+        self.conflicts_since_restart = 0;
+    }
+}
+
+/// An exponential moving average. In the beginning, when few values have been observed, the
+/// smoothing factor is larger so the average is not biased towards the initial value of 0.
+struct Ema {
+    value: f64,
+    alpha: f64,
+    /// The smoothing factor currently in use. This decreases towards `alpha`.
+    beta: f64,
+}
+
+impl Ema {
+    fn new(alpha: f64) -> Self {
+        Ema {
+            value: 0.0,
+            alpha,
+            beta: 1.0,
+        }
+    }
+
+    fn update(&mut self, observation: f64) {
+        self.value += self.beta * (observation - self.value);
+
+        if self.beta > self.alpha {
+            self.beta = (self.beta * 0.5).max(self.alpha);
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn luby_sequence() {
+        let sequence = (1..=15).map(luby).collect::<Vec<_>>();
+
+        assert_eq!(vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8], sequence);
+    }
+
+    #[test]
+    fn luby_restarts_are_scaled_by_the_unit() {
+        let mut policy = LubyRestarts::new(2);
+        let mut intervals = vec![];
+
+        for _ in 0..4 {
+            let mut conflicts = 0;
+            while !policy.should_restart() {
+                policy.on_conflict(1);
+                conflicts += 1;
+            }
+
+            policy.on_restart();
+            intervals.push(conflicts);
+        }
+
+        assert_eq!(vec![2, 2, 4, 2], intervals);
+    }
+
+    #[test]
+    fn geometric_restart_intervals_grow() {
+        let mut policy = GeometricRestarts::new(10, 2.0);
+        let mut intervals = vec![];
+
+        for _ in 0..3 {
+            let mut conflicts = 0;
+            while !policy.should_restart() {
+                policy.on_conflict(1);
+                conflicts += 1;
+            }
+
+            policy.on_restart();
+            intervals.push(conflicts);
+        }
+
+        assert_eq!(vec![10, 20, 40], intervals);
+    }
+
+    #[test]
+    #[should_panic]
+    fn luby_restarts_need_a_positive_unit() {
+        LubyRestarts::new(0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn geometric_restart_intervals_need_to_grow() {
+        GeometricRestarts::new(10, 1.0);
+    }
+
+    #[test]
+    fn glucose_restarts_when_recent_lbd_increases() {
+        let mut policy = GlucoseRestarts::new(1.25, 10);
+
+        for _ in 0..1000 {
+            policy.on_conflict(4);
+        }
+        assert!(!policy.should_restart());
+
+        for _ in 0..50 {
+            policy.on_conflict(20);
+        }
+        assert!(policy.should_restart());
+
+        policy.on_restart();
+        assert!(!policy.should_restart());
+    }
+}
@@ -31,6 +31,12 @@ impl SearchTree {
         self.decided_at[lit.var()] = self.current_depth;
     }
 
+    /// Register that a literal has been assigned at the given decision level, which can be lower
+    /// than the current depth with chronological backtracking.
+    pub fn register_assignment_at(&mut self, lit: Lit, decision_level: usize) {
+        self.decided_at[lit.var()] = decision_level;
+    }
+
     /// Cut the search tree to the new depth.
     pub fn cut(&mut self, depth: usize) {
         self.current_depth = depth;
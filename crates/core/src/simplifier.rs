@@ -0,0 +1,824 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use log::trace;
+
+use crate::{
+    assignment::Assignment,
+    clause::{ClauseDb, ClauseId, ClauseRef},
+    lit::{Lit, Var},
+    proof::ProofLog,
+    storage::KeyedVec,
+};
+
+/// Variables are only eliminated if there are at most this many pairs of clauses to resolve.
+/// Eliminating variables with more occurrences tends to make the formula harder to solve, even if
+/// it gets smaller.
+const MAX_RESOLUTION_PAIRS: usize = 9;
+/// Clauses are not tested for being blocked on literals whose negation occurs in more clauses
+/// than this.
+const MAX_OCCURRENCES: usize = 16;
+/// Variables are not eliminated if that produces a resolvent with more literals than this.
+const MAX_RESOLVENT_LEN: usize = 20;
+
+/// Simplifies the formula before search with equivalent literal substitution, bounded variable
+/// elimination and blocked clause elimination, in the style of SatELite. All of them remove clauses
+/// from the formula while preserving satisfiability. The removed clauses are kept on a
+/// reconstruction stack, which is used to turn a solution of the simplified formula into a
+/// solution of the original formula.
+///
+/// Every removed clause has a witness, which is one of its literals. If a variable of a witness is
+/// used again, e.g. in a new clause, the removed clauses have to be restored first.
+#[derive(Default)]
+pub struct Simplifier {
+    /// For every literal, the irredundant clauses it occurs in. Only populated while simplifying.
+    occurrences: KeyedVec<Lit, Vec<ClauseRef>>,
+    /// Variables which may not be removed from the formula in the current simplification.
+    frozen: KeyedVec<Var, bool>,
+    /// For every variable, whether it occurs in a clause which was added to the formula since the
+    /// previous simplification. The other variables were already considered then, so only these
+    /// variables are considered again.
+    touched: KeyedVec<Var, bool>,
+    /// For every variable, whether it is the variable of the witness of a removed clause.
+    is_witness: KeyedVec<Var, bool>,
+    /// For every variable, whether it was eliminated and no longer occurs in the formula.
+    is_eliminated: KeyedVec<Var, bool>,
+    /// The removed clauses, in the order they were removed.
+    reconstruction_stack: Vec<RemovedClause>,
+    /// Marks the literals of the clause which is resolved or tested for being blocked.
+    marks: KeyedVec<Lit, bool>,
+    /// For every literal, the literals it implies through binary clauses, with the IDs of those
+    /// clauses. Only populated while substituting equivalent literals.
+    implications: KeyedVec<Lit, Vec<(Lit, ClauseId)>>,
+    num_vars: u32,
+}
+
+struct RemovedClause {
+    /// The literal which is made true if the clause is not satisfied by a solution.
+    witness: Lit,
+    lits: Box<[Lit]>,
+    id: ClauseId,
+}
+
+/// The outcome of simplifying the formula.
+#[derive(Debug, Default)]
+pub struct Simplification {
+    /// Unit clauses which were derived, with their IDs. They still have to be assigned.
+    pub units: Vec<(Lit, ClauseId)>,
+    /// The number of variables which were eliminated.
+    pub eliminated_vars: u64,
+    /// The number of clauses which were removed because they are blocked.
+    pub blocked_clauses: u64,
+    /// The number of variables which were substituted by an equivalent literal.
+    pub substituted_vars: u64,
+}
+
+/// A variable which is replaced by an equivalent literal, with the IDs of the clauses which state
+/// the equivalence.
+#[derive(Clone, Copy)]
+struct Substitution {
+    lit: Lit,
+    /// The ID of the clause `!var \/ lit`.
+    implies_lit: ClauseId,
+    /// The ID of the clause `var \/ !lit`.
+    implied_by_lit: ClauseId,
+}
+
+impl Simplifier {
+    pub fn grow_to(&mut self, var: Var) {
+        self.occurrences.grow_to(Lit::positive(var));
+        self.marks.grow_to(Lit::positive(var));
+        self.implications.grow_to(Lit::positive(var));
+        self.frozen.grow_to(var);
+        self.touched.grow_to(var);
+        self.is_witness.grow_to(var);
+        self.is_eliminated.grow_to(var);
+        self.num_vars = self.num_vars.max(var.code() + 1);
+    }
+
+    /// Mark the variables of a clause which is added to the formula, so the next simplification
+    /// considers them again, see [`Simplifier::is_touched`].
+    pub fn touch(&mut self, lits: &[Lit]) {
+        for lit in lits {
+            self.touched[lit.var()] = true;
+        }
+    }
+
+    /// Whether the variable occurs in a clause which was added to the formula since the previous
+    /// simplification. Only these variables are candidates for elimination, and only the clauses
+    /// with such a variable are tested for being blocked.
+    pub fn is_touched(&self, var: Var) -> bool {
+        self.touched[var]
+    }
+
+    /// Simplify the clauses in the database. The given assignment contains the assignments at the
+    /// root, which should be propagated to fixpoint. The variables of the frozen literals, e.g.
+    /// assumptions, are not removed from the formula.
+    ///
+    /// The resolvents and the substituted clauses which are added are logged to the proof. The
+    /// removed clauses are not deleted from the proof, so they can be restored later without
+    /// justification. Learned clauses which contain a removed variable are deleted.
+    pub fn simplify(
+        &mut self,
+        clauses: &mut ClauseDb,
+        assignment: &Assignment,
+        frozen: &[Lit],
+        mut proof: Option<&mut Box<dyn ProofLog>>,
+    ) -> Simplification {
+        let mut simplification = Simplification::default();
+
+        for lit in frozen {
+            self.frozen[lit.var()] = true;
+        }
+
+        self.substitute_equivalences(clauses, assignment, &mut proof, &mut simplification);
+
+        for (clause_ref, clause) in clauses.iter() {
+            // Clauses satisfied at the root remain satisfied, so they do not constrain any
+            // variable.
+            if clause.is_learned() || is_satisfied(clause.lits(), assignment) {
+                continue;
+            }
+
+            for &lit in clause.iter() {
+                self.occurrences[lit].push(clause_ref);
+            }
+        }
+
+        let mut candidates = self
+            .vars()
+            .filter(|&var| self.touched[var] && self.is_removable(var, assignment))
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|&var| {
+            self.occurrences[Lit::positive(var)].len() * self.occurrences[Lit::negative(var)].len()
+        });
+
+        for var in candidates {
+            if self.is_removable(var, assignment)
+                && self.eliminate(var, clauses, assignment, &mut proof, &mut simplification)
+            {
+                simplification.eliminated_vars += 1;
+            }
+        }
+
+        let irredundant = clauses
+            .iter()
+            .filter(|(_, clause)| !clause.is_learned() && !is_satisfied(clause.lits(), assignment))
+            .filter(|(_, clause)| clause.iter().any(|lit| self.touched[lit.var()]))
+            .map(|(clause_ref, _)| clause_ref)
+            .collect::<Vec<_>>();
+
+        for clause_ref in irredundant {
+            if self.eliminate_if_blocked(clause_ref, clauses, assignment) {
+                simplification.blocked_clauses += 1;
+            }
+        }
+
+        let mut learned_to_delete = vec![];
+        for (clause_ref, clause) in clauses.iter() {
+            if clause.is_learned() && clause.iter().any(|lit| self.is_witness[lit.var()]) {
+                learned_to_delete.push(clause_ref);
+            }
+        }
+
+        for clause_ref in learned_to_delete {
+            if let Some(proof) = proof.as_mut() {
+                let clause = &clauses[clause_ref];
+                proof.delete_clause(clause.id(), clause.lits());
+            }
+
+            clauses.delete_clause(clause_ref);
+        }
+
+        for occurrences in self.occurrences.iter_mut() {
+            occurrences.clear();
+        }
+
+        // Frozen variables stay touched, as they may no longer be frozen in the next
+        // simplification.
+        for (touched, frozen) in self.touched.iter_mut().zip(self.frozen.iter_mut()) {
+            *touched = *frozen;
+            *frozen = false;
+        }
+
+        trace!("simplification: {simplification:?}");
+
+        simplification
+    }
+
+    /// Replace every variable which is equivalent to another literal by the representative of its
+    /// equivalence class, in all clauses. The equivalence classes are the strongly connected
+    /// components of the implication graph of the binary clauses. The clauses which state the
+    /// equivalence of a substituted variable are removed with the variable as their witness, so
+    /// the reconstruction gives it the value of its representative.
+    fn substitute_equivalences(
+        &mut self,
+        clauses: &mut ClauseDb,
+        assignment: &Assignment,
+        proof: &mut Option<&mut Box<dyn ProofLog>>,
+        simplification: &mut Simplification,
+    ) {
+        for (_, clause) in clauses.iter() {
+            if clause.len() != 2 || clause.iter().any(|&lit| !assignment.is_unassigned(lit)) {
+                continue;
+            }
+
+            let [a, b] = [clause[0], clause[1]];
+            self.implications[!a].push((b, clause.id()));
+            self.implications[!b].push((a, clause.id()));
+        }
+
+        let (components, component_of) = self.strongly_connected_components();
+
+        let mut substitutions = KeyedVec::<Var, Option<Substitution>>::default();
+        if let Some(var) = self.vars().last() {
+            substitutions.grow_to(var);
+        }
+
+        for component in components.iter() {
+            if let Some(&lit) = component
+                .iter()
+                .find(|&&lit| component_of[!lit] == component_of[lit])
+            {
+                // The literal implies its own negation, so it is false.
+                let id = clauses.new_clause_id();
+                if let Some(proof) = proof.as_mut() {
+                    proof.add_clause(
+                        id,
+                        &[!lit],
+                        &self.implication_path(lit, !lit, &component_of),
+                    );
+                }
+
+                trace!("{lit:?} is equivalent to its negation");
+                self.frozen[lit.var()] = true;
+                simplification.units.push((!lit, id));
+                continue;
+            }
+
+            // Frozen variables are preferred as representatives, since they cannot be substituted.
+            let representative = component
+                .iter()
+                .copied()
+                .min_by_key(|lit| (!self.frozen[lit.var()], lit.var()))
+                .expect("components are not empty");
+
+            // The complementary component has the same representative variable, and only one of
+            // both is used for the substitution.
+            if !representative.is_positive() {
+                continue;
+            }
+
+            for &lit in component.iter() {
+                let var = lit.var();
+                if var == representative.var() || self.frozen[var] {
+                    continue;
+                }
+
+                let x = Lit::positive(var);
+                let equivalent = if lit.is_positive() {
+                    representative
+                } else {
+                    !representative
+                };
+
+                trace!("substituting {x:?} by {equivalent:?}");
+
+                let implies_lit = clauses.new_clause_id();
+                let implied_by_lit = clauses.new_clause_id();
+                let forward = [!x, equivalent];
+                let backward = [x, !equivalent];
+                if let Some(proof) = proof.as_mut() {
+                    let hints = self.implication_path(x, equivalent, &component_of);
+                    proof.add_clause(implies_lit, &forward, &hints);
+                    let hints = self.implication_path(equivalent, x, &component_of);
+                    proof.add_clause(implied_by_lit, &backward, &hints);
+                }
+
+                self.reconstruction_stack.push(RemovedClause {
+                    witness: !x,
+                    lits: forward.into(),
+                    id: implies_lit,
+                });
+                self.reconstruction_stack.push(RemovedClause {
+                    witness: x,
+                    lits: backward.into(),
+                    id: implied_by_lit,
+                });
+                self.is_witness[var] = true;
+                self.is_eliminated[var] = true;
+
+                substitutions[var] = Some(Substitution {
+                    lit: equivalent,
+                    implies_lit,
+                    implied_by_lit,
+                });
+                simplification.substituted_vars += 1;
+            }
+        }
+
+        for implications in self.implications.iter_mut() {
+            implications.clear();
+        }
+
+        if simplification.substituted_vars > 0 {
+            self.substitute(clauses, &substitutions, proof, simplification);
+        }
+    }
+
+    /// Replace the substituted variables in all clauses. Clauses which become tautologies are
+    /// deleted.
+    fn substitute(
+        &mut self,
+        clauses: &mut ClauseDb,
+        substitutions: &KeyedVec<Var, Option<Substitution>>,
+        proof: &mut Option<&mut Box<dyn ProofLog>>,
+        simplification: &mut Simplification,
+    ) {
+        let to_substitute = clauses
+            .iter()
+            .filter(|(_, clause)| clause.iter().any(|lit| substitutions[lit.var()].is_some()))
+            .map(|(clause_ref, _)| clause_ref)
+            .collect::<Vec<_>>();
+
+        for clause_ref in to_substitute {
+            let clause = &clauses[clause_ref];
+            let (id, lbd, is_learned) = (clause.id(), clause.lbd(), clause.is_learned());
+
+            // Under the negation of the new clause, the equivalences make the substituted literals
+            // false, after which the clause is falsified.
+            let mut hints = vec![];
+            let mut lits = vec![];
+            for &lit in clause.iter() {
+                match substitutions[lit.var()] {
+                    Some(substitution) if lit.is_positive() => {
+                        lits.push(substitution.lit);
+                        hints.push(substitution.implies_lit);
+                    }
+                    Some(substitution) => {
+                        lits.push(!substitution.lit);
+                        hints.push(substitution.implied_by_lit);
+                    }
+                    None => lits.push(lit),
+                }
+            }
+            hints.push(id);
+
+            lits.sort();
+            lits.dedup();
+            let is_tautology = lits.windows(2).any(|pair| pair[0] == !pair[1]);
+
+            // The new clause is derived before the old clause is deleted from the proof.
+            let old_lits = clause.lits().to_vec();
+
+            if !is_tautology {
+                let new_id = if lits.len() == 1 {
+                    self.frozen[lits[0].var()] = true;
+                    let new_id = clauses.new_clause_id();
+                    simplification.units.push((lits[0], new_id));
+                    new_id
+                } else if is_learned {
+                    let new_ref = clauses.add_learned_clause(&lits, lbd.min(lits.len() as u32));
+                    clauses[new_ref].id()
+                } else {
+                    let new_id = clauses.new_clause_id();
+                    clauses.add_clause(&lits, new_id);
+                    new_id
+                };
+
+                if let Some(proof) = proof.as_mut() {
+                    proof.add_clause(new_id, &lits, &hints);
+                }
+            }
+
+            if let Some(proof) = proof.as_mut() {
+                proof.delete_clause(id, &old_lits);
+            }
+            clauses.delete_clause(clause_ref);
+        }
+    }
+
+    /// Tarjan's algorithm on the implication graph, without recursion. Returns the components with
+    /// more than one literal, and the index of the component of every literal.
+    fn strongly_connected_components(&self) -> (Vec<Vec<Lit>>, KeyedVec<Lit, usize>) {
+        let mut index = KeyedVec::<Lit, usize>::default();
+        let mut low_link = KeyedVec::<Lit, usize>::default();
+        let mut on_stack = KeyedVec::<Lit, bool>::default();
+        let mut component_of = KeyedVec::<Lit, usize>::default();
+        if let Some(var) = self.vars().last() {
+            index.grow_to(Lit::positive(var));
+            low_link.grow_to(Lit::positive(var));
+            on_stack.grow_to(Lit::positive(var));
+            component_of.grow_to_with(Lit::positive(var), usize::MAX);
+        }
+
+        let mut components = vec![];
+        let mut num_components = 0;
+        let mut next_index = 1;
+        let mut stack = vec![];
+        let mut calls = vec![];
+
+        let lits = self
+            .vars()
+            .flat_map(|var| [Lit::positive(var), Lit::negative(var)]);
+        for root in lits {
+            if index[root] != 0 || self.implications[root].is_empty() {
+                continue;
+            }
+
+            index[root] = next_index;
+            low_link[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            calls.push((root, 0));
+
+            while let Some((lit, edge)) = calls.last_mut() {
+                let lit = *lit;
+
+                if let Some(&(implied, _)) = self.implications[lit].get(*edge) {
+                    *edge += 1;
+
+                    if index[implied] == 0 {
+                        index[implied] = next_index;
+                        low_link[implied] = next_index;
+                        next_index += 1;
+                        stack.push(implied);
+                        on_stack[implied] = true;
+                        calls.push((implied, 0));
+                    } else if on_stack[implied] {
+                        low_link[lit] = low_link[lit].min(index[implied]);
+                    }
+
+                    continue;
+                }
+
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    low_link[parent] = low_link[parent].min(low_link[lit]);
+                }
+
+                if low_link[lit] != index[lit] {
+                    continue;
+                }
+
+                let mut component = vec![];
+                loop {
+                    let member = stack.pop().expect("the literal is on the stack");
+                    on_stack[member] = false;
+                    component_of[member] = num_components;
+                    component.push(member);
+
+                    if member == lit {
+                        break;
+                    }
+                }
+
+                num_components += 1;
+                if component.len() > 1 {
+                    components.push(component);
+                }
+            }
+        }
+
+        (components, component_of)
+    }
+
+    /// The IDs of the binary clauses on a shortest path of implications from one literal to
+    /// another in the same component.
+    fn implication_path(
+        &self,
+        from: Lit,
+        to: Lit,
+        component_of: &KeyedVec<Lit, usize>,
+    ) -> Vec<ClauseId> {
+        let mut parents = BTreeMap::new();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(lit) = queue.pop_front() {
+            if lit == to {
+                break;
+            }
+
+            for &(implied, id) in self.implications[lit].iter() {
+                if component_of[implied] == component_of[from]
+                    && implied != from
+                    && !parents.contains_key(&implied)
+                {
+                    parents.insert(implied, (lit, id));
+                    queue.push_back(implied);
+                }
+            }
+        }
+
+        let mut path = vec![];
+        let mut lit = to;
+        while lit != from {
+            let (parent, id) = parents[&lit];
+            path.push(id);
+            lit = parent;
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Eliminate the variable by replacing the clauses it occurs in by all their non-tautological
+    /// resolvents on the variable. The variable is only eliminated if this does not increase the
+    /// number of clauses, and no resolvent is too long.
+    fn eliminate(
+        &mut self,
+        var: Var,
+        clauses: &mut ClauseDb,
+        assignment: &Assignment,
+        proof: &mut Option<&mut Box<dyn ProofLog>>,
+        simplification: &mut Simplification,
+    ) -> bool {
+        let positive = std::mem::take(&mut self.occurrences[Lit::positive(var)]);
+        let negative = std::mem::take(&mut self.occurrences[Lit::negative(var)]);
+
+        let max_resolvents = positive.len() + negative.len();
+        let max_resolvent_lits = positive
+            .iter()
+            .chain(negative.iter())
+            .map(|&clause_ref| clauses[clause_ref].len())
+            .sum::<usize>();
+
+        let mut resolvents = vec![];
+        let mut resolvent_lits = 0;
+        let mut is_bounded = true;
+
+        'resolve: for &p in positive.iter() {
+            for &n in negative.iter() {
+                let Some(resolvent) = self.resolve(clauses[p].lits(), clauses[n].lits(), var)
+                else {
+                    continue;
+                };
+
+                resolvent_lits += resolvent.len();
+
+                if resolvent.len() > MAX_RESOLVENT_LEN
+                    || resolvents.len() == max_resolvents
+                    || resolvent_lits > max_resolvent_lits
+                {
+                    is_bounded = false;
+                    break 'resolve;
+                }
+
+                resolvents.push((resolvent, clauses[p].id(), clauses[n].id()));
+            }
+        }
+
+        if !is_bounded {
+            self.occurrences[Lit::positive(var)] = positive;
+            self.occurrences[Lit::negative(var)] = negative;
+            return false;
+        }
+
+        trace!(
+            "eliminating {var:?}, replacing {} clauses by {} resolvents",
+            positive.len() + negative.len(),
+            resolvents.len()
+        );
+
+        for clause_ref in positive {
+            self.remove_clause(clause_ref, Lit::positive(var), clauses);
+        }
+
+        for clause_ref in negative {
+            self.remove_clause(clause_ref, Lit::negative(var), clauses);
+        }
+
+        self.is_eliminated[var] = true;
+
+        for (resolvent, positive_id, negative_id) in resolvents {
+            // Assuming the negation of the resolvent, the positive antecedent propagates the
+            // variable, after which the negative antecedent is falsified.
+            let id = clauses.new_clause_id();
+            if let Some(proof) = proof.as_mut() {
+                proof.add_clause(id, &resolvent, &[positive_id, negative_id]);
+            }
+
+            if is_satisfied(&resolvent, assignment) {
+                continue;
+            }
+
+            if resolvent.len() == 1 {
+                self.frozen[resolvent[0].var()] = true;
+                simplification.units.push((resolvent[0], id));
+                continue;
+            }
+
+            let clause_ref = clauses.add_clause(&resolvent, id);
+            for &lit in resolvent.iter() {
+                self.occurrences[lit].push(clause_ref);
+            }
+        }
+
+        true
+    }
+
+    /// Resolve the clause with the positive literal of the variable with the clause with the
+    /// negative literal. Returns `None` if the resolvent is a tautology.
+    fn resolve(&mut self, positive: &[Lit], negative: &[Lit], var: Var) -> Option<Vec<Lit>> {
+        let mut resolvent = positive
+            .iter()
+            .copied()
+            .filter(|lit| lit.var() != var)
+            .collect::<Vec<_>>();
+
+        for &lit in resolvent.iter() {
+            self.marks[lit] = true;
+        }
+
+        let mut is_tautology = false;
+        for &lit in negative.iter().filter(|lit| lit.var() != var) {
+            if self.marks[!lit] {
+                is_tautology = true;
+                break;
+            }
+
+            if !self.marks[lit] {
+                resolvent.push(lit);
+            }
+        }
+
+        for &lit in positive.iter() {
+            self.marks[lit] = false;
+        }
+
+        (!is_tautology).then_some(resolvent)
+    }
+
+    /// Remove the clause if it is blocked on one of its literals: every resolvent on that literal
+    /// with the other irredundant clauses is a tautology.
+    fn eliminate_if_blocked(
+        &mut self,
+        clause_ref: ClauseRef,
+        clauses: &mut ClauseDb,
+        assignment: &Assignment,
+    ) -> bool {
+        if clauses[clause_ref].is_deleted() {
+            return false;
+        }
+
+        for &lit in clauses[clause_ref].iter() {
+            self.marks[lit] = true;
+        }
+
+        let blocking_lit = clauses[clause_ref].iter().copied().find(|&lit| {
+            let var = lit.var();
+            if self.frozen[var] || assignment.value(lit).is_some() {
+                return false;
+            }
+
+            let resolution_partners = &self.occurrences[!lit];
+            resolution_partners.len() <= MAX_OCCURRENCES
+                && resolution_partners.iter().all(|&partner| {
+                    clauses[partner]
+                        .iter()
+                        .any(|&other| other.var() != var && self.marks[!other])
+                })
+        });
+
+        for &lit in clauses[clause_ref].iter() {
+            self.marks[lit] = false;
+        }
+
+        let Some(blocking_lit) = blocking_lit else {
+            return false;
+        };
+
+        trace!(
+            "removing {:?}, which is blocked on {blocking_lit:?}",
+            &clauses[clause_ref]
+        );
+
+        self.remove_clause(clause_ref, blocking_lit, clauses);
+        true
+    }
+
+    /// Remove the clause from the formula and push it onto the reconstruction stack.
+    fn remove_clause(&mut self, clause_ref: ClauseRef, witness: Lit, clauses: &mut ClauseDb) {
+        let clause = &clauses[clause_ref];
+
+        for &lit in clause.iter() {
+            self.occurrences[lit].retain(|&other| other != clause_ref);
+        }
+
+        self.reconstruction_stack.push(RemovedClause {
+            witness,
+            lits: clause.lits().into(),
+            id: clause.id(),
+        });
+        self.is_witness[witness.var()] = true;
+
+        clauses.delete_clause(clause_ref);
+    }
+
+    /// Whether the given variable is the variable of the witness of a removed clause. Before such a
+    /// variable is used in a new clause or an assumption, the clauses have to be restored with
+    /// [`Simplifier::restore`].
+    pub fn is_witness(&self, var: Var) -> bool {
+        self.is_witness[var]
+    }
+
+    /// Whether the given variable was eliminated. Such a variable does not occur in the formula, so
+    /// it should not be decided on. It gets its value when the solution is reconstructed.
+    pub fn is_eliminated(&self, var: Var) -> bool {
+        self.is_eliminated[var]
+    }
+
+    /// The variables which were eliminated.
+    pub fn eliminated_vars(&self) -> impl Iterator<Item = Var> + '_ {
+        self.vars().filter(|&var| self.is_eliminated[var])
+    }
+
+    /// Take the removed clauses which have to be added back to the formula before the given
+    /// variables can be used again. These are the clauses with a witness on one of the variables,
+    /// and the clauses with a witness on the variables of restored clauses.
+    pub fn restore(
+        &mut self,
+        vars: impl IntoIterator<Item = Var>,
+        restored: &mut Vec<(Box<[Lit]>, ClauseId)>,
+    ) {
+        let mut to_restore = vars
+            .into_iter()
+            .filter(|&var| self.is_witness[var])
+            .collect::<Vec<_>>();
+
+        while let Some(var) = to_restore.pop() {
+            if !self.is_witness[var] {
+                continue;
+            }
+
+            trace!("restoring the clauses removed by {var:?}");
+            self.is_witness[var] = false;
+            self.is_eliminated[var] = false;
+
+            let mut idx = 0;
+            while idx < self.reconstruction_stack.len() {
+                if self.reconstruction_stack[idx].witness.var() != var {
+                    idx += 1;
+                    continue;
+                }
+
+                let removed = self.reconstruction_stack.remove(idx);
+                self.touch(&removed.lits);
+                to_restore.extend(
+                    removed
+                        .lits
+                        .iter()
+                        .map(|lit| lit.var())
+                        .filter(|&var| self.is_witness[var]),
+                );
+                restored.push((removed.lits, removed.id));
+            }
+        }
+    }
+
+    /// Take all removed clauses, see [`Simplifier::restore`].
+    pub fn restore_all(&mut self, restored: &mut Vec<(Box<[Lit]>, ClauseId)>) {
+        for removed in self.reconstruction_stack.drain(..) {
+            restored.push((removed.lits, removed.id));
+        }
+
+        for is_witness in self.is_witness.iter_mut() {
+            *is_witness = false;
+        }
+
+        for is_eliminated in self.is_eliminated.iter_mut() {
+            *is_eliminated = false;
+        }
+    }
+
+    /// Turn a solution of the simplified formula into a solution of the original formula. Every
+    /// removed clause which is not satisfied is satisfied by flipping its witness, going from the
+    /// last removed clause to the first. All variables have to be assigned.
+    pub fn reconstruct(&self, assignment: &mut Assignment) {
+        for removed in self.reconstruction_stack.iter().rev() {
+            if !is_satisfied(&removed.lits, assignment) {
+                assignment.unassign(removed.witness);
+                assignment.assign(removed.witness);
+            }
+        }
+    }
+
+    fn vars(&self) -> impl Iterator<Item = Var> + '_ {
+        (0..self.num_vars).map(|code| Var::try_from(code).expect("valid var code"))
+    }
+
+    fn is_removable(&self, var: Var, assignment: &Assignment) -> bool {
+        let num_positive = self.occurrences[Lit::positive(var)].len();
+        let num_negative = self.occurrences[Lit::negative(var)].len();
+
+        !self.frozen[var]
+            && !self.is_witness[var]
+            && assignment.is_unassigned(Lit::positive(var))
+            && num_positive + num_negative > 0
+            && num_positive * num_negative <= MAX_RESOLUTION_PAIRS
+    }
+}
+
+fn is_satisfied(lits: &[Lit], assignment: &Assignment) -> bool {
+    lits.iter().any(|&lit| assignment.value(lit) == Some(true))
+}
@@ -1,4 +1,4 @@
-use std::fmt::Debug;
+use std::{fmt::Debug, time::Instant};
 
 use log::trace;
 
@@ -6,29 +6,62 @@ use crate::{
     analysis::ConflictAnalyzer,
     assignment::Assignment,
     brancher::Brancher,
-    clause::{ClauseDb, ClauseRef},
+    clause::{ClauseDb, ClauseId, ClauseRef},
     domains::{
         Conflict, DomainFactory, DomainId, DomainStore, GlobalDomainIdPool, UntypedDomainId,
     },
     implication_graph::ImplicationGraph,
     integer::{BoundedInt, Int},
     lit::{Lit, Var},
+    local_search::LocalSearch,
+    mode::{ModeSwitching, SearchMode},
+    phase::Phases,
     preprocessor::{ClausePreProcessor, PreProcessedClause},
+    proof::{PbConstraint, ProofLog},
     propagation::{
         Context, LitWatch, Propagator, PropagatorFactory, PropagatorId, PropagatorQueue, Reason,
         VariableRegistrar, WatchList,
     },
+    restart::RestartPolicy,
     search_tree::SearchTree,
-    storage::{Arena, StaticIndexer},
+    simplifier::Simplifier,
+    statistics::SolverStatistics,
+    storage::{Arena, KeyedVec, StaticIndexer},
+    subsumption::Subsumer,
     termination::Terminator,
     trail::Trail,
 };
 
+/// The number of conflicts before the learned clause database is reduced for the first time.
+const FIRST_REDUCTION: u64 = 2000;
+/// The number of conflicts by which the interval between two reductions grows.
+const REDUCTION_INCREMENT: u64 = 300;
+/// The maximum number of variables which are probed when the formula is simplified.
+const MAX_PROBED_VARS: usize = 10000;
+/// The number of conflicts between two rounds of subsumption on the learned clauses. A round
+/// starts the next time the solver is at the root.
+const SUBSUMPTION_INTERVAL: u64 = 5000;
+/// The number of conflicts between two rounds of vivification on the learned clauses. A round
+/// starts the next time the solver is at the root.
+const VIVIFICATION_INTERVAL: u64 = 3000;
+/// The maximum number of learned clauses which are vivified in a round.
+const MAX_VIVIFIED_CLAUSES: usize = 500;
+/// The number of flips in a round of local search, per clause it has to satisfy.
+const LOCAL_SEARCH_FLIPS_PER_CLAUSE: u64 = 10;
+/// The maximum number of flips in a round of local search.
+const MAX_LOCAL_SEARCH_FLIPS: u64 = 1 << 20;
+
 pub struct Solver<Domains, Event> {
     domains: Domains,
     domain_id_pool: GlobalDomainIdPool,
+    /// The literals whose variables are kept in the formula when it is simplified.
+    frozen_lits: Vec<Lit>,
 
     preprocessor: ClausePreProcessor,
+    simplifier: Simplifier,
+    subsumer: Subsumer,
+    /// Whether clauses were added since the formula was last simplified.
+    needs_simplification: bool,
     analyzer: ConflictAnalyzer,
     clauses: ClauseDb,
     implication_graph: ImplicationGraph<Domains>,
@@ -36,6 +69,8 @@ pub struct Solver<Domains, Event> {
     state: State,
     propagators: Arena<PropagatorId, Box<dyn Propagator<Domains, Event>>>,
     propagator_queue: PropagatorQueue,
+    /// The ID of the definition of every propagator in the proof, if it has one.
+    definitions: KeyedVec<PropagatorId, Option<ClauseId>>,
 
     trail: Trail,
     assignment: Assignment,
@@ -43,6 +78,26 @@ pub struct Solver<Domains, Event> {
     next_propagation_idx: usize,
     watch_list: WatchList<Event>,
     next_var_code: u32,
+
+    reduction_interval: u64,
+    conflicts_until_reduction: u64,
+    conflicts_until_subsumption: u64,
+    conflicts_until_vivification: u64,
+    /// The largest ID of the clauses which were candidates in the previous round of vivification.
+    last_vivified_id: Option<ClauseId>,
+    /// The largest number of decision levels the solver backjumps over after a conflict. Longer
+    /// backjumps are replaced by chronological backtracking. `None` if the solver always
+    /// backjumps.
+    max_backjump: Option<usize>,
+    /// When the search switches between focused and stable mode. `None` if the search does not
+    /// switch modes.
+    mode_switching: Option<ModeSwitching>,
+
+    proof: Option<Box<dyn ProofLog>>,
+    /// The hints to derive the empty clause, if it has not been logged yet.
+    empty_clause_hints: Option<Vec<ClauseId>>,
+
+    statistics: SolverStatistics,
 }
 
 pub trait ExtendSolver<Domains, Event> {
@@ -76,6 +131,7 @@ where
         Solver {
             domains: Default::default(),
             domain_id_pool: Default::default(),
+            frozen_lits: vec![],
             clauses: Default::default(),
             search_tree: Default::default(),
             state: Default::default(),
@@ -85,10 +141,24 @@ where
             watch_list: Default::default(),
             next_var_code: 0,
             preprocessor: Default::default(),
+            simplifier: Default::default(),
+            subsumer: Default::default(),
+            needs_simplification: false,
             analyzer: Default::default(),
             implication_graph: Default::default(),
             propagators: Default::default(),
             propagator_queue: Default::default(),
+            definitions: Default::default(),
+            reduction_interval: FIRST_REDUCTION,
+            conflicts_until_reduction: FIRST_REDUCTION,
+            conflicts_until_subsumption: SUBSUMPTION_INTERVAL,
+            conflicts_until_vivification: VIVIFICATION_INTERVAL,
+            last_vivified_id: None,
+            max_backjump: None,
+            mode_switching: None,
+            proof: None,
+            empty_clause_hints: None,
+            statistics: Default::default(),
         }
     }
 }
@@ -102,16 +172,32 @@ where
         Factory: DomainFactory<Event>,
         Domains: DomainStore<Factory::Domain>,
     {
+        self.backtrack_to_root();
+
         let global_id = self.domain_id_pool.next_id();
         self.watch_list.grow_to_domain(global_id);
 
+        let first_var_code = self.next_var_code;
         let domain = factory.create(&mut DomainFactoryContext {
             solver: self,
             untyped_domain_id: global_id,
         });
 
+        // Branchers may decide on the literals of the domain.
+        for code in first_var_code..self.next_var_code {
+            self.freeze(Lit::positive(Var::try_from(code).expect("valid var code")));
+        }
+
         self.domains.alloc(global_id, domain)
     }
+
+    /// The domain with the given ID, e.g. for a brancher to keep its own copy.
+    pub fn domain<Dom>(&self, domain: DomainId<Dom>) -> &Dom
+    where
+        Domains: DomainStore<Dom>,
+    {
+        &self.domains[domain]
+    }
 }
 
 impl<Domains, Event> Solver<Domains, Event>
@@ -125,7 +211,61 @@ where
         }
     }
 
+    /// Add a clause to the formula. This can also be done after a call to [`Solver::solve`], in
+    /// which case the solver first backtracks to the root.
     pub fn add_clause(&mut self, lits: impl IntoIterator<Item = Lit>) {
+        // Every clause gets an ID, even if it is not stored, so the IDs of the clauses in a formula
+        // match the order in which they are added.
+        let id = self.clauses.new_clause_id();
+
+        if self.state == State::ConflictAtRoot {
+            return;
+        }
+
+        self.backtrack_to_root();
+
+        let lits = match self.preprocessor.preprocess(lits, &self.assignment) {
+            PreProcessedClause::Satisfiable => return,
+            PreProcessedClause::Lits(lits) => lits.to_vec(),
+        };
+
+        if let Some(proof) = &mut self.proof {
+            proof.add_axiom(id, &PbConstraint::clause(&lits));
+        }
+
+        self.restore_removed_clauses(lits.iter().map(|lit| lit.var()));
+        self.simplifier.touch(&lits);
+        self.insert_clause(lits, id);
+        self.needs_simplification = true;
+    }
+}
+
+impl<Domains, Event> Solver<Domains, Event> {
+    /// Add the clauses which were removed by simplification back to the formula, so the given
+    /// variables can be used again.
+    fn restore_removed_clauses(&mut self, vars: impl IntoIterator<Item = Var>) {
+        let mut restored = vec![];
+        self.simplifier.restore(vars, &mut restored);
+        self.insert_restored_clauses(restored);
+    }
+
+    fn insert_restored_clauses(&mut self, restored: Vec<(Box<[Lit]>, ClauseId)>) {
+        if restored.is_empty() {
+            return;
+        }
+
+        self.backtrack_to_root();
+
+        for (lits, id) in restored {
+            trace!("restoring clause {lits:?}");
+            self.insert_clause(lits.iter().copied(), id);
+        }
+    }
+
+    /// Insert a clause with the given ID into the formula. This happens at the root. The clause is
+    /// either stored in the clause database, or it assigns a literal at the root. The clause is not
+    /// logged to the proof.
+    fn insert_clause(&mut self, lits: impl IntoIterator<Item = Lit>, id: ClauseId) {
         if self.state == State::ConflictAtRoot {
             return;
         }
@@ -137,14 +277,20 @@ where
             };
 
             if lits.is_empty() {
-                self.state = State::ConflictAtRoot;
+                self.conflict_at_root(vec![id]);
                 return;
             }
 
             if lits.len() > 1 {
-                let clause_ref = self.clauses.add_clause(lits);
+                let clause_ref = self.clauses.add_clause(lits, id);
                 trace!("adding clause {lits:?} with id {clause_ref:?}");
 
+                // The false literals are at the end. If the clause is watched by a false literal,
+                // it is unit or conflicting, which is found by propagating the root again.
+                if self.assignment.value(lits[1]) == Some(false) {
+                    self.next_propagation_idx = 0;
+                }
+
                 self.watch_clause(clause_ref);
                 return;
             }
@@ -152,57 +298,243 @@ where
             lits[0]
         };
 
-        if !self.enqueue(root_assignment, Reason::Decision) {
-            self.state = State::ConflictAtRoot;
+        if !self.enqueue(root_assignment, Reason::Decision, &mut Unobserved) {
+            let hints = self
+                .clauses
+                .unit_id(root_assignment.var())
+                .into_iter()
+                .chain([id])
+                .collect();
+
+            self.conflict_at_root(hints);
+            return;
         }
 
+        self.clauses.set_unit_id(root_assignment.var(), id);
+
         trace!("adding clause [{root_assignment:?}] as assignment");
     }
 
     fn watch_clause(&mut self, clause_ref: ClauseRef) {
         trace!("setting up watchers for {clause_ref:?}");
         let clause = &self.clauses[clause_ref];
-        self.watch_list[clause[0]].push(clause_ref.into());
-        self.watch_list[clause[1]].push(clause_ref.into());
+
+        if clause.len() == 2 {
+            self.watch_list[clause[0]].push(LitWatch::BinaryClause {
+                clause_ref,
+                other: clause[1],
+            });
+            self.watch_list[clause[1]].push(LitWatch::BinaryClause {
+                clause_ref,
+                other: clause[0],
+            });
+        } else {
+            self.watch_list[clause[0]].push(LitWatch::Clause {
+                clause_ref,
+                blocker: clause[1],
+            });
+            self.watch_list[clause[1]].push(LitWatch::Clause {
+                clause_ref,
+                blocker: clause[0],
+            });
+        }
+    }
+
+    fn unwatch_clause(&mut self, clause_ref: ClauseRef) {
+        trace!("removing watchers for {clause_ref:?}");
+        let clause = &self.clauses[clause_ref];
+        self.watch_list.remove_clause_watch(clause[0], clause_ref);
+        self.watch_list.remove_clause_watch(clause[1], clause_ref);
+    }
+
+    /// Remove learned clauses which are unlikely to be useful in the remainder of the search.
+    /// Clauses which are the reason for an assignment are kept.
+    fn reduce_learned_clauses(&mut self) {
+        let assignment = &self.assignment;
+        let implication_graph = &self.implication_graph;
+
+        let mut to_delete = vec![];
+        self.clauses.select_clauses_to_delete(
+            |clause_ref, clause| {
+                // Binary clauses are not reordered when they propagate, so either of the watched
+                // literals can be the propagated literal.
+                clause.iter().take(2).any(|&propagated| {
+                    assignment.value(propagated) == Some(true)
+                        && implication_graph.reason(propagated.var()).clause_ref()
+                            == Some(clause_ref)
+                })
+            },
+            &mut to_delete,
+        );
+
+        trace!("reducing {} learned clauses", to_delete.len());
+
+        for clause_ref in to_delete {
+            self.unwatch_clause(clause_ref);
+
+            if let Some(proof) = &mut self.proof {
+                let clause = &self.clauses[clause_ref];
+                proof.delete_clause(clause.id(), clause.lits());
+            }
+
+            self.clauses.delete_clause(clause_ref);
+        }
+
+        self.collect_garbage();
+    }
+
+    /// Compact the clause database once enough of it is taken up by deleted clauses, see
+    /// [`ClauseDb::collect_garbage`]. The watches, and the reasons of the assigned literals, are
+    /// updated to the new clause references.
+    fn collect_garbage(&mut self) {
+        if !self.clauses.needs_garbage_collection() {
+            return;
+        }
+
+        let relocation = self.clauses.collect_garbage();
+        self.watch_list.relocate_clauses(&relocation);
+        self.implication_graph
+            .relocate_clauses(self.trail.iter().map(|lit| lit.var()), &relocation);
     }
 
-    fn enqueue(&mut self, lit: Lit, reason: Reason<Domains>) -> bool {
+    /// Assign the literal for the given reason, and tell the brancher about it. Returns `false` if
+    /// the literal is already false.
+    fn enqueue(&mut self, lit: Lit, reason: Reason<Domains>, brancher: &mut impl Brancher) -> bool {
         if let Some(false) = self.assignment.value(lit) {
             return false;
         }
 
+        let decision_level = self.propagation_level(&reason);
+        if decision_level == 0 {
+            self.log_root_unit(lit, &reason);
+        }
+
         self.trail.enqueue(lit);
         self.assignment.assign(lit);
         self.implication_graph.add(lit.var(), reason);
-        self.search_tree.register_assignment(lit);
+        self.search_tree.register_assignment_at(lit, decision_level);
+        brancher.on_variable_assigned(lit);
 
         true
     }
 
+    /// The decision level at which a literal is assigned for the given reason. With chronological
+    /// backtracking, a literal propagated by a clause gets the highest decision level of the other
+    /// literals in the clause, which can be lower than the current depth. Otherwise, and for
+    /// decisions, it is the current depth.
+    fn propagation_level(&self, reason: &Reason<Domains>) -> usize {
+        if self.max_backjump.is_none() {
+            return self.search_tree.depth();
+        }
+
+        let level = |lit: &Lit| self.search_tree.decision_level(lit.var());
+
+        match reason {
+            Reason::Clause(clause_ref) => self.clauses[*clause_ref]
+                .iter()
+                .skip(1)
+                .map(level)
+                .max()
+                .unwrap_or(0),
+            Reason::BinaryClause { lits, .. } => level(&lits[1]),
+            Reason::Decision | Reason::Explanation { .. } => self.search_tree.depth(),
+        }
+    }
+
+    /// The highest decision level among the literals of the conflict.
+    fn conflict_level(&self, conflict: &Conflict<Domains>) -> usize {
+        conflict
+            .lits(&self.clauses, &self.domains)
+            .iter()
+            .map(|lit| self.search_tree.decision_level(lit.var()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Derive the unit clause of a literal which is propagated by a clause at the root, so later
+    /// proof steps can refer to it.
+    fn log_root_unit(&mut self, lit: Lit, reason: &Reason<Domains>) {
+        let Some(clause_ref) = reason.clause_ref() else {
+            return;
+        };
+
+        let Some(proof) = self.proof.as_mut().filter(|proof| proof.needs_hints()) else {
+            return;
+        };
+
+        let clause = &self.clauses[clause_ref];
+        let hints = clause
+            .iter()
+            .filter(|p| p.var() != lit.var())
+            .filter_map(|p| self.clauses.unit_id(p.var()))
+            .chain([clause.id()])
+            .collect::<Vec<_>>();
+
+        let id = self.clauses.new_clause_id();
+        proof.add_clause(id, &[lit], &hints);
+        self.clauses.set_unit_id(lit.var(), id);
+    }
+
+    /// The hints to derive the empty clause from a conflict at the root: the unit clauses of the
+    /// literals in the conflict, followed by the conflicting clause.
+    fn root_conflict_hints(&self, conflict: &Conflict<Domains>) -> Vec<ClauseId> {
+        let mut hints = conflict
+            .lits(&self.clauses, &self.domains)
+            .iter()
+            .filter_map(|lit| self.clauses.unit_id(lit.var()))
+            .collect::<Vec<_>>();
+
+        if let Conflict::Clause(clause_ref) = conflict {
+            hints.push(self.clauses[*clause_ref].id());
+        }
+
+        hints
+    }
+
     fn backtrack_to(&mut self, decision_level: usize, brancher: &mut impl Brancher) {
-        self.trail.backtrack_to(decision_level).for_each(|lit| {
-            self.assignment.unassign(lit);
-            brancher.on_variable_unassigned(lit.var());
-        });
+        brancher.on_backtrack(&self.trail);
+
+        // The literals which are kept on the trail are moved, so their watches are processed
+        // again.
+        let first_kept_idx = self.trail.level_end(decision_level);
+
+        self.trail
+            .backtrack_to(decision_level, &self.search_tree)
+            .for_each(|lit| {
+                self.assignment.unassign(lit);
+                brancher.on_variable_unassigned(lit);
+            });
 
         self.search_tree.cut(decision_level);
-        self.next_propagation_idx = self.trail.len();
+        self.next_propagation_idx = first_kept_idx;
     }
+}
 
-    fn propagate(&mut self) -> Result<(), Conflict<Domains>> {
+impl<Domains, Event> Solver<Domains, Event>
+where
+    Event: Copy + Debug + StaticIndexer,
+{
+    fn propagate(&mut self, brancher: &mut impl Brancher) -> Result<(), Conflict<Domains>> {
         trace!("propagating...");
-        self.propagate_propositional()?;
+        self.propagate_propositional(brancher)?;
 
         while let Some(propagator_id) = self.propagator_queue.pop() {
-            self.propagate_propagator(propagator_id)?;
-            self.propagate_propositional()?;
+            self.propagate_propagator(propagator_id, brancher)?;
+            self.propagate_propositional(brancher)?;
         }
 
         Ok(())
     }
 
-    fn propagate_propagator(&mut self, propagator_id: PropagatorId) -> Result<(), Conflict<Domains>> {
+    fn propagate_propagator(
+        &mut self,
+        propagator_id: PropagatorId,
+        brancher: &mut impl Brancher,
+    ) -> Result<(), Conflict<Domains>> {
         trace!("propagating propagator {propagator_id:?}...");
+        self.statistics.propagator_calls += 1;
+        let first_new_idx = self.trail.len();
+
         let propagator = &mut self.propagators[propagator_id];
         let mut ctx = Context::new(
             &mut self.assignment,
@@ -210,17 +542,65 @@ where
             &mut self.implication_graph,
             &mut self.search_tree,
             &mut self.domains,
+            propagator_id,
+            brancher,
         );
 
-        propagator.propagate(&mut ctx)
+        let result = propagator.propagate(&mut ctx);
+
+        if self.search_tree.is_at_root() {
+            self.log_root_explanations(first_new_idx, result.as_ref().err());
+        }
+
+        result
+    }
+
+    /// Log the explanations of the literals a propagator assigned at the root, starting at the
+    /// given trail index, and of the conflict it found there. Conflict analysis does not trace the
+    /// literals assigned at the root, so their explanations are logged as soon as they are found.
+    fn log_root_explanations(
+        &mut self,
+        first_new_idx: usize,
+        conflict: Option<&Conflict<Domains>>,
+    ) {
+        let Some(proof) = self
+            .proof
+            .as_mut()
+            .filter(|proof| proof.needs_explanations())
+        else {
+            return;
+        };
+
+        for idx in first_new_idx..self.trail.len() {
+            let reason = self.implication_graph.reason(self.trail[idx].var());
+            let Reason::Explanation { propagator, .. } = reason else {
+                continue;
+            };
+
+            let id = self.clauses.new_clause_id();
+            let explanation = reason.as_clause(&self.clauses, &self.domains);
+
+            proof.add_explanation(id, &explanation, self.definitions[*propagator]);
+        }
+
+        if let Some(conflict @ Conflict::Propagator { propagator, .. }) = conflict {
+            let id = self.clauses.new_clause_id();
+            let explanation = conflict.lits(&self.clauses, &self.domains);
+
+            proof.add_explanation(id, &explanation, self.definitions[*propagator]);
+        }
     }
 
-    fn propagate_propositional(&mut self) -> Result<(), Conflict<Domains>> {
+    fn propagate_propositional(
+        &mut self,
+        brancher: &mut impl Brancher,
+    ) -> Result<(), Conflict<Domains>> {
         trace!("propagating propositional trail...");
         while self.next_propagation_idx < self.trail.len() {
             let trail_lit = self.trail[self.next_propagation_idx];
             let false_lit = !trail_lit;
             self.next_propagation_idx += 1;
+            self.statistics.propagations += 1;
 
             trace!("processing {trail_lit:?}");
 
@@ -232,21 +612,48 @@ where
                 let watch = watches[i];
 
                 let conflict = match watch {
-                    LitWatch::Clause(clause_ref) => {
-                        if !self.propagate_clause(clause_ref, false_lit) {
+                    LitWatch::Clause {
+                        clause_ref,
+                        blocker,
+                    } => {
+                        if self.assignment.value(blocker) == Some(true) {
+                            self.watch_list[false_lit].push(watch);
+                            None
+                        } else if !self.propagate_clause(clause_ref, false_lit, brancher) {
                             Some(clause_ref)
                         } else {
                             None
                         }
                     }
+                    LitWatch::BinaryClause { clause_ref, other } => {
+                        self.watch_list[false_lit].push(watch);
+
+                        match self.assignment.value(other) {
+                            Some(true) => None,
+                            Some(false) => Some(clause_ref),
+                            None => {
+                                trace!("propagating {other:?} because of {clause_ref:?}");
+
+                                let reason = Reason::BinaryClause {
+                                    clause_ref,
+                                    lits: [other, false_lit],
+                                };
+                                self.enqueue(other, reason, brancher);
+                                None
+                            }
+                        }
+                    }
                     LitWatch::Propagator {
                         propagator_id,
                         local_id: _,
                     } => {
+                        // Unlike clauses, propagators and domains keep watching the same literal.
+                        self.watch_list[false_lit].push(watch);
                         self.propagator_queue.push(propagator_id);
                         None
                     }
                     LitWatch::DomainEvent { domain_id, event } => {
+                        self.watch_list[false_lit].push(watch);
                         self.watch_list[(domain_id, event)]
                             .iter()
                             .for_each(|watch| self.propagator_queue.push(watch.propagator_id));
@@ -271,7 +678,532 @@ where
         Ok(())
     }
 
-    fn propagate_clause(&mut self, clause_ref: ClauseRef, false_lit: Lit) -> bool {
+    /// Analyze a conflict which is not at the root, add the learned clause, and backjump so that
+    /// the learned clause propagates. Returns the LBD of the learned clause.
+    fn learn(&mut self, conflict: Conflict<Domains>, brancher: &mut impl Brancher) -> u32 {
+        let (literal_to_enqueue, reason, backjump_level, lbd, id) = {
+            let analysis = self.analyzer.analyze(
+                conflict,
+                &self.clauses,
+                &self.implication_graph,
+                &self.search_tree,
+                &self.trail,
+                brancher,
+                &self.domains,
+            );
+
+            trace!("learned clause {:?}", analysis.learned_clause);
+            self.statistics.learned_literals_before_minimization += analysis.unminimized_len as u64;
+            self.statistics.learned_literals += analysis.learned_clause.len() as u64;
+
+            for &clause_ref in analysis.used_clauses {
+                self.clauses.bump_activity(clause_ref);
+            }
+
+            let (reason, id) = if analysis.learned_clause.len() > 1 {
+                let clause_ref = self
+                    .clauses
+                    .add_learned_clause(analysis.learned_clause, analysis.lbd);
+
+                (clause_ref.into(), self.clauses[clause_ref].id())
+            } else {
+                (Reason::Decision, self.clauses.new_clause_id())
+            };
+
+            if let Some(proof) = &mut self.proof {
+                for (propagator, explanation) in analysis.explanations {
+                    let id = self.clauses.new_clause_id();
+                    proof.add_explanation(id, explanation, self.definitions[*propagator]);
+                }
+
+                proof.add_clause(id, analysis.learned_clause, analysis.hints);
+            }
+
+            (
+                analysis.learned_clause[0],
+                reason,
+                analysis.backjump_level,
+                analysis.lbd,
+                id,
+            )
+        };
+
+        let is_unit = match reason {
+            Reason::Clause(clause_ref) => {
+                self.watch_clause(clause_ref);
+                false
+            }
+            _ => true,
+        };
+
+        // Unit clauses are always assigned at the root, so the solver backjumps all the way.
+        let depth = self.search_tree.depth();
+        let backtrack_level = match self.max_backjump {
+            Some(max_backjump) if !is_unit && depth - backjump_level > max_backjump.max(1) => {
+                self.statistics.chronological_backtracks += 1;
+                depth - 1
+            }
+            _ => backjump_level,
+        };
+
+        self.backtrack_to(backtrack_level, brancher);
+
+        assert!(
+            self.enqueue(literal_to_enqueue, reason, brancher),
+            "conflicting asserting literal"
+        );
+
+        if is_unit {
+            self.clauses.set_unit_id(literal_to_enqueue.var(), id);
+        }
+
+        lbd
+    }
+
+    /// The conflicting clause, if it has a single literal at the current depth. This happens with
+    /// chronological backtracking when the clause became unit at a lower decision level, but the
+    /// literal was assigned at the current depth before the clause was propagated.
+    fn missed_implication(&self, conflict: &Conflict<Domains>) -> Option<ClauseRef> {
+        let Conflict::Clause(clause_ref) = *conflict else {
+            return None;
+        };
+
+        let depth = self.search_tree.depth();
+        let num_lits_at_depth = self.clauses[clause_ref]
+            .iter()
+            .filter(|lit| self.search_tree.decision_level(lit.var()) == depth)
+            .count();
+
+        (depth > 0 && num_lits_at_depth == 1).then_some(clause_ref)
+    }
+
+    /// Propagate the literal of the conflicting clause at the current depth, at the decision level
+    /// where the clause became unit. Nothing is learned from the conflict.
+    fn propagate_missed_implication(
+        &mut self,
+        clause_ref: ClauseRef,
+        brancher: &mut impl Brancher,
+    ) {
+        let depth = self.search_tree.depth();
+        let is_binary = self.clauses[clause_ref].len() == 2;
+
+        // The propagated literal goes first and the literal with the highest decision level
+        // second, so they are the watched literals.
+        if !is_binary {
+            self.unwatch_clause(clause_ref);
+        }
+
+        let clause = &mut self.clauses[clause_ref];
+        let mut levels = clause
+            .iter()
+            .map(|lit| self.search_tree.decision_level(lit.var()))
+            .collect::<Vec<_>>();
+
+        let lit_idx = levels.iter().position(|&level| level == depth).unwrap();
+        clause.swap(0, lit_idx);
+        levels.swap(0, lit_idx);
+
+        let (second_idx, _) = levels
+            .iter()
+            .enumerate()
+            .skip(1)
+            .max_by_key(|(_, &level)| level)
+            .unwrap();
+        clause.swap(1, second_idx);
+        let lit = clause[0];
+
+        if !is_binary {
+            self.watch_clause(clause_ref);
+        }
+
+        self.backtrack_to(depth - 1, brancher);
+
+        assert!(
+            self.enqueue(lit, clause_ref.into(), brancher),
+            "conflicting missed implication"
+        );
+    }
+
+    /// Simplify the formula at the root, see [`Simplifier`]. The assumptions are not removed from
+    /// the formula. Formulas with propagators are not simplified, as propagators can assign any
+    /// variable. After the first simplification, only the clauses which were added since are
+    /// considered again.
+    fn simplify(&mut self, assumptions: &[Lit]) {
+        self.needs_simplification = false;
+
+        let subsumption = self.subsumer.subsume(
+            &mut self.clauses,
+            &self.assignment,
+            false,
+            self.proof.as_mut(),
+        );
+
+        self.statistics.subsumed_clauses += subsumption.subsumed_clauses;
+        self.statistics.strengthened_clauses += subsumption.strengthened_clauses;
+
+        self.watch_all_clauses();
+        for (lit, id) in subsumption.units {
+            self.insert_clause([lit], id);
+        }
+        self.propagate_root();
+
+        // Probing and eliminating variables only consider the clauses, so they assume the clauses
+        // are the whole formula.
+        if self.propagators.is_empty() && self.state == State::Consistent {
+            self.probe();
+        }
+
+        let mut units = vec![];
+        if self.propagators.is_empty() && self.state == State::Consistent {
+            let frozen = assumptions
+                .iter()
+                .chain(self.frozen_lits.iter())
+                .copied()
+                .collect::<Vec<_>>();
+            let simplification = self.simplifier.simplify(
+                &mut self.clauses,
+                &self.assignment,
+                &frozen,
+                self.proof.as_mut(),
+            );
+
+            self.statistics.eliminated_vars += simplification.eliminated_vars;
+            self.statistics.blocked_clauses += simplification.blocked_clauses;
+            self.statistics.substituted_vars += simplification.substituted_vars;
+            units = simplification.units;
+        }
+
+        self.watch_all_clauses();
+
+        for (lit, id) in units {
+            self.insert_clause([lit], id);
+        }
+
+        self.collect_garbage();
+    }
+
+    /// Failed literal probing on the variables of binary clauses. Both literals of a variable are
+    /// propagated at a new decision level. If that leads to a conflict, the learned clause is a
+    /// unit. Literals which are implied by both literals of the variable are units as well. Only
+    /// the variables which occur in clauses added since the previous simplification are probed, see
+    /// [`Simplifier::is_touched`].
+    fn probe(&mut self) {
+        let mut candidates = self
+            .clauses
+            .iter()
+            .filter(|(_, clause)| clause.len() == 2)
+            .flat_map(|(_, clause)| clause.iter().map(|lit| lit.var()))
+            .filter(|&var| self.simplifier.is_touched(var))
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+        candidates.truncate(MAX_PROBED_VARS);
+
+        for var in candidates {
+            if self.state == State::ConflictAtRoot {
+                return;
+            }
+
+            if !self.assignment.is_unassigned(Lit::positive(var)) {
+                continue;
+            }
+
+            let Some(positive) = self.probe_lit(Lit::positive(var)) else {
+                continue;
+            };
+
+            let Some(mut negative) = self.probe_lit(Lit::negative(var)) else {
+                continue;
+            };
+
+            negative.sort();
+            let units = positive
+                .into_iter()
+                .filter(|lit| negative.binary_search(lit).is_ok())
+                .collect::<Vec<_>>();
+
+            if !units.is_empty() {
+                self.add_probed_units(var, units);
+            }
+        }
+    }
+
+    /// Propagate the literal at a new decision level. Returns the literals it implies, or `None`
+    /// if it fails, in which case the learned unit is propagated at the root. The brancher is not
+    /// told about the probe, since its literals are unassigned again without the brancher.
+    fn probe_lit(&mut self, lit: Lit) -> Option<Vec<Lit>> {
+        self.trail.push();
+        self.search_tree.branch();
+        self.enqueue(lit, Reason::Decision, &mut Unobserved);
+        let first_implied = self.trail.len();
+
+        match self.propagate_propositional(&mut Unobserved) {
+            Ok(()) => {
+                let implied = self.trail.iter().skip(first_implied).collect();
+                self.backtrack_to_root();
+                Some(implied)
+            }
+
+            Err(conflict) => {
+                trace!("failed literal {lit:?}");
+                self.learn(conflict, &mut Unobserved);
+                self.statistics.probing_units += 1;
+                self.propagate_root();
+                None
+            }
+        }
+    }
+
+    /// Add the literals which are implied by both literals of the variable as units. In the proof,
+    /// every unit is derived from the two binary clauses which state the implications.
+    fn add_probed_units(&mut self, var: Var, units: Vec<Lit>) {
+        trace!("{units:?} are implied by both literals of {var:?}");
+
+        let positive_hints = self.implication_hints(Lit::positive(var), &units);
+        let negative_hints = self.implication_hints(Lit::negative(var), &units);
+
+        for (idx, unit) in units.into_iter().enumerate() {
+            let id = self.clauses.new_clause_id();
+
+            if let Some(proof) = self.proof.as_mut() {
+                let positive_id = self.clauses.new_clause_id();
+                let negative_id = self.clauses.new_clause_id();
+                let positive_clause = [Lit::negative(var), unit];
+                let negative_clause = [Lit::positive(var), unit];
+
+                proof.add_clause(positive_id, &positive_clause, &positive_hints[idx]);
+                proof.add_clause(negative_id, &negative_clause, &negative_hints[idx]);
+                proof.add_clause(id, &[unit], &[positive_id, negative_id]);
+                proof.delete_clause(positive_id, &positive_clause);
+                proof.delete_clause(negative_id, &negative_clause);
+            }
+
+            self.statistics.probing_units += 1;
+            self.insert_clause([unit], id);
+        }
+
+        self.propagate_root();
+    }
+
+    /// The hints for the clauses `!decision \/ lit` for all given literals, which are implied by
+    /// the decision. Empty if the proof does not use hints.
+    fn implication_hints(&mut self, decision: Lit, lits: &[Lit]) -> Vec<Vec<ClauseId>> {
+        if !self.proof.as_ref().is_some_and(|proof| proof.needs_hints()) {
+            return vec![vec![]; lits.len()];
+        }
+
+        self.trail.push();
+        self.search_tree.branch();
+        self.enqueue(decision, Reason::Decision, &mut Unobserved);
+        assert!(
+            self.propagate_propositional(&mut Unobserved).is_ok(),
+            "the decision was propagated without conflict before"
+        );
+
+        let hints = lits
+            .iter()
+            .map(|&lit| {
+                self.analyzer
+                    .implication_hints(
+                        decision,
+                        lit,
+                        &self.clauses,
+                        &self.implication_graph,
+                        &self.search_tree,
+                        &self.domains,
+                    )
+                    .to_vec()
+            })
+            .collect();
+
+        self.backtrack_to_root();
+        hints
+    }
+
+    /// Propagate the trail at the root, and record a conflict if there is one.
+    fn propagate_root(&mut self) {
+        if let Err(conflict) = self.propagate_propositional(&mut Unobserved) {
+            let hints = self.root_conflict_hints(&conflict);
+            self.conflict_at_root(hints);
+        }
+    }
+
+    /// Remove subsumed learned clauses and strengthen learned clauses, see [`Subsumer`].
+    fn subsume_learned_clauses(&mut self) {
+        let subsumption = self.subsumer.subsume(
+            &mut self.clauses,
+            &self.assignment,
+            true,
+            self.proof.as_mut(),
+        );
+
+        self.statistics.subsumed_clauses += subsumption.subsumed_clauses;
+        self.statistics.strengthened_clauses += subsumption.strengthened_clauses;
+
+        self.watch_all_clauses();
+
+        for (lit, id) in subsumption.units {
+            self.insert_clause([lit], id);
+        }
+
+        self.collect_garbage();
+    }
+
+    /// Vivify the learned clauses which were learned since the previous round, see
+    /// [`Solver::vivify_clause`]. The clauses with the lowest LBD go first.
+    fn vivify_learned_clauses(&mut self) {
+        let mut candidates = self
+            .clauses
+            .iter()
+            .filter(|(_, clause)| {
+                clause.is_learned()
+                    && self.last_vivified_id.is_none_or(|id| clause.id() > id)
+                    && clause
+                        .iter()
+                        .all(|&lit| self.assignment.value(lit) != Some(true))
+            })
+            .map(|(clause_ref, clause)| (clause.lbd(), clause.id(), clause_ref))
+            .collect::<Vec<_>>();
+
+        self.last_vivified_id = candidates
+            .iter()
+            .map(|&(_, id, _)| id)
+            .max()
+            .or(self.last_vivified_id);
+
+        candidates.sort_by_key(|&(lbd, _, _)| lbd);
+        candidates.truncate(MAX_VIVIFIED_CLAUSES);
+
+        for (_, _, clause_ref) in candidates {
+            if self.state == State::ConflictAtRoot {
+                return;
+            }
+
+            self.vivify_clause(clause_ref);
+        }
+
+        self.collect_garbage();
+    }
+
+    /// Remove the redundant literals from a clause which is not satisfied at the root. The
+    /// negations of its literals are assigned one at a time at a new decision level. A literal
+    /// which is false after propagation is redundant. If a literal is true after propagation, or
+    /// propagation leads to a conflict, the literals which are not assigned yet are redundant.
+    ///
+    /// The shorter clause replaces the clause in the database with a new ID, since it is implied
+    /// by propagation.
+    fn vivify_clause(&mut self, clause_ref: ClauseRef) {
+        // Units found by vivifying earlier clauses may satisfy the clause.
+        if self.clauses[clause_ref]
+            .iter()
+            .any(|&lit| self.assignment.value(lit) == Some(true))
+        {
+            return;
+        }
+
+        // The clause should not propagate its own literals.
+        self.unwatch_clause(clause_ref);
+
+        let lits = self.clauses[clause_ref].lits().to_vec();
+        let mut kept = vec![];
+        let mut conflict = None;
+
+        self.trail.push();
+        self.search_tree.branch();
+
+        for &lit in lits.iter() {
+            match self.assignment.value(lit) {
+                Some(false) => {}
+
+                Some(true) => {
+                    kept.push(lit);
+                    let reason = self
+                        .implication_graph
+                        .reason(lit.var())
+                        .clause_ref()
+                        .expect("the literal is propagated by a clause");
+                    conflict = Some(Conflict::Clause(reason));
+                    break;
+                }
+
+                None => {
+                    kept.push(lit);
+                    self.enqueue(!lit, Reason::Decision, &mut Unobserved);
+
+                    if let Err(propagation_conflict) = self.propagate_propositional(&mut Unobserved)
+                    {
+                        conflict = Some(propagation_conflict);
+                        break;
+                    }
+                }
+            }
+        }
+
+        if kept.len() == lits.len() {
+            self.backtrack_to_root();
+            self.watch_clause(clause_ref);
+            return;
+        }
+
+        trace!("vivified {lits:?} to {kept:?}");
+
+        // If all literals are assigned, the clause itself is falsified.
+        let conflict = conflict.unwrap_or(Conflict::Clause(clause_ref));
+        let hints = if self.proof.as_ref().is_some_and(|proof| proof.needs_hints()) {
+            self.analyzer
+                .derivation_hints(
+                    &kept,
+                    conflict,
+                    &self.clauses,
+                    &self.implication_graph,
+                    &self.search_tree,
+                    &self.domains,
+                )
+                .to_vec()
+        } else {
+            vec![]
+        };
+
+        self.backtrack_to_root();
+        self.statistics.vivified_clauses += 1;
+
+        let id = self.clauses.new_clause_id();
+        if let Some(proof) = self.proof.as_mut() {
+            proof.add_clause(id, &kept, &hints);
+            proof.delete_clause(self.clauses[clause_ref].id(), &lits);
+        }
+
+        if let [unit] = kept[..] {
+            self.clauses.delete_clause(clause_ref);
+            self.insert_clause([unit], id);
+            self.propagate_root();
+        } else {
+            self.clauses.shorten_clause(clause_ref, &kept, id);
+            self.watch_clause(clause_ref);
+        }
+    }
+
+    /// Watch all clauses again after they were changed at the root. Propagating the root again
+    /// restores the watch invariants.
+    fn watch_all_clauses(&mut self) {
+        self.watch_list.remove_clause_watches();
+        let clause_refs = self
+            .clauses
+            .iter()
+            .map(|(clause_ref, _)| clause_ref)
+            .collect::<Vec<_>>();
+        for clause_ref in clause_refs {
+            self.watch_clause(clause_ref);
+        }
+        self.next_propagation_idx = 0;
+    }
+
+    fn propagate_clause(
+        &mut self,
+        clause_ref: ClauseRef,
+        false_lit: Lit,
+        brancher: &mut impl Brancher,
+    ) -> bool {
         let lit_to_propagate = {
             let is_learned = self.clauses.is_learned(clause_ref);
             let clause = &mut self.clauses[clause_ref];
@@ -282,10 +1214,16 @@ where
                 clause.swap(0, 1);
             }
 
+            // The other watched literal is the blocker in the new watch.
+            let watch = LitWatch::Clause {
+                clause_ref,
+                blocker: clause[0],
+            };
+
             // If the 0th watch is true, then clause is already satisfied.
             if self.assignment.value(clause[0]) == Some(true) {
                 trace!("clause is satisfied because of 0th literal");
-                self.watch_list[false_lit].push(clause_ref.into());
+                self.watch_list[false_lit].push(watch);
                 return true;
             }
 
@@ -296,13 +1234,13 @@ where
                     trace!("found new watch literal {candidate:?}");
                     clause.swap(1, idx);
 
-                    self.watch_list[clause[1]].push(clause_ref.into());
+                    self.watch_list[clause[1]].push(watch);
                     return true;
                 }
             }
 
             // The clause is unit under the current assignment.
-            self.watch_list[false_lit].push(clause_ref.into());
+            self.watch_list[false_lit].push(watch);
             clause[0]
         };
 
@@ -311,7 +1249,7 @@ where
             lit_to_propagate
         );
 
-        self.enqueue(lit_to_propagate, clause_ref.into())
+        self.enqueue(lit_to_propagate, clause_ref.into(), brancher)
     }
 }
 
@@ -322,11 +1260,29 @@ where
     pub fn solve(
         &mut self,
         terminator: impl Terminator,
-        mut brancher: impl Brancher,
+        brancher: impl Brancher,
+        restart_policy: impl RestartPolicy,
     ) -> SolveResult<'_, Domains> {
-        if self.state == State::ConflictAtRoot {
-            return SolveResult::Unsatisfiable;
-        }
+        self.solve_with_assumptions(&[], terminator, brancher, restart_policy)
+    }
+
+    /// Solve the formula under the given assumptions. The assumptions are treated as decisions
+    /// which are made before any decision of the brancher. If there is no solution which satisfies
+    /// the assumptions, the subset of the assumptions responsible is returned in
+    /// [`SolveResult::UnsatisfiableUnderAssumptions`].
+    pub fn solve_with_assumptions(
+        &mut self,
+        assumptions: &[Lit],
+        terminator: impl Terminator,
+        mut brancher: impl Brancher,
+        mut restart_policy: impl RestartPolicy,
+    ) -> SolveResult<'_, Domains> {
+        self.restore_removed_clauses(assumptions.iter().map(|lit| lit.var()));
+
+        if self.state == State::ConflictAtRoot {
+            self.log_empty_clause();
+            return SolveResult::Unsatisfiable;
+        }
 
         if self.next_var_code == 0 {
             return SolveResult::Satisfiable(Solution {
@@ -341,78 +1297,230 @@ where
                 .expect("next_var_code should be one more than a valid variable"),
         );
 
+        if let Some(mode_switching) = &self.mode_switching {
+            brancher.on_mode_switch(mode_switching.mode());
+        }
+
+        // A previous call may have stopped at a higher decision level.
+        if !self.search_tree.is_at_root() {
+            self.backtrack_to(0, &mut brancher);
+        }
+
+        let start_time = Instant::now();
+
         while !terminator.should_stop() {
-            match self.propagate() {
+            let result = self.propagate(&mut brancher);
+
+            match result {
                 Err(conflict) => {
                     trace!("conflict at dl {}", self.search_tree.depth());
+                    self.statistics.conflicts += 1;
+
+                    if self.max_backjump.is_some() {
+                        // With chronological backtracking, the conflicting literals can all be
+                        // assigned below the current depth.
+                        let conflict_level = self.conflict_level(&conflict);
+                        if conflict_level < self.search_tree.depth() {
+                            self.backtrack_to(conflict_level, &mut brancher);
+
+                            // The conflict was found above the root, so its explanation has
+                            // not been logged yet.
+                            if conflict_level == 0 {
+                                self.log_root_explanations(self.trail.len(), Some(&conflict));
+                            }
+                        }
+
+                        if let Some(conflict_clause) = self.missed_implication(&conflict) {
+                            self.propagate_missed_implication(conflict_clause, &mut brancher);
+                            continue;
+                        }
+                    }
 
                     if self.search_tree.is_at_root() {
+                        let hints = self.root_conflict_hints(&conflict);
+                        self.conflict_at_root(hints);
+                        self.log_empty_clause();
+                        self.statistics.solve_time += start_time.elapsed();
                         return SolveResult::Unsatisfiable;
                     }
 
-                    let (literal_to_enqueue, reason, backjump_level) = {
-                        let analysis = self.analyzer.analyze(
-                            conflict,
-                            &self.clauses,
-                            &self.implication_graph,
-                            &self.search_tree,
-                            &self.trail,
-                            &mut brancher,
-                            &self.domains,
-                        );
+                    let lbd = self.learn(conflict, &mut brancher);
 
-                        trace!("learned clause {:?}", analysis.learned_clause);
+                    brancher.on_conflict();
+                    self.restart_policy(&mut restart_policy).on_conflict(lbd);
+                    if let Some(mode_switching) = &mut self.mode_switching {
+                        mode_switching.on_conflict();
 
-                        let clause_ref = if analysis.learned_clause.len() > 1 {
-                            self.clauses
-                                .add_learned_clause(analysis.learned_clause)
-                                .into()
-                        } else {
-                            Reason::Decision
-                        };
-
-                        (
-                            analysis.learned_clause[0],
-                            clause_ref,
-                            analysis.backjump_level,
-                        )
-                    };
+                        if mode_switching.mode() == SearchMode::Stable {
+                            self.statistics.stable_conflicts += 1;
+                        }
+                    }
+                    self.clauses.decay_activities();
+                    self.conflicts_until_reduction =
+                        self.conflicts_until_reduction.saturating_sub(1);
+                    self.conflicts_until_subsumption =
+                        self.conflicts_until_subsumption.saturating_sub(1);
+                    self.conflicts_until_vivification =
+                        self.conflicts_until_vivification.saturating_sub(1);
+                }
 
-                    if let Reason::Clause(clause_ref) = reason {
-                        self.watch_clause(clause_ref);
+                Ok(()) => {
+                    if self.needs_simplification && self.search_tree.is_at_root() {
+                        self.simplify(assumptions);
+
+                        if self.state == State::ConflictAtRoot {
+                            self.log_empty_clause();
+                            self.statistics.solve_time += start_time.elapsed();
+                            return SolveResult::Unsatisfiable;
+                        }
+
+                        continue;
                     }
 
-                    self.backtrack_to(backjump_level, &mut brancher);
+                    if self.conflicts_until_subsumption == 0 && self.search_tree.is_at_root() {
+                        self.subsume_learned_clauses();
+                        self.conflicts_until_subsumption = SUBSUMPTION_INTERVAL;
 
-                    assert!(
-                        self.enqueue(literal_to_enqueue, reason),
-                        "conflicting asserting literal"
-                    );
+                        if self.state == State::ConflictAtRoot {
+                            self.log_empty_clause();
+                            self.statistics.solve_time += start_time.elapsed();
+                            return SolveResult::Unsatisfiable;
+                        }
 
-                    brancher.on_conflict();
-                }
+                        continue;
+                    }
+
+                    if self.conflicts_until_vivification == 0 && self.search_tree.is_at_root() {
+                        self.vivify_learned_clauses();
+                        self.conflicts_until_vivification = VIVIFICATION_INTERVAL;
+
+                        if self.state == State::ConflictAtRoot {
+                            self.log_empty_clause();
+                            self.statistics.solve_time += start_time.elapsed();
+                            return SolveResult::Unsatisfiable;
+                        }
+
+                        continue;
+                    }
+
+                    if self.conflicts_until_reduction == 0 {
+                        self.reduce_learned_clauses();
+
+                        self.reduction_interval += REDUCTION_INCREMENT;
+                        self.conflicts_until_reduction = self.reduction_interval;
+                    }
+
+                    let switches_mode = self
+                        .mode_switching
+                        .as_ref()
+                        .is_some_and(ModeSwitching::should_switch);
+
+                    if switches_mode || self.restart_policy(&mut restart_policy).should_restart() {
+                        if !self.search_tree.is_at_root() {
+                            trace!("restarting");
+                            self.backtrack_to(0, &mut brancher);
+                        }
+
+                        self.restart_policy(&mut restart_policy).on_restart();
+                        brancher.on_restart();
+                        self.statistics.restarts += 1;
+
+                        if switches_mode {
+                            let mode_switching =
+                                self.mode_switching.as_mut().expect("search switches modes");
+                            let mode = mode_switching.switch();
+                            trace!("switching to {mode} mode");
+
+                            brancher.on_mode_switch(mode);
+                            self.statistics.mode = mode;
+                            self.statistics.mode_switches += 1;
+                        }
+
+                        continue;
+                    }
+
+                    // Local search only considers the clauses, so it is not used together with
+                    // propagators. A round starts the next time the solver is at the root after
+                    // the brancher reset its phases.
+                    if self.search_tree.is_at_root()
+                        && self.propagators.is_empty()
+                        && assumptions.is_empty()
+                        && brancher
+                            .phases()
+                            .is_some_and(|phases| phases.take_rephased())
+                    {
+                        let phases = brancher.phases().expect("brancher has phases");
+                        if let Some(model) = self.search_locally(phases) {
+                            trace!("local search found a solution");
+
+                            self.trail.push();
+                            self.search_tree.branch();
+                            for lit in model {
+                                self.enqueue(lit, Reason::Decision, &mut brancher);
+                            }
+
+                            self.statistics.solve_time += start_time.elapsed();
+                            return SolveResult::Satisfiable(self.complete_solution(&mut brancher));
+                        }
+                    }
+
+                    if let Some(&assumption) = assumptions.get(self.search_tree.depth()) {
+                        if self.assignment.value(assumption) == Some(false) {
+                            let core = self.analyzer.analyze_final(
+                                assumption,
+                                &self.clauses,
+                                &self.implication_graph,
+                                &self.search_tree,
+                                &self.trail,
+                                &self.domains,
+                            );
+
+                            self.statistics.solve_time += start_time.elapsed();
+                            return SolveResult::UnsatisfiableUnderAssumptions(core);
+                        }
+
+                        // Every assumption gets its own decision level, even if it is already
+                        // satisfied. That way the depth of the search tree indicates how many
+                        // assumptions have been made.
+                        self.trail.push();
+                        self.search_tree.branch();
+
+                        if self.assignment.is_unassigned(assumption) {
+                            trace!("assumed {assumption:?}");
+                            self.enqueue(assumption, Reason::Decision, &mut brancher);
+                        }
+
+                        continue;
+                    }
 
-                Ok(()) => {
                     self.trail.push();
                     self.search_tree.branch();
 
-                    if let Some(decision) = brancher.next_decision(&self.assignment) {
+                    // Eliminated variables do not occur in the formula, so deciding on them
+                    // would only add pointless decision levels.
+                    let decision = loop {
+                        match brancher.next_decision(&self.assignment) {
+                            Some(lit) if self.simplifier.is_eliminated(lit.var()) => continue,
+                            decision => break decision,
+                        }
+                    };
+
+                    if let Some(decision) = decision {
                         trace!("decided {decision:?}");
+                        self.statistics.decisions += 1;
                         assert!(
-                            self.enqueue(decision, Reason::Decision),
+                            self.enqueue(decision, Reason::Decision, &mut brancher),
                             "decided already assigned literal"
                         );
                     } else {
-                        return SolveResult::Satisfiable(Solution {
-                            assignment: &mut self.assignment,
-                            domains: &self.domains,
-                            next_new_var_code: self.next_var_code,
-                        });
+                        self.statistics.solve_time += start_time.elapsed();
+                        return SolveResult::Satisfiable(self.complete_solution(&mut brancher));
                     }
                 }
             }
         }
 
+        self.statistics.solve_time += start_time.elapsed();
         SolveResult::Unknown
     }
 }
@@ -422,6 +1530,9 @@ pub enum SolveResult<'solver, Domains> {
     Satisfiable(Solution<'solver, Domains>),
     /// No solution exists for the formula.
     Unsatisfiable,
+    /// No solution exists which satisfies the assumptions. Contains the subset of the assumptions
+    /// which is responsible.
+    UnsatisfiableUnderAssumptions(&'solver [Lit]),
     /// The solver was interrupted before reaching a conclusion.
     Unknown,
 }
@@ -442,7 +1553,7 @@ impl<Domains> Solution<'_, Domains> {
         Domains: DomainStore<Dom>,
         Dom: BoundedInt,
     {
-        self.domains[domain].max()
+        self.domains[domain].max(self.assignment)
     }
 
     pub fn vars(&self) -> impl Iterator<Item = Var> + '_ {
@@ -450,19 +1561,263 @@ impl<Domains> Solution<'_, Domains> {
     }
 }
 
+impl<Domains, Event> Solver<Domains, Event> {
+    /// The statistics of the search, accumulated over all calls to solve.
+    pub fn statistics(&self) -> &SolverStatistics {
+        &self.statistics
+    }
+
+    /// Backtrack chronologically, i.e. to the decision level below the conflict, when backjumping
+    /// would undo more than `max_backjump` decision levels. The assignments on the levels in
+    /// between are kept, which avoids redoing the same decisions and propagations after the
+    /// conflict. See "Chronological Backtracking" by Nadel and Ryvchin (SAT 2018).
+    pub fn use_chronological_backtracking(&mut self, max_backjump: usize) {
+        self.max_backjump = Some(max_backjump);
+    }
+
+    /// Keep the variable of the literal in the formula when it is simplified. This is needed for
+    /// variables a brancher decides on, since the solver does not decide on eliminated variables.
+    pub fn freeze(&mut self, lit: Lit) {
+        self.frozen_lits.push(lit);
+    }
+
+    /// Alternate the search between a focused mode, which restarts according to the restart policy
+    /// given to [`Solver::solve`], and a stable mode, which rarely restarts. The brancher is told
+    /// about every switch through [`Brancher::on_mode_switch`]. The search starts in focused mode,
+    /// and switches to stable mode after `first_interval` conflicts.
+    pub fn use_mode_switching(&mut self, first_interval: u64) {
+        self.mode_switching = Some(ModeSwitching::new(first_interval));
+    }
+
+    /// Log a proof of the clauses derived from now on. The proof can only be checked if every
+    /// clause and propagator was added to the solver while the proof was being logged. Reasoning
+    /// by propagators is only covered by proofs which need explanations, and only for propagators
+    /// with a [`Propagator::definition`].
+    pub fn log_proof(&mut self, proof: impl ProofLog + 'static) {
+        self.analyzer.record_hints(proof.needs_hints());
+        self.analyzer
+            .record_explanations(proof.needs_explanations());
+        self.proof = Some(Box::new(proof));
+    }
+
+    /// Stop logging the proof, and get it back so it can be finished.
+    pub fn take_proof(&mut self) -> Option<Box<dyn ProofLog>> {
+        self.analyzer.record_hints(false);
+        self.analyzer.record_explanations(false);
+        self.proof.take()
+    }
+
+    /// The restart policy of the current mode. Without mode switching, or in focused mode, this is
+    /// the restart policy the solver was given.
+    fn restart_policy<'a>(
+        &'a mut self,
+        restart_policy: &'a mut impl RestartPolicy,
+    ) -> &'a mut dyn RestartPolicy {
+        match &mut self.mode_switching {
+            Some(mode_switching) => mode_switching.restart_policy(restart_policy),
+            None => restart_policy,
+        }
+    }
+
+    /// Turn the assignment, which satisfies the formula, into a solution by assigning the
+    /// eliminated variables.
+    fn complete_solution(&mut self, brancher: &mut impl Brancher) -> Solution<'_, Domains> {
+        // The reconstruction needs every variable to be assigned. Assigning the eliminated
+        // variables on the trail makes sure they are unassigned again when the solver backtracks.
+        let eliminated_vars = self
+            .simplifier
+            .eliminated_vars()
+            .filter(|&var| self.assignment.is_unassigned(Lit::positive(var)))
+            .collect::<Vec<_>>();
+        for var in eliminated_vars {
+            self.enqueue(Lit::negative(var), Reason::Decision, brancher);
+        }
+
+        self.simplifier.reconstruct(&mut self.assignment);
+        Solution {
+            assignment: &mut self.assignment,
+            domains: &self.domains,
+            next_new_var_code: self.next_var_code,
+        }
+    }
+
+    /// Improve the saved phases by local search, starting from the saved phases themselves. The
+    /// irredundant clauses have to be satisfied, with the variables assigned at the root fixed. If
+    /// the best assignment that was found satisfies every clause, including the learned clauses,
+    /// the literals it assigns to the unassigned variables are returned.
+    fn search_locally(&mut self, phases: &mut Phases) -> Option<Vec<Lit>> {
+        let vars = (0..self.next_var_code)
+            .map(|code| Var::try_from(code).expect("valid variable"))
+            .filter(|&var| self.assignment.is_unassigned(Lit::positive(var)))
+            .filter(|&var| !self.simplifier.is_eliminated(var))
+            .collect::<Vec<_>>();
+
+        let mut local_search = LocalSearch::new(self.statistics.local_search_flips);
+        local_search.grow_to(Var::try_from(self.next_var_code - 1).expect("valid variable"));
+
+        let mut lits = vec![];
+        for (_, clause) in self
+            .clauses
+            .iter()
+            .filter(|(_, clause)| !clause.is_learned())
+        {
+            if clause
+                .iter()
+                .any(|&lit| self.assignment.value(lit) == Some(true))
+            {
+                continue;
+            }
+
+            lits.clear();
+            lits.extend(
+                clause
+                    .iter()
+                    .filter(|&&lit| self.assignment.is_unassigned(lit)),
+            );
+            local_search.add_clause(&lits);
+        }
+
+        let max_flips = (LOCAL_SEARCH_FLIPS_PER_CLAUSE * local_search.num_clauses() as u64)
+            .min(MAX_LOCAL_SEARCH_FLIPS);
+        let (flips, is_satisfied) = local_search.run(
+            |var| match self.assignment.value(Lit::positive(var)) {
+                Some(value) => value,
+                None => phases.saved(var),
+            },
+            max_flips,
+        );
+
+        self.statistics.local_search_flips += flips;
+        trace!("local search flipped {flips} times");
+
+        let model = vars
+            .into_iter()
+            .map(|var| {
+                if local_search.value(var) {
+                    Lit::positive(var)
+                } else {
+                    Lit::negative(var)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for &lit in model.iter() {
+            phases.save(lit);
+        }
+
+        let satisfies_learned_clauses = || {
+            self.clauses.iter().all(|(_, clause)| {
+                clause
+                    .iter()
+                    .any(|&lit| local_search.value(lit.var()) == lit.is_positive())
+            })
+        };
+
+        (is_satisfied && satisfies_learned_clauses()).then_some(model)
+    }
+
+    /// Mark the formula as unsatisfiable. In the proof this is concluded by the empty clause, which
+    /// is derived using the given hints. The empty clause is logged when solving, so its ID comes
+    /// after those of the clauses in the formula.
+    fn conflict_at_root(&mut self, hints: Vec<ClauseId>) {
+        self.state = State::ConflictAtRoot;
+        self.empty_clause_hints = Some(hints);
+    }
+
+    /// Log the empty clause if a conflict at the root was found but not logged yet.
+    fn log_empty_clause(&mut self) {
+        let Some(hints) = self.empty_clause_hints.take() else {
+            return;
+        };
+
+        let id = self.clauses.new_clause_id();
+        if let Some(proof) = &mut self.proof {
+            proof.add_clause(id, &[], &hints);
+        }
+    }
+
+    /// Backtrack to the root without involving a brancher. A previous call to [`Solver::solve`] may
+    /// have left the solver at a higher decision level, but the formula can only be modified at the
+    /// root. The phases of the unassigned variables are not saved.
+    fn backtrack_to_root(&mut self) {
+        if self.search_tree.is_at_root() {
+            return;
+        }
+
+        let first_kept_idx = self.trail.level_end(0);
+
+        self.trail
+            .backtrack_to(0, &self.search_tree)
+            .for_each(|lit| self.assignment.unassign(lit));
+
+        self.search_tree.cut(0);
+        self.next_propagation_idx = first_kept_idx;
+    }
+}
+
 impl<Domains, Event> ExtendSolver<Domains, Event> for Solver<Domains, Event> {
     fn add_propagator(&mut self, factory: impl PropagatorFactory<Domains, Event>) -> bool {
+        self.backtrack_to_root();
+
+        // The propagator may depend on any variable.
+        let mut restored = vec![];
+        self.simplifier.restore_all(&mut restored);
+        self.insert_restored_clauses(restored);
+
         let slot = self.propagators.new_ref();
-        self.propagator_queue.grow_to(slot.id());
-        let mut variable_registrar = VariableRegistrar::new(slot.id(), &mut self.watch_list);
+        let propagator_id = slot.id();
+        self.propagator_queue.grow_to(propagator_id);
+        let mut variable_registrar = VariableRegistrar::new(propagator_id, &mut self.watch_list);
 
         let propagator = factory.create(&mut variable_registrar);
         slot.alloc(propagator);
+        self.definitions.grow_to(propagator_id);
+
+        if let Some(proof) = &mut self.proof {
+            let mut brancher = Unobserved;
+            let mut ctx = Context::new(
+                &mut self.assignment,
+                &mut self.trail,
+                &mut self.implication_graph,
+                &mut self.search_tree,
+                &mut self.domains,
+                propagator_id,
+                &mut brancher,
+            );
+
+            if let Some(definition) = self.propagators[propagator_id].definition(&mut ctx) {
+                let id = self.clauses.new_clause_id();
+                proof.add_axiom(id, &definition);
+                self.definitions[propagator_id] = Some(id);
+            }
+        }
+
+        // The propagator has not seen the current root assignment yet.
+        self.propagator_queue.push(propagator_id);
 
         true
     }
 }
 
+/// Stands in for the brancher where the solver assigns literals which it unassigns again without
+/// involving the brancher, see [`Solver::backtrack_to_root`]. That way the brancher is told about
+/// the unassignment of every literal it was told was assigned.
+struct Unobserved;
+
+impl Brancher for Unobserved {
+    fn initialize(&mut self, _: Var) {}
+
+    fn on_variable_activated(&mut self, _: Var) {}
+
+    fn on_conflict(&mut self) {}
+
+    fn on_variable_unassigned(&mut self, _: Lit) {}
+
+    fn next_decision(&mut self, _: &Assignment) -> Option<Lit> {
+        None
+    }
+}
+
 struct DomainFactoryContext<'a, Domains, Event> {
     solver: &'a mut Solver<Domains, Event>,
     untyped_domain_id: UntypedDomainId,
@@ -534,6 +1889,522 @@ where
             self.solver.search_tree.grow_to(last_var);
             self.solver.watch_list.grow_to_lit(Lit::positive(last_var));
             self.solver.analyzer.grow_to(last_var);
+            self.solver.clauses.grow_to(last_var);
+            self.solver.simplifier.grow_to(last_var);
+            self.solver.subsumer.grow_to(last_var);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        atom::Atom,
+        brancher::VsidsBrancher,
+        propagation::{LitEvent, PropagatorVar},
+        restart::NeverRestart,
+        termination::Indefinite,
+        testing::count_solutions,
+    };
+
+    use super::*;
+
+    fn solver_with_lits(num_lits: usize) -> (Solver<(), ()>, Vec<Lit>) {
+        let mut solver = Solver::default();
+        let lits = solver.new_lits().take(num_lits).collect();
+
+        (solver, lits)
+    }
+
+    fn solve_under<'solver>(
+        solver: &'solver mut Solver<(), ()>,
+        assumptions: &[Lit],
+    ) -> SolveResult<'solver, ()> {
+        solver.solve_with_assumptions(
+            assumptions,
+            Indefinite,
+            VsidsBrancher::new(0.95),
+            NeverRestart,
+        )
+    }
+
+    #[test]
+    fn solution_satisfies_the_assumptions() {
+        let (mut solver, lits) = solver_with_lits(2);
+        let [a, b] = lits[..] else { unreachable!() };
+        solver.add_clause([a, b]);
+
+        match solve_under(&mut solver, &[!a]) {
+            SolveResult::Satisfiable(solution) => {
+                assert!(!solution.value(a.var()));
+                assert!(solution.value(b.var()));
+            }
+            _ => panic!("expected a solution"),
+        }
+    }
+
+    #[test]
+    fn core_contains_only_the_responsible_assumptions() {
+        let (mut solver, lits) = solver_with_lits(4);
+        let [a, b, c, d] = lits[..] else {
+            unreachable!()
+        };
+        solver.add_clause([!a, c]);
+        solver.add_clause([!b, !c]);
+
+        match solve_under(&mut solver, &[a, d, b]) {
+            SolveResult::UnsatisfiableUnderAssumptions(core) => {
+                let mut core = core.to_vec();
+                core.sort();
+                assert_eq!(vec![a, b], core);
+            }
+            _ => panic!("expected the assumptions to be unsatisfiable"),
+        }
+    }
+
+    #[test]
+    fn assumption_falsified_at_the_root_is_its_own_core() {
+        let (mut solver, lits) = solver_with_lits(2);
+        let [a, b] = lits[..] else { unreachable!() };
+        solver.add_clause([!a]);
+
+        match solve_under(&mut solver, &[b, a]) {
+            SolveResult::UnsatisfiableUnderAssumptions(core) => assert_eq!(&[a], core),
+            _ => panic!("expected the assumptions to be unsatisfiable"),
+        }
+    }
+
+    #[test]
+    fn contradicting_assumptions_are_both_in_the_core() {
+        let (mut solver, lits) = solver_with_lits(1);
+        let a = lits[0];
+
+        match solve_under(&mut solver, &[a, !a]) {
+            SolveResult::UnsatisfiableUnderAssumptions(core) => {
+                assert_eq!(2, core.len());
+                assert!(core.contains(&a) && core.contains(&!a));
+            }
+            _ => panic!("expected the assumptions to be unsatisfiable"),
+        }
+    }
+
+    #[test]
+    fn blocking_clauses_enumerate_all_solutions() {
+        let (mut solver, _) = solver_with_lits(3);
+
+        assert_eq!(8, count_solutions(&mut solver, |_| {}));
+    }
+
+    #[test]
+    fn clause_added_after_solve_is_respected() {
+        let (mut solver, lits) = solver_with_lits(2);
+        let [a, b] = lits[..] else { unreachable!() };
+        solver.add_clause([a, b]);
+
+        assert!(matches!(
+            solve_under(&mut solver, &[]),
+            SolveResult::Satisfiable(_)
+        ));
+
+        solver.add_clause([!a]);
+
+        match solve_under(&mut solver, &[]) {
+            SolveResult::Satisfiable(solution) => {
+                assert!(!solution.value(a.var()));
+                assert!(solution.value(b.var()));
+            }
+            _ => panic!("expected a solution"),
+        }
+
+        solver.add_clause([!b]);
+        assert!(matches!(
+            solve_under(&mut solver, &[]),
+            SolveResult::Unsatisfiable
+        ));
+    }
+
+    #[test]
+    fn statistics_count_the_search() {
+        // Four parity constraints over the triples of the variables, which simplification leaves
+        // alone. Together they only allow every variable to be false, which local search finds
+        // before any decision is made.
+        let (mut solver, lits) = solver_with_lits(4);
+        for triple in [[0, 1, 2], [1, 2, 3], [0, 2, 3], [0, 1, 3]] {
+            for signs in (0..8_u32).filter(|signs| signs.count_ones() % 2 == 1) {
+                solver.add_clause(triple.iter().enumerate().map(|(bit, &idx)| {
+                    if signs & (1 << bit) == 0 {
+                        lits[idx]
+                    } else {
+                        !lits[idx]
+                    }
+                }));
+            }
+        }
+
+        match solve_under(&mut solver, &[]) {
+            SolveResult::Satisfiable(solution) => {
+                assert!(lits.iter().all(|lit| !solution.value(lit.var())));
+            }
+            _ => panic!("expected a solution"),
+        }
+
+        let statistics = solver.statistics();
+        assert_eq!(0, statistics.decisions);
+        assert_eq!(0, statistics.conflicts);
+        assert_eq!(0, statistics.learned_literals);
+        assert_eq!(0, statistics.propagations);
+        assert!(statistics.local_search_flips > 0);
+        assert_eq!(0, statistics.propagator_calls);
+        assert_eq!(0, statistics.eliminated_vars);
+        assert_eq!(0, statistics.blocked_clauses);
+        assert_eq!(0, statistics.subsumed_clauses);
+        assert_eq!(0, statistics.strengthened_clauses);
+        assert_eq!(0, statistics.vivified_clauses);
+    }
+
+    #[test]
+    fn eliminated_variables_are_reconstructed() {
+        let (mut solver, lits) = solver_with_lits(3);
+        let [a, b, c] = lits[..] else { unreachable!() };
+        let clauses = [vec![a, c], vec![!a, b], vec![!b, c]];
+        for clause in clauses.iter() {
+            solver.add_clause(clause.iter().copied());
+        }
+
+        match solve_under(&mut solver, &[]) {
+            SolveResult::Satisfiable(solution) => {
+                for clause in clauses.iter() {
+                    assert!(clause
+                        .iter()
+                        .any(|&lit| solution.value(lit.var()) == lit.is_positive()));
+                }
+            }
+            _ => panic!("expected a solution"),
+        }
+
+        assert!(solver.statistics().eliminated_vars > 0);
+    }
+
+    #[test]
+    fn failed_literals_are_probed() {
+        let (mut solver, lits) = solver_with_lits(4);
+        let [a, b, c, d] = lits[..] else {
+            unreachable!()
+        };
+        solver.add_clause([!a, b]);
+        solver.add_clause([!a, c]);
+        solver.add_clause([!b, !c, d]);
+        solver.add_clause([!a, !d]);
+
+        match solve_under(&mut solver, &[]) {
+            SolveResult::Satisfiable(solution) => assert!(!solution.value(a.var())),
+            _ => panic!("expected a solution"),
+        }
+
+        assert!(solver.statistics().probing_units > 0);
+    }
+
+    #[test]
+    fn equivalent_literals_are_substituted() {
+        let (mut solver, lits) = solver_with_lits(4);
+        let [a, b, c, d] = lits[..] else {
+            unreachable!()
+        };
+        let clauses = [
+            vec![!a, b],
+            vec![a, !b],
+            vec![a, c],
+            vec![!b, !c],
+            vec![b, c, d],
+            vec![!a, !c, !d],
+        ];
+        for clause in clauses.iter() {
+            solver.add_clause(clause.iter().copied());
+        }
+
+        match solve_under(&mut solver, &[]) {
+            SolveResult::Satisfiable(solution) => {
+                for clause in clauses.iter() {
+                    assert!(clause
+                        .iter()
+                        .any(|&lit| solution.value(lit.var()) == lit.is_positive()));
+                }
+            }
+            _ => panic!("expected a solution"),
+        }
+
+        assert_eq!(solver.statistics().substituted_vars, 2);
+    }
+
+    #[test]
+    fn simplification_only_reconsiders_the_variables_of_new_clauses() {
+        let (mut solver, lits) = solver_with_lits(4);
+        let [a, b, c, d] = lits[..] else {
+            unreachable!()
+        };
+        solver.add_clause([a, b]);
+        solver.add_clause([!a, c]);
+        let is_touched =
+            |solver: &Solver<(), ()>, lit: &Lit| solver.simplifier.is_touched(lit.var());
+
+        assert!(matches!(
+            solve_under(&mut solver, &[]),
+            SolveResult::Satisfiable(_)
+        ));
+        assert!(!lits.iter().any(|lit| is_touched(&solver, lit)));
+
+        solver.add_clause([c, d]);
+        assert!(is_touched(&solver, &c) && is_touched(&solver, &d));
+
+        // The assumption may no longer be frozen in the next simplification.
+        assert!(matches!(
+            solve_under(&mut solver, &[!d]),
+            SolveResult::Satisfiable(_)
+        ));
+        assert!(is_touched(&solver, &d));
+        assert!(!is_touched(&solver, &c));
+    }
+
+    #[test]
+    fn learned_clauses_are_vivified() {
+        let (mut solver, lits) = solver_with_lits(4);
+        let [a, b, c, d] = lits[..] else {
+            unreachable!()
+        };
+        solver.add_clause([!a, b]);
+        solver.add_clause([!b, c]);
+
+        let clause_ref = solver.clauses.add_learned_clause([!a, c, d], 3);
+        solver.watch_clause(clause_ref);
+        solver.vivify_learned_clauses();
+
+        assert_eq!(&[!a, c], solver.clauses[clause_ref].lits());
+        assert_eq!(1, solver.statistics().vivified_clauses);
+    }
+
+    #[test]
+    fn subsumed_clauses_are_removed_and_strengthened() {
+        let (mut solver, lits) = solver_with_lits(4);
+        let [a, b, c, d] = lits[..] else {
+            unreachable!()
+        };
+        let clauses = [vec![a, b], vec![a, b, c], vec![a, !b, d], vec![!a, c, !d]];
+        for clause in clauses.iter() {
+            solver.add_clause(clause.iter().copied());
+        }
+
+        match solve_under(&mut solver, &[]) {
+            SolveResult::Satisfiable(solution) => {
+                for clause in clauses.iter() {
+                    assert!(clause
+                        .iter()
+                        .any(|&lit| solution.value(lit.var()) == lit.is_positive()));
+                }
+            }
+            _ => panic!("expected a solution"),
+        }
+
+        assert_eq!(1, solver.statistics().subsumed_clauses);
+        assert_eq!(1, solver.statistics().strengthened_clauses);
+    }
+
+    /// Add the clauses which put each pigeon in a hole, with at most one pigeon per hole. The
+    /// variable of pigeon `p` in hole `h` is `lits[p * holes + h]`.
+    fn add_pigeonhole(solver: &mut Solver<(), ()>, lits: &[Lit], pigeons: usize, holes: usize) {
+        for pigeon in 0..pigeons {
+            solver.add_clause((0..holes).map(|hole| lits[pigeon * holes + hole]));
+        }
+
+        for hole in 0..holes {
+            for first in 0..pigeons {
+                for second in first + 1..pigeons {
+                    solver.add_clause([!lits[first * holes + hole], !lits[second * holes + hole]]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn chronological_backtracking_refutes_unsatisfiable_formulas() {
+        let (mut solver, lits) = solver_with_lits(30);
+        add_pigeonhole(&mut solver, &lits, 6, 5);
+        solver.use_chronological_backtracking(0);
+
+        assert!(matches!(
+            solve_under(&mut solver, &[]),
+            SolveResult::Unsatisfiable
+        ));
+        assert!(solver.statistics().chronological_backtracks > 0);
+    }
+
+    #[test]
+    fn chronological_backtracking_finds_solutions() {
+        let (mut solver, lits) = solver_with_lits(36);
+        add_pigeonhole(&mut solver, &lits, 6, 6);
+        solver.use_chronological_backtracking(0);
+
+        match solve_under(&mut solver, &[]) {
+            SolveResult::Satisfiable(solution) => {
+                for hole in 0..6 {
+                    let pigeons_in_hole = (0..6)
+                        .filter(|pigeon| solution.value(lits[pigeon * 6 + hole].var()))
+                        .count();
+                    assert!(pigeons_in_hole <= 1);
+                }
+
+                for pigeon in 0..6 {
+                    assert!((0..6).any(|hole| solution.value(lits[pigeon * 6 + hole].var())));
+                }
+            }
+            _ => panic!("expected a solution"),
+        }
+    }
+
+    #[test]
+    fn mode_switching_alternates_the_search_modes() {
+        let (mut solver, lits) = solver_with_lits(42);
+        add_pigeonhole(&mut solver, &lits, 7, 6);
+        solver.use_mode_switching(10);
+
+        assert!(matches!(
+            solve_under(&mut solver, &[]),
+            SolveResult::Unsatisfiable
+        ));
+
+        let statistics = solver.statistics();
+        assert!(statistics.mode_switches > 1);
+        assert!(statistics.stable_conflicts > 0);
+    }
+
+    /// Checks that every literal the brancher is told was unassigned was reported as assigned.
+    struct PairedAssignments {
+        brancher: VsidsBrancher,
+        assigned: Vec<Lit>,
+    }
+
+    impl Brancher for PairedAssignments {
+        fn initialize(&mut self, largest_var: Var) {
+            self.brancher.initialize(largest_var);
+        }
+
+        fn on_variable_activated(&mut self, var: Var) {
+            self.brancher.on_variable_activated(var);
+        }
+
+        fn on_conflict(&mut self) {
+            self.brancher.on_conflict();
+        }
+
+        fn on_variable_assigned(&mut self, lit: Lit) {
+            assert!(!self.assigned.contains(&lit), "{lit:?} reported twice");
+            self.assigned.push(lit);
+        }
+
+        fn on_variable_unassigned(&mut self, lit: Lit) {
+            let idx = self.assigned.iter().position(|&assigned| assigned == lit);
+            let idx = idx.expect("unassigned literal was not reported");
+            self.assigned.swap_remove(idx);
+            self.brancher.on_variable_unassigned(lit);
+        }
+
+        fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+            self.brancher.next_decision(assignment)
+        }
+    }
+
+    #[test]
+    fn brancher_is_told_about_the_assignments_it_sees_undone() {
+        let (mut solver, lits) = solver_with_lits(46);
+        let [a, b, c, d] = lits[..4] else {
+            unreachable!()
+        };
+        solver.add_clause([!a, b]);
+        solver.add_clause([!a, c]);
+        solver.add_clause([!b, !c, d]);
+        solver.add_clause([!a, !d]);
+        add_pigeonhole(&mut solver, &lits[4..], 7, 6);
+
+        let brancher = PairedAssignments {
+            brancher: VsidsBrancher::new(0.95),
+            assigned: vec![],
+        };
+
+        assert!(matches!(
+            solver.solve(Indefinite, brancher, NeverRestart),
+            SolveResult::Unsatisfiable
+        ));
+        assert!(solver.statistics().probing_units > 0);
+    }
+
+    #[test]
+    fn unsatisfiability_is_remembered() {
+        let (mut solver, lits) = solver_with_lits(2);
+        let [a, b] = lits[..] else { unreachable!() };
+        solver.add_clause([a, b]);
+        solver.add_clause([a, !b]);
+        solver.add_clause([!a, b]);
+        solver.add_clause([!a, !b]);
+
+        assert!(matches!(
+            solve_under(&mut solver, &[]),
+            SolveResult::Unsatisfiable
+        ));
+        assert!(matches!(
+            solve_under(&mut solver, &[]),
+            SolveResult::Unsatisfiable
+        ));
+    }
+
+    /// Propagates `a -> b`, but only watches `a`.
+    struct Implication {
+        a: PropagatorVar<Lit>,
+        b: PropagatorVar<Lit>,
+    }
+
+    impl PropagatorFactory<(), LitEvent> for Implication {
+        fn create(
+            self,
+            registrar: &mut VariableRegistrar<'_, LitEvent>,
+        ) -> Box<dyn Propagator<(), LitEvent>> {
+            let a = registrar.register(self.a.variable, 0.into(), LitEvent::FixedTrue);
+            Box::new(Implication { a, b: self.b })
+        }
+    }
+
+    impl Propagator<(), LitEvent> for Implication {
+        fn propagate(&mut self, ctx: &mut Context<(), LitEvent>) -> Result<(), Conflict<()>> {
+            if ctx.value(self.a) == Some(true) {
+                let explanation = vec![Box::new(self.a.variable) as Box<dyn Atom<()>>];
+                ctx.assign(self.b, true, explanation)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn propagators_keep_watching_after_they_are_triggered() {
+        let mut solver = Solver::<(), LitEvent>::default();
+        let lits = solver.new_lits().take(2).collect::<Vec<_>>();
+        let [a, b] = lits[..] else { unreachable!() };
+
+        solver.add_propagator(Implication {
+            a: PropagatorVar {
+                variable: a,
+                local_id: 0.into(),
+            },
+            b: PropagatorVar {
+                variable: b,
+                local_id: 1.into(),
+            },
+        });
+
+        // The propagator is triggered by `a` in every solution in which `a` is true, so it has to
+        // keep watching `a` across the calls to solve.
+        let num_solutions = count_solutions(&mut solver, |solution| {
+            assert!(!solution.value(a.var()) || solution.value(b.var()));
+        });
+
+        assert_eq!(3, num_solutions);
+    }
+}
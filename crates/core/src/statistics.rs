@@ -0,0 +1,81 @@
+use std::{fmt::Display, time::Duration};
+
+use crate::mode::SearchMode;
+
+/// Counters which describe the work done by the solver. They are accumulated over all calls to
+/// solve.
+#[derive(Clone, Debug, Default)]
+pub struct SolverStatistics {
+    /// The number of conflicts, including the conflict at the root.
+    pub conflicts: u64,
+    /// The number of decisions made by the brancher. Assumptions are not counted.
+    pub decisions: u64,
+    /// The number of literals on the trail whose watches have been processed.
+    pub propagations: u64,
+    /// The number of times a propagator has been called.
+    pub propagator_calls: u64,
+    /// The number of restarts.
+    pub restarts: u64,
+    /// The mode the search is in. The search stays in focused mode unless mode switching is
+    /// enabled.
+    pub mode: SearchMode,
+    /// The number of times the search switched modes.
+    pub mode_switches: u64,
+    /// The number of conflicts in stable mode.
+    pub stable_conflicts: u64,
+    /// The number of conflicts after which the solver backtracked chronologically instead of
+    /// backjumping.
+    pub chronological_backtracks: u64,
+    /// The total length of the learned clauses before they were minimized.
+    pub learned_literals_before_minimization: u64,
+    /// The total length of the learned clauses.
+    pub learned_literals: u64,
+    /// The number of variables eliminated by simplification.
+    pub eliminated_vars: u64,
+    /// The number of blocked clauses removed by simplification.
+    pub blocked_clauses: u64,
+    /// The number of variables substituted by an equivalent literal.
+    pub substituted_vars: u64,
+    /// The number of units found by failed literal probing.
+    pub probing_units: u64,
+    /// The number of clauses removed because they are subsumed by another clause.
+    pub subsumed_clauses: u64,
+    /// The number of clauses strengthened by self-subsuming resolution.
+    pub strengthened_clauses: u64,
+    /// The number of learned clauses which were shortened by vivification.
+    pub vivified_clauses: u64,
+    /// The number of variables flipped by local search.
+    pub local_search_flips: u64,
+    /// The time spent searching.
+    pub solve_time: Duration,
+}
+
+impl SolverStatistics {
+    /// Call `f` with the name and the value of every statistic. The names are in camel case, as is
+    /// common for MiniZinc statistics. The solve time is given in seconds.
+    pub fn for_each(&self, mut f: impl FnMut(&str, &dyn Display)) {
+        f("conflicts", &self.conflicts);
+        f("decisions", &self.decisions);
+        f("propagations", &self.propagations);
+        f("propagatorCalls", &self.propagator_calls);
+        f("restarts", &self.restarts);
+        f("mode", &self.mode);
+        f("modeSwitches", &self.mode_switches);
+        f("stableConflicts", &self.stable_conflicts);
+        f("chronologicalBacktracks", &self.chronological_backtracks);
+        f(
+            "learnedLiteralsBeforeMinimization",
+            &self.learned_literals_before_minimization,
+        );
+        f("learnedLiterals", &self.learned_literals);
+        f("eliminatedVariables", &self.eliminated_vars);
+        f("blockedClauses", &self.blocked_clauses);
+        f("substitutedVariables", &self.substituted_vars);
+        f("probingUnits", &self.probing_units);
+        f("subsumedClauses", &self.subsumed_clauses);
+        f("strengthenedClauses", &self.strengthened_clauses);
+        f("vivifiedClauses", &self.vivified_clauses);
+        f("localSearchFlips", &self.local_search_flips);
+        f("solveTime", &self.solve_time.as_secs_f64());
+    }
+}
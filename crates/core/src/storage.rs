@@ -96,6 +96,10 @@ impl<Id, Value> Default for Arena<Id, Value> {
 }
 
 impl<Id, Value> Arena<Id, Value> {
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
     pub fn new_ref(&mut self) -> ArenaSlot<'_, Id, Value> {
         ArenaSlot { arena: self }
     }
@@ -0,0 +1,318 @@
+use log::trace;
+
+use crate::{
+    assignment::Assignment,
+    clause::{ClauseDb, ClauseId, ClauseRef, LongClause},
+    lit::{Lit, Var},
+    proof::ProofLog,
+    storage::KeyedVec,
+};
+
+/// Removes subsumed clauses and strengthens clauses with self-subsuming resolution.
+///
+/// A clause `C` subsumes a clause `D` if every literal of `C` occurs in `D`, in which case `D` is
+/// redundant. If `C` only differs from a subset of `D` in a single literal `l`, where `!l` occurs
+/// in `D`, then the resolvent of both clauses on `l` subsumes `D`, so `!l` can be removed from `D`.
+///
+/// Only the pairs of clauses of which at least one was added since the previous call are compared,
+/// which keeps repeated calls cheap.
+#[derive(Default)]
+pub struct Subsumer {
+    /// For every literal, the clauses it occurs in. Only populated while subsuming.
+    occurrences: KeyedVec<Lit, Vec<ClauseRef>>,
+    /// Marks the literals of the clause which is compared to other clauses.
+    marks: KeyedVec<Lit, bool>,
+    /// The largest clause ID at the end of the previous call. Clauses with a larger ID are new.
+    last_checked_id: Option<ClauseId>,
+}
+
+/// The outcome of subsuming the clauses.
+#[derive(Debug, Default)]
+pub struct Subsumption {
+    /// Unit clauses which were derived by strengthening, with their IDs. They still have to be
+    /// assigned.
+    pub units: Vec<(Lit, ClauseId)>,
+    /// The number of clauses which were removed because they are subsumed.
+    pub subsumed_clauses: u64,
+    /// The number of literals which were removed from clauses.
+    pub strengthened_clauses: u64,
+}
+
+impl Subsumer {
+    pub fn grow_to(&mut self, var: Var) {
+        self.occurrences.grow_to(Lit::positive(var));
+        self.marks.grow_to(Lit::positive(var));
+    }
+
+    /// Compare the clauses in the database which are not satisfied at the root. If `learned_only`
+    /// is set, the irredundant clauses are left alone, otherwise all clauses are compared.
+    ///
+    /// The clauses are changed in place, so they have to be watched again afterwards. Strengthened
+    /// clauses get a new ID, and are logged to the proof with both antecedents as hints.
+    pub fn subsume(
+        &mut self,
+        clauses: &mut ClauseDb,
+        assignment: &Assignment,
+        learned_only: bool,
+        mut proof: Option<&mut Box<dyn ProofLog>>,
+    ) -> Subsumption {
+        let mut subsumption = Subsumption::default();
+        let mut candidates = vec![];
+        let mut max_id = self.last_checked_id;
+
+        for (clause_ref, clause) in clauses.iter() {
+            if (learned_only && !clause.is_learned())
+                || clause
+                    .iter()
+                    .any(|&lit| assignment.value(lit) == Some(true))
+            {
+                continue;
+            }
+
+            for &lit in clause.iter() {
+                self.occurrences[lit].push(clause_ref);
+            }
+
+            if self.last_checked_id.is_none_or(|id| clause.id() > id) {
+                candidates.push(clause_ref);
+            }
+
+            max_id = max_id.max(Some(clause.id()));
+        }
+
+        candidates.sort_by_key(|&clause_ref| clauses[clause_ref].len());
+
+        for clause_ref in candidates {
+            // A candidate may have been subsumed by an earlier candidate, or became a unit.
+            if clauses[clause_ref].is_deleted() {
+                continue;
+            }
+
+            self.forward(clause_ref, clauses, &mut proof, &mut subsumption);
+
+            if !clauses[clause_ref].is_deleted() {
+                self.backward(clause_ref, clauses, &mut proof, &mut subsumption);
+            }
+        }
+
+        for occurrences in self.occurrences.iter_mut() {
+            occurrences.clear();
+        }
+
+        // Strengthened clauses got a new ID, so they are compared again in the next call.
+        self.last_checked_id = max_id;
+
+        trace!("subsumption: {subsumption:?}");
+
+        subsumption
+    }
+
+    /// Remove or strengthen the clause with one of the clauses from previous calls that are at most
+    /// as long. Other candidates are compared by [`Subsumer::backward`].
+    fn forward(
+        &mut self,
+        clause_ref: ClauseRef,
+        clauses: &mut ClauseDb,
+        proof: &mut Option<&mut Box<dyn ProofLog>>,
+        subsumption: &mut Subsumption,
+    ) {
+        let Some(last_checked_id) = self.last_checked_id else {
+            return;
+        };
+
+        let lits = clauses[clause_ref].lits().to_vec();
+        for &lit in lits.iter() {
+            self.marks[lit] = true;
+        }
+
+        // Every clause which can subsume or strengthen the clause has its first literal, or its
+        // negation, in the clause, so each of them is visited exactly once.
+        let mut outcome = None;
+        'search: for &lit in lits.iter() {
+            for first in [lit, !lit] {
+                for &other in self.occurrences[first].iter() {
+                    let other_clause = &clauses[other];
+                    if other_clause.id() > last_checked_id
+                        || other_clause[0] != first
+                        || other_clause.len() > lits.len()
+                    {
+                        continue;
+                    }
+
+                    match self.compare(other_clause.lits()) {
+                        Some(None) if !may_subsume(other_clause, &clauses[clause_ref]) => {}
+                        Some(removed) => {
+                            outcome = Some((other, removed));
+                            break 'search;
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+
+        for &lit in lits.iter() {
+            self.marks[lit] = false;
+        }
+
+        match outcome {
+            Some((other, None)) => {
+                self.remove_subsumed(other, clause_ref, clauses, proof, subsumption)
+            }
+            Some((other, Some(removed))) => {
+                self.strengthen(clause_ref, removed, other, clauses, proof, subsumption)
+            }
+            None => {}
+        }
+    }
+
+    /// Remove or strengthen the clauses which are at least as long as the clause with it.
+    fn backward(
+        &mut self,
+        clause_ref: ClauseRef,
+        clauses: &mut ClauseDb,
+        proof: &mut Option<&mut Box<dyn ProofLog>>,
+        subsumption: &mut Subsumption,
+    ) {
+        // Every clause which is subsumed or strengthened contains the literal with the fewest
+        // occurrences, or its negation.
+        let lit = clauses[clause_ref]
+            .iter()
+            .copied()
+            .min_by_key(|&lit| self.occurrences[lit].len() + self.occurrences[!lit].len())
+            .expect("clauses have at least two literals");
+
+        let others = self.occurrences[lit]
+            .iter()
+            .chain(self.occurrences[!lit].iter())
+            .copied()
+            .filter(|&other| other != clause_ref)
+            .collect::<Vec<_>>();
+
+        for other in others {
+            let other_clause = &clauses[other];
+            if other_clause.is_deleted() || other_clause.len() < clauses[clause_ref].len() {
+                continue;
+            }
+
+            for &lit in other_clause.iter() {
+                self.marks[lit] = true;
+            }
+
+            let outcome = self.compare(clauses[clause_ref].lits());
+
+            for &lit in clauses[other].iter() {
+                self.marks[lit] = false;
+            }
+
+            match outcome {
+                Some(None) if may_subsume(&clauses[clause_ref], &clauses[other]) => {
+                    self.remove_subsumed(clause_ref, other, clauses, proof, subsumption)
+                }
+                Some(Some(removed)) => {
+                    self.strengthen(other, removed, clause_ref, clauses, proof, subsumption)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Compare the given clause to the clause whose literals are marked. Returns `Some(None)` if
+    /// the given clause subsumes the marked clause, and `Some(Some(lit))` if `lit` can be removed
+    /// from the marked clause.
+    fn compare(&self, lits: &[Lit]) -> Option<Option<Lit>> {
+        let mut removed = None;
+
+        for &lit in lits {
+            if self.marks[lit] {
+                continue;
+            }
+
+            if self.marks[!lit] && removed.is_none() {
+                removed = Some(!lit);
+            } else {
+                return None;
+            }
+        }
+
+        Some(removed)
+    }
+
+    fn remove_subsumed(
+        &mut self,
+        subsuming: ClauseRef,
+        subsumed: ClauseRef,
+        clauses: &mut ClauseDb,
+        proof: &mut Option<&mut Box<dyn ProofLog>>,
+        subsumption: &mut Subsumption,
+    ) {
+        trace!(
+            "{:?} subsumes {:?}",
+            &clauses[subsuming],
+            &clauses[subsumed]
+        );
+
+        self.unindex(subsumed, clauses);
+
+        if let Some(proof) = proof.as_mut() {
+            let clause = &clauses[subsumed];
+            proof.delete_clause(clause.id(), clause.lits());
+        }
+
+        clauses.delete_clause(subsumed);
+        subsumption.subsumed_clauses += 1;
+    }
+
+    fn strengthen(
+        &mut self,
+        clause_ref: ClauseRef,
+        lit: Lit,
+        antecedent: ClauseRef,
+        clauses: &mut ClauseDb,
+        proof: &mut Option<&mut Box<dyn ProofLog>>,
+        subsumption: &mut Subsumption,
+    ) {
+        trace!(
+            "{:?} removes {lit:?} from {:?}",
+            &clauses[antecedent],
+            &clauses[clause_ref]
+        );
+
+        let strengthened = clauses[clause_ref]
+            .iter()
+            .copied()
+            .filter(|&other| other != lit)
+            .collect::<Vec<_>>();
+
+        // Assuming the negation of the strengthened clause, the antecedent propagates the negation
+        // of the removed literal, after which the clause is falsified.
+        let id = clauses.new_clause_id();
+        if let Some(proof) = proof.as_mut() {
+            let clause = &clauses[clause_ref];
+            proof.add_clause(id, &strengthened, &[clauses[antecedent].id(), clause.id()]);
+            proof.delete_clause(clause.id(), clause.lits());
+        }
+
+        subsumption.strengthened_clauses += 1;
+
+        if strengthened.len() == 1 {
+            self.unindex(clause_ref, clauses);
+            clauses.delete_clause(clause_ref);
+            subsumption.units.push((strengthened[0], id));
+        } else {
+            self.occurrences[lit].retain(|&other| other != clause_ref);
+            clauses.strengthen_clause(clause_ref, lit, id);
+        }
+    }
+
+    fn unindex(&mut self, clause_ref: ClauseRef, clauses: &ClauseDb) {
+        for &lit in clauses[clause_ref].iter() {
+            self.occurrences[lit].retain(|&other| other != clause_ref);
+        }
+    }
+}
+
+/// A learned clause may be deleted later, so it cannot replace an irredundant clause.
+fn may_subsume(subsuming: &LongClause, subsumed: &LongClause) -> bool {
+    !subsuming.is_learned() || subsumed.is_learned()
+}
@@ -0,0 +1,47 @@
+use std::fmt::Debug;
+
+use crate::{
+    brancher::VsidsBrancher,
+    lit::Lit,
+    restart::NeverRestart,
+    solver::{Solution, SolveResult, Solver},
+    storage::StaticIndexer,
+    termination::Indefinite,
+};
+
+/// Count the solutions of the solver by blocking each solution with a clause until the solver
+/// proves there are no more. Each solution is passed to `check` before it is blocked. The clause
+/// blocks the values of all variables, so it also blocks the values of the domains they encode.
+pub fn count_solutions<Domains, Event>(
+    solver: &mut Solver<Domains, Event>,
+    mut check: impl FnMut(&Solution<'_, Domains>),
+) -> usize
+where
+    Event: Copy + Debug + StaticIndexer,
+{
+    let mut brancher = VsidsBrancher::new(0.95);
+    let mut num_solutions = 0;
+
+    loop {
+        let blocking_clause = match solver.solve(Indefinite, &mut brancher, NeverRestart) {
+            SolveResult::Satisfiable(solution) => {
+                check(&solution);
+
+                solution
+                    .vars()
+                    .map(|var| match solution.value(var) {
+                        true => Lit::negative(var),
+                        false => Lit::positive(var),
+                    })
+                    .collect::<Vec<_>>()
+            }
+            SolveResult::Unsatisfiable => break,
+            _ => panic!("expected the solver to finish"),
+        };
+
+        num_solutions += 1;
+        solver.add_clause(blocking_clause);
+    }
+
+    num_solutions
+}
@@ -1,11 +1,17 @@
 use std::ops::Index;
 
-use crate::lit::Lit;
+use crate::{lit::Lit, search_tree::SearchTree};
 
+/// The literals in the order they were assigned. With chronological backtracking, a literal can be
+/// assigned at a lower decision level than the current one, so the decision levels on the trail
+/// are not necessarily in increasing order.
 #[derive(Default)]
 pub struct Trail {
     trail: Vec<Lit>,
     trail_delim: Vec<usize>,
+    /// The literals which stay on the trail when backtracking, because they are assigned at or
+    /// below the decision level that is backtracked to.
+    kept: Vec<Lit>,
 }
 
 impl Trail {
@@ -22,7 +28,22 @@ impl Trail {
         self.trail_delim.push(self.trail.len());
     }
 
-    pub fn backtrack_to(&mut self, decision_level: usize) -> impl Iterator<Item = Lit> + '_ {
+    /// The position on the trail where the given decision level ends. Backtracking to the
+    /// decision level removes the literals from this position onwards, except the literals which
+    /// are assigned at or below the decision level.
+    pub fn level_end(&self, decision_level: usize) -> usize {
+        self.trail_delim[decision_level]
+    }
+
+    /// Remove the literals which are assigned above the given decision level. The removed
+    /// literals are returned in reverse trail order, and the iterator has to be consumed. The
+    /// literals assigned at or below the decision level keep their order, and move to the end of
+    /// the trail.
+    pub fn backtrack_to<'a>(
+        &'a mut self,
+        decision_level: usize,
+        search_tree: &'a SearchTree,
+    ) -> impl Iterator<Item = Lit> + 'a {
         let target_len = self.trail_delim[decision_level];
         self.trail_delim.truncate(decision_level);
 
@@ -30,6 +51,8 @@ impl Trail {
 
         BacktrackingIterator {
             trail: self,
+            search_tree,
+            decision_level,
             target_len,
             current,
         }
@@ -50,6 +73,8 @@ impl Index<usize> for Trail {
 
 struct BacktrackingIterator<'a> {
     trail: &'a mut Trail,
+    search_tree: &'a SearchTree,
+    decision_level: usize,
     current: usize,
     target_len: usize,
 }
@@ -58,27 +83,48 @@ impl<'a> Iterator for BacktrackingIterator<'a> {
     type Item = Lit;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current > 0 && self.current > self.target_len {
+        while self.current > self.target_len {
             self.current -= 1;
-            Some(self.trail[self.current])
-        } else {
-            None
+            let lit = self.trail[self.current];
+
+            if self.search_tree.decision_level(lit.var()) <= self.decision_level {
+                self.trail.kept.push(lit);
+            } else {
+                return Some(lit);
+            }
         }
+
+        None
     }
 }
 
 impl<'a> Drop for BacktrackingIterator<'a> {
     fn drop(&mut self) {
-        self.trail.trail.truncate(self.target_len);
+        let Trail { trail, kept, .. } = &mut *self.trail;
+
+        trail.truncate(self.target_len);
+        trail.extend(kept.drain(..).rev());
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::lit;
+    use crate::{lit, lit::Var};
 
     use super::*;
 
+    fn search_tree(num_vars: u32) -> SearchTree {
+        let mut search_tree = SearchTree::default();
+        search_tree.grow_to(Var::try_from(num_vars).unwrap());
+        search_tree
+    }
+
+    /// Enqueue the literal at the given decision level.
+    fn enqueue(trail: &mut Trail, search_tree: &mut SearchTree, lit: Lit, decision_level: usize) {
+        trail.enqueue(lit);
+        search_tree.register_assignment_at(lit, decision_level);
+    }
+
     #[test]
     fn len_of_trail_is_number_of_enqueued_lits() {
         let mut trail = Trail::default();
@@ -93,23 +139,53 @@ mod tests {
     #[test]
     fn backtracking_returns_iterator_with_removed_literals() {
         let mut trail = Trail::default();
+        let mut search_tree = search_tree(9);
 
-        trail.enqueue(unsafe { lit!(1) });
-        trail.enqueue(unsafe { lit!(2) });
-        trail.enqueue(unsafe { lit!(3) });
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(1) }, 0);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(2) }, 0);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(3) }, 0);
         trail.push();
-        trail.enqueue(unsafe { lit!(4) });
-        trail.enqueue(unsafe { lit!(5) });
-        trail.enqueue(unsafe { lit!(6) });
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(4) }, 1);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(5) }, 1);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(6) }, 1);
         trail.push();
-        trail.enqueue(unsafe { lit!(7) });
-        trail.enqueue(unsafe { lit!(8) });
-        trail.enqueue(unsafe { lit!(9) });
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(7) }, 2);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(8) }, 2);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(9) }, 2);
 
-        let removed_lits = trail.backtrack_to(0).collect::<Vec<_>>();
+        let removed_lits = trail.backtrack_to(0, &search_tree).collect::<Vec<_>>();
         assert_eq!(
             unsafe { vec![lit!(9), lit!(8), lit!(7), lit!(6), lit!(5), lit!(4)] },
             removed_lits
         );
     }
+
+    #[test]
+    fn backtracking_keeps_literals_assigned_at_lower_levels() {
+        let mut trail = Trail::default();
+        let mut search_tree = search_tree(6);
+
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(1) }, 0);
+        trail.push();
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(2) }, 1);
+        trail.push();
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(3) }, 2);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(4) }, 0);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(5) }, 2);
+        enqueue(&mut trail, &mut search_tree, unsafe { lit!(6) }, 1);
+
+        let removed_lits = trail.backtrack_to(1, &search_tree).collect::<Vec<_>>();
+        assert_eq!(unsafe { vec![lit!(5), lit!(3)] }, removed_lits);
+        assert_eq!(
+            unsafe { vec![lit!(1), lit!(2), lit!(4), lit!(6)] },
+            trail.iter().collect::<Vec<_>>()
+        );
+
+        let removed_lits = trail.backtrack_to(0, &search_tree).collect::<Vec<_>>();
+        assert_eq!(unsafe { vec![lit!(6), lit!(2)] }, removed_lits);
+        assert_eq!(
+            unsafe { vec![lit!(1), lit!(4)] },
+            trail.iter().collect::<Vec<_>>()
+        );
+    }
 }
@@ -2,6 +2,10 @@
 //! can be very large, the implementation is designed to read the file in chunks. The parser also
 //! will not allocate for every encountered clause, but rather re-use its buffers.
 //!
+//! Besides clauses, XOR constraints are accepted on lines which start with an `x`, as in
+//! CryptoMiniSat. The line `x1 -2 3 0` states that an odd number of the literals `1`, `-2` and
+//! `3` is true. XOR constraints count towards the number of clauses in the header.
+//!
 //! It should be noted that the parser should not be used as a DIMACS validator. Even though it
 //! should only accept valid DIMACS files, the errors are not extremely detailed. Perhaps this
 //! could change over time, however.
@@ -13,10 +17,83 @@ use std::{
 
 use thiserror::Error;
 
+/// The longest XOR constraint that the default encoding adds as clauses directly. Longer XOR
+/// constraints are cut into pieces of this length, which are chained by new variables.
+const MAX_XOR_PIECE_LEN: usize = 4;
+
 /// A dimacs sink stores a set of clauses and allows for new variables to be created.
 pub trait DimacsSink {
     /// Add a new clause to the formula.
     fn add_clause(&mut self, clause: &[NonZeroI32]);
+
+    /// Add an XOR constraint to the formula, which states that an odd number of the literals is
+    /// true.
+    ///
+    /// By default the constraint is added as clauses. It is cut into pieces of at most four
+    /// literals, where a variable from `new_variables` carries the parity of a piece over to the
+    /// next one. Every piece is added as the clauses which forbid the assignments to its literals
+    /// with an even number of true literals, so the number of clauses is linear in the number of
+    /// literals.
+    fn add_xor(&mut self, lits: &[NonZeroI32], new_variables: &mut NewVariables) {
+        let mut piece = lits.to_vec();
+
+        while piece.len() > MAX_XOR_PIECE_LEN {
+            // The new variable is true if an odd number of the split off literals is true, which
+            // takes its place in the remainder of the constraint.
+            let parity = new_variables.next_variable();
+            let mut split_off = piece.split_off(piece.len() - (MAX_XOR_PIECE_LEN - 1));
+            split_off.push(-parity);
+
+            add_xor_piece(self, &split_off);
+            piece.push(parity);
+        }
+
+        add_xor_piece(self, &piece);
+    }
+}
+
+/// Add the XOR constraint over the literals as the `2^(n - 1)` clauses which forbid the
+/// assignments with an even number of true literals.
+fn add_xor_piece(sink: &mut (impl DimacsSink + ?Sized), lits: &[NonZeroI32]) {
+    let mut clause = Vec::with_capacity(lits.len());
+
+    for true_lits in 0_u32..1 << lits.len() {
+        if true_lits.count_ones() % 2 == 1 {
+            continue;
+        }
+
+        clause.clear();
+        clause.extend(lits.iter().enumerate().map(|(idx, &lit)| {
+            if true_lits & (1 << idx) != 0 {
+                -lit
+            } else {
+                lit
+            }
+        }));
+
+        sink.add_clause(&clause);
+    }
+}
+
+/// Hands out the variables which a sink introduces while the formula is parsed, such as those of
+/// the default encoding of XOR constraints. They are numbered after the variables in the header.
+pub struct NewVariables {
+    next: usize,
+}
+
+impl NewVariables {
+    /// Get the code of a variable which does not occur in the formula yet.
+    pub fn next_variable(&mut self) -> NonZeroI32 {
+        let code = i32::try_from(self.next).expect("the parser checks that the variables fit");
+        self.next += 1;
+
+        NonZeroI32::new(code).expect("variables are numbered from 1")
+    }
+
+    /// Whether the given number of variables can still be introduced.
+    fn has_room_for(&self, num_variables: usize) -> bool {
+        self.next.saturating_add(num_variables) <= i32::MAX as usize + 1
+    }
 }
 
 #[derive(Debug, Error)]
@@ -44,6 +121,9 @@ pub enum DimacsParseError {
 
     #[error("expected to parse {expected} clauses, but parsed {parsed}")]
     IncorrectClauseCount { expected: usize, parsed: usize },
+
+    #[error("the XOR constraint over {0} literals needs more variables than DIMACS can number")]
+    XorTooLong(usize),
 }
 
 pub fn parse_cnf<Sink: DimacsSink>(
@@ -73,8 +153,11 @@ struct DimacsParser<Sink: DimacsSink, SinkConstructor> {
     sink_constructor: Option<SinkConstructor>,
     sink: Option<Sink>,
     header: Option<CNFHeader>,
+    new_variables: NewVariables,
     buffer: String,
     clause: Vec<NonZeroI32>,
+    /// Whether the literals which are being parsed belong to an XOR constraint.
+    is_xor: bool,
     state: ParseState,
     parsed_clauses: usize,
 }
@@ -100,8 +183,10 @@ where
             sink_constructor: Some(sink_constructor),
             sink: None,
             header: None,
+            new_variables: NewVariables { next: 1 },
             buffer: String::new(),
             clause: vec![],
+            is_xor: false,
             state: ParseState::StartLine,
             parsed_clauses: 0,
         }
@@ -125,6 +210,12 @@ where
                         self.state = ParseState::Comment;
                     }
 
+                    // An XOR constraint cannot start in the middle of a clause.
+                    b'x' if self.clause.is_empty() => {
+                        self.is_xor = true;
+                        self.state = ParseState::Clause;
+                    }
+
                     b @ b'1'..=b'9' => {
                         self.start_literal(b, true);
                     }
@@ -232,6 +323,7 @@ where
             .expect("only parse header once");
 
         self.sink = Some(sink_constructor(&header));
+        self.new_variables.next = header.num_variables.saturating_add(1);
 
         self.header = Some(header);
 
@@ -254,7 +346,18 @@ where
     fn finish_clause(&mut self) -> Result<(), DimacsParseError> {
         let sink = self.sink.as_mut().ok_or(DimacsParseError::MissingHeader)?;
         self.parsed_clauses += 1;
-        sink.add_clause(&self.clause);
+
+        if std::mem::take(&mut self.is_xor) {
+            // Every piece of the default encoding but the last takes a new variable.
+            if !self.new_variables.has_room_for(self.clause.len()) {
+                return Err(DimacsParseError::XorTooLong(self.clause.len()));
+            }
+
+            sink.add_xor(&self.clause, &mut self.new_variables);
+        } else {
+            sink.add_clause(&self.clause);
+        }
+
         self.clause.clear();
 
         Ok(())
@@ -413,6 +516,70 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn xor_lines_are_added_as_clauses() {
+        let source = "p cnf 3 2\nx1 -2 0\n-1 3 0\n";
+        let formula = parse_cnf_source(source);
+
+        assert_eq!(vec![vec![1, -2], vec![-1, 2], vec![-1, 3]], formula);
+    }
+
+    #[test]
+    fn long_xor_lines_are_chained_by_new_variables() {
+        let lits = (1..=100).map(|code| code.to_string()).collect::<Vec<_>>();
+        let source = format!("p cnf 100 1\nx{} 0\n", lits.join(" "));
+        let formula = parse_cnf_source(&source);
+
+        // Every piece but the last takes two literals off the constraint, and adds 8 clauses.
+        assert_eq!(8 * 49, formula.len());
+        assert_eq!(
+            Some(148),
+            formula.iter().flatten().map(|lit| lit.abs()).max()
+        );
+    }
+
+    #[test]
+    fn chained_xor_lines_only_allow_an_odd_number_of_true_literals() {
+        let source = "p cnf 9 1\nx1 -2 3 4 5 6 7 8 9 0\n";
+        let formula = parse_cnf_source(source);
+        let num_vars = formula.iter().flatten().map(|lit| lit.abs()).max().unwrap();
+
+        // The assignments to the variables of the header which can be extended to the new ones.
+        let mut satisfiable = vec![false; 1 << 9];
+        for assignment in 0_u32..1 << num_vars {
+            let value = |lit: i32| (assignment >> (lit.abs() - 1) & 1 == 1) == (lit > 0);
+
+            if formula
+                .iter()
+                .all(|clause| clause.iter().any(|&lit| value(lit)))
+            {
+                satisfiable[(assignment & 0x1ff) as usize] = true;
+            }
+        }
+
+        for (assignment, &is_satisfiable) in satisfiable.iter().enumerate() {
+            // The second literal is negative.
+            let num_true = (assignment ^ 0b10).count_ones();
+            assert_eq!(num_true % 2 == 1, is_satisfiable);
+        }
+    }
+
+    #[test]
+    fn xor_lines_may_separate_x_and_literals() {
+        let source = "p cnf 3 1\nx 1 2 3 0\n";
+        let formula = parse_xor_source(source);
+
+        assert_eq!(vec![vec![1, 2, 3]], formula);
+    }
+
+    #[test]
+    fn xor_cannot_start_within_clause() {
+        let source = "p cnf 2 2\n1\nx2 0";
+        let err = get_cnf_parse_error(source);
+
+        assert!(matches!(err, DimacsParseError::UnexpectedCharacter('x')));
+    }
+
     fn parse_cnf_source(source: &str) -> Vec<Vec<i32>> {
         parse_cnf::<Vec<Vec<i32>>>(source.as_bytes(), |_| vec![]).expect("valid dimacs")
     }
@@ -421,6 +588,23 @@ mod tests {
         parse_cnf::<Vec<Vec<i32>>>(source.as_bytes(), |_| vec![]).expect_err("invalid dimacs")
     }
 
+    fn parse_xor_source(source: &str) -> Vec<Vec<i32>> {
+        parse_cnf::<Xors>(source.as_bytes(), |_| Xors(vec![]))
+            .expect("valid dimacs")
+            .0
+    }
+
+    /// Only records the XOR constraints.
+    struct Xors(Vec<Vec<i32>>);
+
+    impl DimacsSink for Xors {
+        fn add_clause(&mut self, _: &[NonZeroI32]) {}
+
+        fn add_xor(&mut self, lits: &[NonZeroI32], _: &mut NewVariables) {
+            self.0.push(lits.iter().map(|lit| lit.get()).collect());
+        }
+    }
+
     impl DimacsSink for Vec<Vec<i32>> {
         fn add_clause(&mut self, clause: &[NonZeroI32]) {
             self.push(clause.iter().map(|lit| lit.get()).collect());
@@ -8,6 +8,7 @@ use limiga_core::{
     domains::TypedDomainStore,
     integer::{interval_domain::IntInterval, Int, IntEvent},
     propagation::{LitEvent, SDomainEvent},
+    restart::LubyRestarts,
     solver::{SolveResult, Solver},
     storage::{Indexer, StaticIndexer},
     termination::Indefinite,
@@ -166,7 +167,8 @@ fn main() {
     }
 
     let brancher = VsidsBrancher::new(0.99);
-    match solver.solve(Indefinite, brancher) {
+    let restart_policy = LubyRestarts::new(100);
+    match solver.solve(Indefinite, brancher, restart_policy) {
         SolveResult::Satisfiable(solution) => {
             for row in matrix.iter() {
                 row.iter()
@@ -177,7 +179,9 @@ fn main() {
             }
         }
 
-        SolveResult::Unsatisfiable => println!("Unsatisfiable"),
+        SolveResult::Unsatisfiable | SolveResult::UnsatisfiableUnderAssumptions(_) => {
+            println!("Unsatisfiable")
+        }
         SolveResult::Unknown => println!("Unknown"),
     }
 }
@@ -0,0 +1,393 @@
+use std::{cmp::Reverse, collections::BTreeSet};
+
+use limiga_core::{lit::Lit, storage::KeyedVec};
+
+/// The largest bound of the detected cardinality constraints. The binomial encoding of an
+/// at-most-k constraint has a clause for every `k + 1` of its literals, so only short clauses are
+/// considered.
+const MAX_BOUND: usize = 2;
+/// The number of literals which are visited when looking for the literals which are implied by a
+/// literal through binary clauses.
+const MAX_IMPLIED_LITS: usize = 512;
+/// The number of literals which are visited when looking for implied literals, over all literals.
+const MAX_IMPLICATION_STEPS: usize = 1 << 18;
+/// The number of steps taken when growing constraints, over all constraints of a bound. A step
+/// visits a neighbour of a candidate literal, or looks up a set of literals among the forbidden
+/// sets.
+const MAX_GROWTH_STEPS: usize = 1 << 22;
+/// The detected constraints are only used if the clauses they replace make up at least this
+/// fraction of the formula. A formula with propagators is not simplified at all, which only pays
+/// off if counting replaces most of the clauses.
+const MIN_CARDINALITY_CLAUSE_FRACTION: f64 = 0.5;
+
+/// The constraint that at most `bound` of the literals are true.
+pub struct AtMost {
+    pub lits: Box<[Lit]>,
+    pub bound: usize,
+}
+
+/// Finds the cardinality constraints which are encoded as clauses. An at-most-k constraint is
+/// recognised in its binomial encoding, which has a clause forbidding every `k + 1` of the
+/// literals to be true together. For at-most-one constraints, the pairs of literals can also be
+/// forbidden through a chain of binary clauses, which covers the sequential counter encoding and
+/// similar encodings with auxiliary variables.
+#[derive(Default)]
+pub struct CardinalityDetector {
+    /// The clauses which can be part of a binomial encoding. They are held back from the solver,
+    /// as the clauses of a detected constraint are replaced by the constraint.
+    clauses: Vec<Box<[Lit]>>,
+    /// The number of clauses which were added, including the ones which are not held back.
+    num_clauses: usize,
+}
+
+impl CardinalityDetector {
+    /// Add a clause of the formula. Returns whether the clause is held back, in which case it is
+    /// returned by [`CardinalityDetector::into_constraints`] unless it is replaced.
+    pub fn add_clause(&mut self, lits: &[Lit]) -> bool {
+        self.num_clauses += 1;
+
+        if !(2..=MAX_BOUND + 1).contains(&lits.len()) {
+            return false;
+        }
+
+        let mut vars = lits.iter().map(|lit| lit.var()).collect::<Vec<_>>();
+        vars.sort();
+
+        // Clauses with a repeated variable do not take part in an encoding.
+        if vars.windows(2).any(|pair| pair[0] == pair[1]) {
+            return false;
+        }
+
+        self.clauses.push(lits.into());
+        true
+    }
+
+    /// The detected cardinality constraints, and the held back clauses which are not replaced by
+    /// them. If the constraints are only a small part of the formula, none are returned.
+    pub fn into_constraints(self) -> (Vec<AtMost>, Vec<Box<[Lit]>>) {
+        let mut constraints = vec![];
+        // The sets of literals which are forbidden by a detected constraint.
+        let mut covered = BTreeSet::new();
+
+        for bound in 1..=MAX_BOUND {
+            // A clause forbids the negations of its literals to be true together.
+            let mut forbidden = self
+                .clauses
+                .iter()
+                .filter(|clause| clause.len() == bound + 1)
+                .map(|clause| forbidden_lits(clause))
+                .collect::<BTreeSet<_>>();
+
+            if bound == 1 {
+                forbidden.extend(self.implied_pairs());
+            }
+
+            constraints.extend(
+                find_constraints(&forbidden, bound, &mut covered)
+                    .into_iter()
+                    .map(|lits| AtMost { lits, bound }),
+            );
+        }
+
+        // Only the clauses which forbid a covered set are replaced. The covered sets which are
+        // forbidden through a chain of binary clauses do not remove any clause.
+        let is_replaced = |clause: &[Lit]| covered.contains(&forbidden_lits(clause));
+        let num_replaced = self
+            .clauses
+            .iter()
+            .filter(|clause| is_replaced(clause))
+            .count();
+
+        if (num_replaced as f64) < MIN_CARDINALITY_CLAUSE_FRACTION * self.num_clauses as f64 {
+            return (vec![], self.clauses);
+        }
+
+        let clauses = self
+            .clauses
+            .into_iter()
+            .filter(|clause| !is_replaced(clause))
+            .collect();
+
+        (constraints, clauses)
+    }
+
+    /// The pairs of literals which cannot be true together because one implies the negation of
+    /// the other through the binary clauses.
+    fn implied_pairs(&self) -> Vec<Box<[Lit]>> {
+        let mut implications = KeyedVec::<Lit, Vec<Lit>>::default();
+        let mut is_visited = KeyedVec::<Lit, bool>::default();
+        let mut sources = vec![];
+
+        for clause in self.clauses.iter().filter(|clause| clause.len() == 2) {
+            for (lit, other) in [(clause[0], clause[1]), (clause[1], clause[0])] {
+                implications.grow_to(lit);
+                implications.grow_to(!lit);
+                is_visited.grow_to(lit);
+                is_visited.grow_to(!lit);
+                implications[!lit].push(other);
+                sources.push(!lit);
+            }
+        }
+
+        sources.sort();
+        sources.dedup();
+
+        let mut pairs = vec![];
+        let mut steps = 0;
+
+        for lit in sources {
+            // The visited literals, in the order of a breadth-first search.
+            let mut visited = vec![lit];
+            is_visited[lit] = true;
+
+            let mut idx = 0;
+            while idx < visited.len()
+                && visited.len() < MAX_IMPLIED_LITS
+                && steps < MAX_IMPLICATION_STEPS
+            {
+                let implying = visited[idx];
+                idx += 1;
+
+                for &implied in implications[implying].iter() {
+                    if is_visited[implied] {
+                        continue;
+                    }
+
+                    steps += 1;
+                    is_visited[implied] = true;
+                    visited.push(implied);
+
+                    if implied.var() != lit.var() {
+                        let mut pair = [lit, !implied];
+                        pair.sort();
+                        pairs.push(pair.into());
+                    }
+                }
+            }
+
+            for visited_lit in visited {
+                is_visited[visited_lit] = false;
+            }
+        }
+
+        pairs
+    }
+}
+
+/// The literals which the clause forbids to be true together, in increasing order.
+fn forbidden_lits(clause: &[Lit]) -> Box<[Lit]> {
+    let mut lits = clause.iter().map(|&lit| !lit).collect::<Box<[_]>>();
+    lits.sort();
+    lits
+}
+
+/// Find sets of literals of which every `bound + 1` literals are forbidden to be true together.
+/// Constraints are grown greedily from the literals which occur in the most forbidden sets, as
+/// long as they cover enough forbidden sets which are not covered yet. The forbidden sets covered
+/// by the constraints are added to `covered`.
+fn find_constraints(
+    forbidden: &BTreeSet<Box<[Lit]>>,
+    bound: usize,
+    covered: &mut BTreeSet<Box<[Lit]>>,
+) -> Vec<Box<[Lit]>> {
+    let mut occurrences = KeyedVec::<Lit, Vec<&[Lit]>>::default();
+    let mut neighbours = KeyedVec::<Lit, Vec<Lit>>::default();
+    let mut seeds = vec![];
+
+    for lits in forbidden.iter() {
+        for &lit in lits.iter() {
+            occurrences.grow_to(lit);
+            neighbours.grow_to(lit);
+
+            if occurrences[lit].is_empty() {
+                seeds.push(lit);
+            }
+
+            occurrences[lit].push(lits);
+            neighbours[lit].extend(lits.iter().copied().filter(|&other| other != lit));
+        }
+    }
+
+    for &lit in seeds.iter() {
+        neighbours[lit].sort();
+        neighbours[lit].dedup();
+    }
+
+    seeds.sort_by_key(|&lit| Reverse(occurrences[lit].len()));
+
+    let mut constraints = vec![];
+    let mut steps = 0;
+
+    for seed in seeds {
+        while steps < MAX_GROWTH_STEPS {
+            steps += occurrences[seed].len();
+            let mut candidates = occurrences[seed]
+                .iter()
+                .filter(|&&lits| !covered.contains(lits))
+                .flat_map(|lits| lits.iter().copied())
+                .filter(|&lit| lit != seed)
+                .collect::<Vec<_>>();
+            candidates.sort();
+            candidates.dedup();
+
+            if candidates.len() < bound + 1 {
+                break;
+            }
+
+            // As in greedy clique finding, the candidates which occur together with the most
+            // other candidates are tried first.
+            let mut scored_candidates = candidates
+                .iter()
+                .map(|&candidate| {
+                    let others = &neighbours[candidate];
+                    steps += others.len().min(candidates.len());
+
+                    let score = if others.len() < candidates.len() {
+                        others
+                            .iter()
+                            .filter(|lit| candidates.binary_search(lit).is_ok())
+                            .count()
+                    } else {
+                        candidates
+                            .iter()
+                            .filter(|lit| others.binary_search(lit).is_ok())
+                            .count()
+                    };
+
+                    (Reverse(score), candidate)
+                })
+                .collect::<Vec<_>>();
+            scored_candidates.sort();
+
+            let mut lits = vec![seed];
+            for (_, candidate) in scored_candidates {
+                // The constraint found so far is still used when the budget runs out.
+                if steps >= MAX_GROWTH_STEPS {
+                    break;
+                }
+
+                let is_neighbour = lits
+                    .iter()
+                    .all(|&lit| neighbours[lit].binary_search(&candidate).is_ok());
+
+                if is_neighbour
+                    && all_forbidden(forbidden, &lits, bound, &mut vec![candidate], &mut steps)
+                {
+                    lits.push(candidate);
+                }
+            }
+
+            let mut new_sets = vec![];
+            subsets(&lits, bound + 1, &mut vec![], &mut new_sets);
+            new_sets.retain(|lits| !covered.contains(lits));
+
+            // Smaller constraints are just a clause. A constraint which forbids no more new sets
+            // than it has literals is hardly more compact than its clauses, or is mostly implied
+            // by the other constraints.
+            if lits.len() < bound + 2 || new_sets.len() <= lits.len() {
+                break;
+            }
+
+            covered.extend(new_sets);
+            constraints.push(lits.into());
+        }
+    }
+
+    constraints
+}
+
+/// Whether every `count` of the literals are forbidden together with the literals of `set`. If
+/// there are fewer than `count` literals, this holds trivially. Every lookup of a set is counted
+/// in `steps`.
+fn all_forbidden(
+    forbidden: &BTreeSet<Box<[Lit]>>,
+    lits: &[Lit],
+    count: usize,
+    set: &mut Vec<Lit>,
+    steps: &mut usize,
+) -> bool {
+    if count == 0 {
+        *steps += 1;
+        let mut sorted = set.clone();
+        sorted.sort();
+        return forbidden.contains(sorted.as_slice());
+    }
+
+    (0..lits.len()).all(|idx| {
+        set.push(lits[idx]);
+        let is_forbidden = all_forbidden(forbidden, &lits[idx + 1..], count - 1, set, steps);
+        set.pop();
+        is_forbidden
+    })
+}
+
+/// Collect every `count` of the literals together with the literals of `set`, in increasing order.
+fn subsets(lits: &[Lit], count: usize, set: &mut Vec<Lit>, sets: &mut Vec<Box<[Lit]>>) {
+    if count == 0 {
+        let mut sorted = set.clone().into_boxed_slice();
+        sorted.sort();
+        sets.push(sorted);
+        return;
+    }
+
+    for idx in 0..lits.len() {
+        set.push(lits[idx]);
+        subsets(&lits[idx + 1..], count - 1, set, sets);
+        set.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use limiga_core::lit::Var;
+
+    use super::*;
+
+    fn lits(num_lits: u32) -> Vec<Lit> {
+        (0..num_lits)
+            .map(|code| Lit::positive(Var::try_from(code).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn pairwise_at_most_one_replaces_its_clauses() {
+        let x = lits(5);
+        let mut detector = CardinalityDetector::default();
+
+        for i in 0..x.len() {
+            for j in i + 1..x.len() {
+                assert!(detector.add_clause(&[!x[i], !x[j]]));
+            }
+        }
+
+        let (constraints, clauses) = detector.into_constraints();
+
+        assert_eq!(1, constraints.len());
+        assert_eq!(1, constraints[0].bound);
+        assert_eq!(x.as_slice(), &*constraints[0].lits);
+        assert!(clauses.is_empty());
+    }
+
+    #[test]
+    fn sequential_counter_at_most_one_is_not_used_when_it_replaces_no_clauses() {
+        // The literals `x` and the auxiliary literals `s`, where `s[i]` is true if one of the
+        // first `i + 1` literals of `x` is.
+        let all_lits = lits(15);
+        let (x, s) = all_lits.split_at(8);
+        let mut detector = CardinalityDetector::default();
+
+        for i in 0..s.len() {
+            detector.add_clause(&[!x[i], s[i]]);
+            detector.add_clause(&[!s[i], !x[i + 1]]);
+
+            if i + 1 < s.len() {
+                detector.add_clause(&[!s[i], s[i + 1]]);
+            }
+        }
+
+        let (constraints, clauses) = detector.into_constraints();
+
+        assert!(constraints.is_empty());
+        assert_eq!(20, clauses.len());
+    }
+}
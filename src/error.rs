@@ -7,4 +7,7 @@ pub enum LimigaError {
 
     #[error("failed to parse dimacs")]
     DimacsError(#[from] limiga_dimacs::DimacsParseError),
+
+    #[error("XOR constraints cannot be used when logging a proof")]
+    XorsInProof,
 }
@@ -1,23 +1,53 @@
 use std::{
-    collections::HashMap, fs::File, io::BufReader, path::Path, process::ExitCode, time::Duration,
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
 };
 
 use flatzinc_serde::FlatZinc;
 use limiga_constraints::{bool_lin_leq, linear_leq};
 use limiga_core::{
-    brancher::VsidsBrancher,
+    brancher::{Brancher, Sequence},
     domains::{DomainId, DomainStore, TypedDomainStore},
-    integer::{interval_domain::IntInterval, Int, IntEvent},
+    integer::{
+        brancher::{DomainBrancher, ValueSelection, VariableSelection},
+        interval_domain::IntInterval,
+        Int, IntEvent,
+    },
     lit::Lit,
+    proof::VeriPbProof,
     propagation::{DomainEvent, LitEvent, SDomainEvent},
+    restart::LubyRestarts,
     solver::{SolveResult, Solver},
     storage::{Indexer, StaticIndexer},
     termination::TimeBudget,
 };
+use serde_json::Value;
 
-use crate::termination::{OrTerminator, SignalTerminator};
+use crate::{
+    heuristic::Heuristic,
+    search::{BoolBrancher, PrioritySearch},
+    termination::{OrTerminator, SignalTerminator},
+};
 
-pub fn solve(path: impl AsRef<Path>, timeout: Option<Duration>) -> ExitCode {
+/// Solve the FlatZinc instance. If a proof path is given, a VeriPB proof is written to it. The
+/// pseudo-Boolean model the proof refers to is written to the same path with the extension `.opb`.
+///
+/// The search annotations of the solve item are followed until the annotated variables are fixed,
+/// after which the heuristic decides on the remaining variables. With free search the annotations
+/// are ignored.
+///
+/// The statistics of the search are printed after the solution, as `%%%mzn-stat` lines.
+pub fn solve(
+    path: impl AsRef<Path>,
+    timeout: Option<Duration>,
+    proof: Option<PathBuf>,
+    heuristic: Heuristic,
+    free_search: bool,
+) -> ExitCode {
     let path = path.as_ref();
 
     let Ok(open) = File::open(path) else {
@@ -26,8 +56,8 @@ pub fn solve(path: impl AsRef<Path>, timeout: Option<Duration>) -> ExitCode {
     };
 
     let reader = BufReader::new(open);
-    let fzn = match serde_json::from_reader::<_, FlatZinc>(reader) {
-        Ok(fzn) => fzn,
+    let (fzn, annotations) = match parse(reader) {
+        Ok(parsed) => parsed,
         Err(e) => {
             eprintln!("Failed to parse flatzinc.");
             eprintln!("{e}");
@@ -36,6 +66,18 @@ pub fn solve(path: impl AsRef<Path>, timeout: Option<Duration>) -> ExitCode {
     };
 
     let mut solver: Solver<TypedDomainStore<IntInterval>, SolverEvent> = Solver::default();
+    if let Some(proof_path) = proof {
+        // The model is logged while the variables and constraints are created.
+        let model_path = proof_path.with_extension("opb");
+        let (Ok(model_file), Ok(proof_file)) = (File::create(model_path), File::create(proof_path))
+        else {
+            eprintln!("Failed to create the proof files.");
+            return ExitCode::FAILURE;
+        };
+
+        solver.log_proof(VeriPbProof::new(model_file, proof_file));
+    }
+
     let variables = match create_variables(&fzn, &mut solver) {
         Ok(variables) => variables,
         Err(e) => {
@@ -51,15 +93,32 @@ pub fn solve(path: impl AsRef<Path>, timeout: Option<Duration>) -> ExitCode {
         return ExitCode::FAILURE;
     };
 
+    let search = if free_search {
+        None
+    } else {
+        match create_search(&fzn, &annotations, &variables, &mut solver) {
+            Ok(search) => search,
+            Err(e) => {
+                eprintln!("Ignoring the search annotations.");
+                eprintln!("{e}");
+                None
+            }
+        }
+    };
+
     let timer = timeout
         .map(TimeBudget::starting_now)
         .unwrap_or(TimeBudget::infinite());
 
     let signal_terminator = SignalTerminator::register();
     let terminator = OrTerminator::new(timer, signal_terminator);
-    let brancher = VsidsBrancher::new(0.95);
+    let mut brancher = match search {
+        Some(search) => Box::new(Sequence::new(search, heuristic.brancher())),
+        None => heuristic.brancher(),
+    };
+    let restart_policy = LubyRestarts::new(100);
 
-    match solver.solve(terminator, brancher) {
+    let exit_code = match solver.solve(terminator, &mut *brancher, restart_policy) {
         SolveResult::Satisfiable(solution) => {
             for (name, variable) in variables.iter() {
                 let value = match variable {
@@ -78,7 +137,7 @@ pub fn solve(path: impl AsRef<Path>, timeout: Option<Duration>) -> ExitCode {
             println!("----------");
             ExitCode::SUCCESS
         }
-        SolveResult::Unsatisfiable => {
+        SolveResult::Unsatisfiable | SolveResult::UnsatisfiableUnderAssumptions(_) => {
             println!("=====UNSATISFIABLE=====");
             ExitCode::SUCCESS
         }
@@ -86,7 +145,40 @@ pub fn solve(path: impl AsRef<Path>, timeout: Option<Duration>) -> ExitCode {
             println!("=====UNKNOWN=====");
             ExitCode::SUCCESS
         }
+    };
+
+    solver
+        .statistics()
+        .for_each(|name, value| println!("%%%mzn-stat: {name}={value}"));
+    println!("%%%mzn-stat-end");
+
+    if let Some(proof) = solver.take_proof() {
+        if let Err(e) = proof.finish() {
+            eprintln!("Failed to write the proof.");
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
     }
+
+    exit_code
+}
+
+/// Parse the FlatZinc instance. The annotations of the solve item are returned separately as JSON,
+/// since the parser does not support the nested annotations of search annotations.
+fn parse(reader: impl std::io::Read) -> anyhow::Result<(FlatZinc, Vec<Value>)> {
+    let mut json = serde_json::from_reader::<_, Value>(reader)?;
+
+    let annotations = match json
+        .get_mut("solve")
+        .and_then(Value::as_object_mut)
+        .and_then(|solve| solve.remove("ann"))
+    {
+        Some(Value::Array(annotations)) => annotations,
+        Some(other) => anyhow::bail!("expected an array of annotations, got {other}"),
+        None => vec![],
+    };
+
+    Ok((serde_json::from_value(json)?, annotations))
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -252,6 +344,190 @@ where
     Ok(())
 }
 
+/// Create the search given by a list of annotations, such as those of the solve item. The search
+/// annotations among them are followed one after the other, as in `seq_search`. Returns `None` if
+/// there are no search annotations.
+fn create_search<Domains>(
+    fzn: &FlatZinc,
+    annotations: &[Value],
+    variables: &VariableMap,
+    solver: &mut Solver<Domains, SolverEvent>,
+) -> anyhow::Result<Option<Box<dyn Brancher>>>
+where
+    Domains: DomainStore<IntInterval>,
+{
+    let searches = annotations
+        .iter()
+        .filter_map(|annotation| {
+            create_annotated_search(fzn, annotation, variables, solver).transpose()
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    Ok(searches
+        .into_iter()
+        .reduce(|first, second| Box::new(Sequence::new(first, second))))
+}
+
+/// Create the search for a single annotation. Returns `None` if it is not a search annotation.
+fn create_annotated_search<Domains>(
+    fzn: &FlatZinc,
+    annotation: &Value,
+    variables: &VariableMap,
+    solver: &mut Solver<Domains, SolverEvent>,
+) -> anyhow::Result<Option<Box<dyn Brancher>>>
+where
+    Domains: DomainStore<IntInterval>,
+{
+    // Atoms are plain strings, and do not describe a search.
+    let Some(id) = annotation.get("id").and_then(Value::as_str) else {
+        return Ok(None);
+    };
+
+    let args = annotation
+        .get("args")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let search: Box<dyn Brancher> = match (id, args) {
+        ("int_search", [vars, variable_selection, value_selection, _]) => {
+            let domains = resolve_search_int_variables(fzn, vars, variables)?
+                .into_iter()
+                .map(|domain| solver.domain(domain).clone())
+                .collect::<Vec<_>>();
+
+            Box::new(DomainBrancher::new(
+                domains,
+                parse_variable_selection(variable_selection)?,
+                parse_value_selection(value_selection)?,
+            ))
+        }
+
+        ("bool_search", [vars, variable_selection, value_selection, _]) => {
+            let lits = search_variable_names(fzn, vars)?
+                .into_iter()
+                .map(|name| {
+                    variables.resolve_bool_variable(name).ok_or_else(|| {
+                        anyhow::anyhow!("could not resolve bool variable for {name}")
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            for &lit in lits.iter() {
+                solver.freeze(lit);
+            }
+
+            Box::new(BoolBrancher::new(
+                lits,
+                parse_variable_selection(variable_selection)?,
+                parse_value_selection(value_selection)?,
+            ))
+        }
+
+        ("seq_search", [Value::Array(annotations)]) => {
+            return create_search(fzn, annotations, variables, solver);
+        }
+
+        ("priority_search", [controls, Value::Array(annotations), variable_selection, _]) => {
+            let controls = resolve_search_int_variables(fzn, controls, variables)?
+                .into_iter()
+                .map(|domain| solver.domain(domain).clone())
+                .collect::<Vec<_>>();
+
+            let searches = annotations
+                .iter()
+                .map(|annotation| {
+                    create_annotated_search(fzn, annotation, variables, solver)?.ok_or_else(|| {
+                        anyhow::anyhow!("expected a search annotation, got {annotation}")
+                    })
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            if controls.len() != searches.len() {
+                anyhow::bail!(
+                    "priority_search needs a search annotation for every control variable"
+                );
+            }
+
+            Box::new(PrioritySearch::new(
+                controls,
+                searches,
+                parse_variable_selection(variable_selection)?,
+            ))
+        }
+
+        ("int_search" | "bool_search" | "seq_search" | "priority_search", _) => {
+            anyhow::bail!("unexpected arguments for {id}")
+        }
+
+        _ => return Ok(None),
+    };
+
+    Ok(Some(search))
+}
+
+/// The names of the variables a search annotation branches on. They are given as an array, or as
+/// the name of an array. Constants in the array are skipped.
+fn search_variable_names<'a>(fzn: &'a FlatZinc, vars: &'a Value) -> anyhow::Result<Vec<&'a str>> {
+    match vars {
+        Value::String(identifier) => {
+            let array = fzn
+                .arrays
+                .get(identifier.as_str())
+                .ok_or_else(|| anyhow::anyhow!("no array for identifier '{identifier}'"))?;
+
+            Ok(array
+                .contents
+                .iter()
+                .filter_map(|literal| match literal {
+                    flatzinc_serde::Literal::Identifier(name) => Some(name.as_str()),
+                    _ => None,
+                })
+                .collect())
+        }
+
+        Value::Array(elements) => Ok(elements.iter().filter_map(Value::as_str).collect()),
+
+        other => anyhow::bail!("expected an array of variables, got {other}"),
+    }
+}
+
+fn resolve_search_int_variables(
+    fzn: &FlatZinc,
+    vars: &Value,
+    variables: &VariableMap,
+) -> anyhow::Result<Vec<DomainId<IntInterval>>> {
+    search_variable_names(fzn, vars)?
+        .into_iter()
+        .map(|name| {
+            variables
+                .resolve_int_variable(name)
+                .ok_or_else(|| anyhow::anyhow!("could not resolve integer variable for {name}"))
+        })
+        .collect()
+}
+
+fn parse_variable_selection(value: &Value) -> anyhow::Result<VariableSelection> {
+    match value.as_str() {
+        Some("input_order") => Ok(VariableSelection::InputOrder),
+        Some("first_fail") => Ok(VariableSelection::FirstFail),
+        Some("smallest") => Ok(VariableSelection::Smallest),
+        Some("largest") => Ok(VariableSelection::Largest),
+        Some("dom_w_deg") => Ok(VariableSelection::DomWDeg),
+        _ => anyhow::bail!("the variable selection {value} is not supported"),
+    }
+}
+
+fn parse_value_selection(value: &Value) -> anyhow::Result<ValueSelection> {
+    match value.as_str() {
+        Some("indomain" | "indomain_min") => Ok(ValueSelection::IndomainMin),
+        Some("indomain_max") => Ok(ValueSelection::IndomainMax),
+        Some("indomain_split") => Ok(ValueSelection::IndomainSplit),
+        Some("indomain_median") => Ok(ValueSelection::IndomainMedian),
+        _ => anyhow::bail!("the value selection {value} is not supported"),
+    }
+}
+
 struct VariableMap {
     map: HashMap<String, SolverVariable>,
 }
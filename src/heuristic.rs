@@ -0,0 +1,29 @@
+use clap::ValueEnum;
+use limiga_core::brancher::{Brancher, LrbBrancher, VmtfBrancher, VsidsBrancher};
+
+/// The decay of the VSIDS activities.
+const VSIDS_DECAY: f64 = 0.95;
+
+/// The decision heuristics the solver can use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Heuristic {
+    /// Variable state independent decaying sum: decide on the variable which occurred in the most
+    /// recent conflicts, weighted by how recent they are.
+    #[default]
+    Vsids,
+    /// Variable move to front: decide on the variable which most recently occurred in a conflict.
+    Vmtf,
+    /// Learning rate branching: decide on the variable which took part in the largest fraction of
+    /// the conflicts while it was assigned, weighted by how recent they are.
+    Lrb,
+}
+
+impl Heuristic {
+    pub fn brancher(self) -> Box<dyn Brancher> {
+        match self {
+            Heuristic::Vsids => Box::new(VsidsBrancher::new(VSIDS_DECAY)),
+            Heuristic::Vmtf => Box::new(VmtfBrancher::default()),
+            Heuristic::Lrb => Box::new(LrbBrancher::default()),
+        }
+    }
+}
@@ -1,28 +1,39 @@
 use std::{path::Path, process::ExitCode, time::Duration};
 
+mod cardinality;
 mod error;
 pub mod flatzinc;
+pub mod heuristic;
 pub mod sat;
+mod search;
 pub mod termination;
+mod xor;
 
-pub fn solve_cnf(path: impl AsRef<Path>, timeout: Option<Duration>) -> ExitCode {
-    match sat::run_solver(path, timeout) {
-        Ok(sat::Conclusion::Satisfiable(assignment)) => {
-            println!("s SATISFIABLE");
-            println!("v {}", assignment.value_line());
-            ExitCode::SUCCESS
-        }
-        Ok(sat::Conclusion::Unsatisfiable) => {
-            println!("s UNSATISFIABLE");
-            ExitCode::SUCCESS
-        }
-        Ok(sat::Conclusion::Unknown) => {
-            println!("s UNKNOWN");
-            ExitCode::SUCCESS
-        }
+pub fn solve_cnf(
+    path: impl AsRef<Path>,
+    timeout: Option<Duration>,
+    proof: Option<sat::ProofOutput>,
+    options: sat::SearchOptions,
+) -> ExitCode {
+    let (conclusion, statistics) = match sat::run_solver_with_options(path, timeout, proof, options)
+    {
+        Ok(result) => result,
         Err(e) => {
             eprintln!("Error: {e}");
-            ExitCode::FAILURE
+            return ExitCode::FAILURE;
         }
+    };
+
+    statistics.for_each(|name, value| println!("c {name}: {value}"));
+
+    match conclusion {
+        sat::Conclusion::Satisfiable(assignment) => {
+            println!("s SATISFIABLE");
+            println!("v {}", assignment.value_line());
+        }
+        sat::Conclusion::Unsatisfiable => println!("s UNSATISFIABLE"),
+        sat::Conclusion::Unknown => println!("s UNKNOWN"),
     }
+
+    ExitCode::SUCCESS
 }
@@ -3,6 +3,11 @@ mod error;
 use std::{io::Write, path::PathBuf, process::ExitCode, time::Duration};
 
 use clap::Parser;
+use limiga::{
+    heuristic::Heuristic,
+    sat::{ProofFormat, ProofOutput, SearchOptions},
+};
+use limiga_core::proof::ProofEncoding;
 
 #[derive(Parser)]
 struct Cli {
@@ -12,6 +17,33 @@ struct Cli {
     /// The timeout of the solver in seconds.
     #[arg(short, long)]
     timeout: Option<u64>,
+
+    /// The file to write a proof to. For CNF instances this is a DRAT proof. For FlatZinc instances
+    /// this is a VeriPB proof, and the pseudo-Boolean model it refers to is written to the same
+    /// path with the extension `.opb`.
+    #[arg(long)]
+    proof: Option<PathBuf>,
+
+    /// Write an LRAT proof instead of a DRAT proof. Only supported for CNF instances.
+    #[arg(long, requires = "proof")]
+    lrat: bool,
+
+    /// Write the proof in the binary format instead of the text format. Only supported for CNF
+    /// instances.
+    #[arg(long, requires = "proof")]
+    binary_proof: bool,
+
+    /// The decision heuristic of the solver.
+    #[arg(long, value_enum, default_value_t)]
+    heuristic: Heuristic,
+
+    /// Switch between a focused and a stable search mode. Only supported for CNF instances.
+    #[arg(long)]
+    mode_switching: bool,
+
+    /// Ignore the search annotations of FlatZinc instances, and only use the decision heuristic.
+    #[arg(short, long)]
+    free_search: bool,
 }
 
 fn main() -> ExitCode {
@@ -21,10 +53,39 @@ fn main() -> ExitCode {
 
     let cli = Cli::parse();
     let timeout = cli.timeout.map(Duration::from_secs);
+    let proof = cli.proof.map(|path| ProofOutput {
+        path,
+        format: if cli.lrat {
+            ProofFormat::Lrat
+        } else {
+            ProofFormat::Drat
+        },
+        encoding: if cli.binary_proof {
+            ProofEncoding::Binary
+        } else {
+            ProofEncoding::Text
+        },
+    });
 
     match cli.file.extension() {
-        Some(ext) if ext == "cnf" => limiga::solve_cnf(cli.file, timeout),
-        Some(ext) if ext == "json" => limiga::flatzinc::solve(&cli.file, timeout),
+        Some(ext) if ext == "cnf" => {
+            let options = SearchOptions {
+                heuristic: cli.heuristic,
+                mode_switching: cli.mode_switching,
+            };
+            limiga::solve_cnf(cli.file, timeout, proof, options)
+        }
+        Some(ext) if ext == "json" && (cli.lrat || cli.binary_proof) => {
+            eprintln!("Only text VeriPB proofs are supported for FlatZinc instances.");
+            ExitCode::FAILURE
+        }
+        Some(ext) if ext == "json" => limiga::flatzinc::solve(
+            &cli.file,
+            timeout,
+            proof.map(|output| output.path),
+            cli.heuristic,
+            cli.free_search,
+        ),
 
         Some(_) | None => {
             eprintln!(
@@ -2,22 +2,31 @@ use std::{
     fmt::{Debug, Write},
     fs::File,
     num::NonZeroI32,
-    path::Path,
+    path::{Path, PathBuf},
     time::Duration,
 };
 
 use crate::{
+    cardinality::CardinalityDetector,
     error::LimigaError,
+    heuristic::Heuristic,
     termination::{OrTerminator, SignalTerminator},
+    xor::XorDetector,
 };
 use limiga_core::{
-    brancher::VsidsBrancher,
     lit::{Lit, Var},
+    proof::{DratProof, LratProof, ProofEncoding},
+    propagation::LitEvent,
+    restart::GlucoseRestarts,
     solver::{Solution, SolveResult, Solver},
+    statistics::SolverStatistics,
     storage::StaticIndexer,
     termination::TimeBudget,
 };
-use limiga_dimacs::DimacsSink;
+use limiga_dimacs::{DimacsSink, NewVariables};
+
+/// The number of conflicts before the search first switches from focused to stable mode.
+const MODE_SWITCH_INTERVAL: u64 = 1000;
 
 pub struct Assignment {
     values: Box<[bool]>,
@@ -29,10 +38,50 @@ pub enum Conclusion {
     Unknown,
 }
 
+/// Where to write a proof for the instance, and in which format.
+pub struct ProofOutput {
+    pub path: PathBuf,
+    pub format: ProofFormat,
+    pub encoding: ProofEncoding,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofFormat {
+    Drat,
+    Lrat,
+}
+
+/// How the solver searches for a solution.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchOptions {
+    pub heuristic: Heuristic,
+    /// Whether the search switches between focused and stable mode.
+    pub mode_switching: bool,
+}
+
+/// Solve the CNF instance at the given path. Along with the conclusion, the statistics of the
+/// search are returned.
+///
+/// XOR constraints in the instance, either given on `x` lines or encoded as clauses, are
+/// propagated by Gauss-Jordan elimination. Cardinality constraints which are encoded as clauses
+/// are propagated by counting the true literals. Since that cannot be justified in a DRAT or LRAT
+/// proof, these constraints are not detected when a proof is logged, and `x` lines are rejected.
 pub fn run_solver(
     path: impl AsRef<Path>,
     timeout: Option<Duration>,
-) -> Result<Conclusion, LimigaError> {
+    proof: Option<ProofOutput>,
+) -> Result<(Conclusion, SolverStatistics), LimigaError> {
+    run_solver_with_options(path, timeout, proof, SearchOptions::default())
+}
+
+/// Solve the CNF instance at the given path like [`run_solver`], but search as configured by the
+/// given options.
+pub fn run_solver_with_options(
+    path: impl AsRef<Path>,
+    timeout: Option<Duration>,
+    proof: Option<ProofOutput>,
+    options: SearchOptions,
+) -> Result<(Conclusion, SolverStatistics), LimigaError> {
     let file = File::open(path)?;
     let timer = timeout
         .map(TimeBudget::starting_now)
@@ -41,7 +90,19 @@ pub fn run_solver(
     let signal_terminator = SignalTerminator::register();
     let terminator = OrTerminator::new(timer, signal_terminator);
 
-    let mut solver: Solver<(), ()> = Solver::default();
+    let mut solver: Solver<(), LitEvent> = Solver::default();
+    let logs_proof = proof.is_some();
+    if let Some(output) = proof {
+        // The proof has to be logged while the clauses are added, as the formula may already be
+        // found to be unsatisfiable during parsing.
+        let proof_file = File::create(output.path)?;
+
+        match output.format {
+            ProofFormat::Drat => solver.log_proof(DratProof::new(proof_file, output.encoding)),
+            ProofFormat::Lrat => solver.log_proof(LratProof::new(proof_file, output.encoding)),
+        }
+    }
+
     let mut sink = limiga_dimacs::parse_cnf(file, |header| {
         let vars = solver
             .new_lits()
@@ -50,15 +111,62 @@ pub fn run_solver(
             .collect::<Vec<_>>()
             .into();
 
-        SolverSink { solver, vars }
+        SolverSink {
+            solver,
+            vars,
+            xors: vec![],
+            xor_detector: (!logs_proof).then(XorDetector::default),
+            cardinality_detector: (!logs_proof).then(CardinalityDetector::default),
+        }
     })?;
 
-    let brancher = VsidsBrancher::new(0.95);
-    match sink.solver.solve(terminator, brancher) {
-        SolveResult::Satisfiable(solution) => Ok(Conclusion::Satisfiable(solution.into())),
-        SolveResult::Unsatisfiable => Ok(Conclusion::Unsatisfiable),
-        SolveResult::Unknown => Ok(Conclusion::Unknown),
+    if logs_proof && !sink.xors.is_empty() {
+        return Err(LimigaError::XorsInProof);
+    }
+
+    let mut xors = std::mem::take(&mut sink.xors);
+    if let Some(detector) = sink.xor_detector.take() {
+        xors.extend(detector.into_xors());
+    }
+
+    if let Some(detector) = sink.cardinality_detector.take() {
+        let (constraints, clauses) = detector.into_constraints();
+
+        for clause in clauses {
+            sink.solver.add_clause(clause.iter().copied());
+        }
+
+        for constraint in constraints {
+            limiga_constraints::at_most(&mut sink.solver, constraint.lits, constraint.bound);
+        }
+    }
+
+    if !xors.is_empty() {
+        limiga_constraints::xors(&mut sink.solver, xors);
     }
+
+    if options.mode_switching {
+        sink.solver.use_mode_switching(MODE_SWITCH_INTERVAL);
+    }
+
+    let mut brancher = options.heuristic.brancher();
+    let restart_policy = GlucoseRestarts::default();
+    let conclusion = match sink
+        .solver
+        .solve(terminator, &mut *brancher, restart_policy)
+    {
+        SolveResult::Satisfiable(solution) => Conclusion::Satisfiable(solution.into()),
+        SolveResult::Unsatisfiable | SolveResult::UnsatisfiableUnderAssumptions(_) => {
+            Conclusion::Unsatisfiable
+        }
+        SolveResult::Unknown => Conclusion::Unknown,
+    };
+
+    if let Some(proof) = sink.solver.take_proof() {
+        proof.finish()?;
+    }
+
+    Ok((conclusion, sink.solver.statistics().clone()))
 }
 
 impl Assignment {
@@ -105,15 +213,18 @@ impl<'a, Domains> From<Solution<'a, Domains>> for Assignment {
 struct SolverSink<Domains, Event> {
     solver: Solver<Domains, Event>,
     vars: Box<[Var]>,
+    /// The XOR constraints of the `x` lines.
+    xors: Vec<Box<[Lit]>>,
+    /// Finds the XOR constraints which are encoded by the clauses, if they are used.
+    xor_detector: Option<XorDetector>,
+    /// Finds the cardinality constraints which are encoded by the clauses, if they are used. It
+    /// holds back the clauses which may be replaced.
+    cardinality_detector: Option<CardinalityDetector>,
 }
 
-impl<Domains, Event> DimacsSink for SolverSink<Domains, Event>
-where
-    Event: Copy + Debug + StaticIndexer,
-{
-    fn add_clause(&mut self, lits: &[std::num::NonZeroI32]) {
-        let lits = lits
-            .iter()
+impl<Domains, Event> SolverSink<Domains, Event> {
+    fn to_lits(&self, lits: &[NonZeroI32]) -> Vec<Lit> {
+        lits.iter()
             .map(|lit| {
                 let idx = lit.get().unsigned_abs() as usize - 1;
 
@@ -123,8 +234,32 @@ where
                     Lit::negative(self.vars[idx])
                 }
             })
-            .collect::<Vec<_>>();
+            .collect()
+    }
+}
+
+impl<Domains, Event> DimacsSink for SolverSink<Domains, Event>
+where
+    Event: Copy + Debug + StaticIndexer,
+{
+    fn add_clause(&mut self, lits: &[NonZeroI32]) {
+        let lits = self.to_lits(lits);
+
+        if let Some(detector) = self.xor_detector.as_mut() {
+            detector.add_clause(&lits);
+        }
+
+        if let Some(detector) = self.cardinality_detector.as_mut() {
+            if detector.add_clause(&lits) {
+                return;
+            }
+        }
 
         self.solver.add_clause(lits);
     }
+
+    fn add_xor(&mut self, lits: &[NonZeroI32], _: &mut NewVariables) {
+        let lits = self.to_lits(lits);
+        self.xors.push(lits.into());
+    }
 }
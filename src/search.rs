@@ -0,0 +1,207 @@
+use limiga_core::{
+    assignment::Assignment,
+    brancher::Brancher,
+    integer::{
+        brancher::{ConflictWeights, ValueSelection, VariableSelection},
+        BoundedInt,
+    },
+    lit::{Lit, Var},
+    mode::SearchMode,
+    trail::Trail,
+};
+
+/// Branches on Boolean variables, as selected by `bool_search`. A Boolean variable is treated as an
+/// integer with the domain `{0, 1}`.
+pub struct BoolBrancher {
+    lits: Box<[Lit]>,
+    variable_selection: VariableSelection,
+    /// The value the selected variable is assigned.
+    value: bool,
+    weights: ConflictWeights,
+}
+
+impl BoolBrancher {
+    pub fn new(
+        lits: impl IntoIterator<Item = Lit>,
+        variable_selection: VariableSelection,
+        value_selection: ValueSelection,
+    ) -> Self {
+        let lits = lits.into_iter().collect::<Box<[_]>>();
+        let weights = ConflictWeights::new(lits.iter().map(|lit| [lit.var()]));
+
+        BoolBrancher {
+            lits,
+            variable_selection,
+            // The middle of `{0, 1}` is rounded down.
+            value: value_selection == ValueSelection::IndomainMax,
+            weights,
+        }
+    }
+}
+
+impl Brancher for BoolBrancher {
+    fn initialize(&mut self, largest_var: Var) {
+        self.weights.grow_to(largest_var);
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        self.weights.on_variable_activated(var);
+    }
+
+    fn on_conflict(&mut self) {
+        self.weights.on_conflict();
+    }
+
+    fn on_variable_unassigned(&mut self, _: Lit) {}
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        // The unassigned variables all have the same bounds, so only their weights tell them
+        // apart.
+        let mut selected: Option<(usize, f64)> = None;
+        for (idx, &lit) in self.lits.iter().enumerate() {
+            if !assignment.is_unassigned(lit) {
+                continue;
+            }
+
+            let score = self
+                .variable_selection
+                .score(0, 1, self.weights.weight(idx));
+            if selected.is_none_or(|(_, best)| score < best) {
+                selected = Some((idx, score));
+            }
+        }
+
+        let (idx, _) = selected?;
+        let lit = self.lits[idx];
+        Some(if self.value { lit } else { !lit })
+    }
+}
+
+/// The search of `priority_search`. Every search belongs to a control variable, and the search to
+/// follow is the first one with a decision when they are ordered by their control variables. The
+/// searches of fixed control variables are skipped. The selected search is followed until it has
+/// no decision left or the solver backtracks.
+pub struct PrioritySearch<Dom> {
+    controls: Box<[Dom]>,
+    searches: Box<[Box<dyn Brancher>]>,
+    variable_selection: VariableSelection,
+    weights: ConflictWeights,
+    /// The search which took the previous decision.
+    current: Option<usize>,
+}
+
+impl<Dom: BoundedInt> PrioritySearch<Dom> {
+    pub fn new(
+        controls: impl IntoIterator<Item = Dom>,
+        searches: impl IntoIterator<Item = Box<dyn Brancher>>,
+        variable_selection: VariableSelection,
+    ) -> Self {
+        let controls = controls.into_iter().collect::<Box<[_]>>();
+        let weights = ConflictWeights::new(
+            controls
+                .iter()
+                .map(|control| control.order_encoding().1.iter().map(|lit| lit.var())),
+        );
+
+        PrioritySearch {
+            controls,
+            searches: searches.into_iter().collect(),
+            variable_selection,
+            weights,
+            current: None,
+        }
+    }
+}
+
+impl<Dom: BoundedInt> Brancher for PrioritySearch<Dom> {
+    fn initialize(&mut self, largest_var: Var) {
+        self.weights.grow_to(largest_var);
+
+        for search in self.searches.iter_mut() {
+            search.initialize(largest_var);
+        }
+    }
+
+    fn on_variable_activated(&mut self, var: Var) {
+        self.weights.on_variable_activated(var);
+
+        for search in self.searches.iter_mut() {
+            search.on_variable_activated(var);
+        }
+    }
+
+    fn on_conflict(&mut self) {
+        self.weights.on_conflict();
+
+        for search in self.searches.iter_mut() {
+            search.on_conflict();
+        }
+    }
+
+    fn on_backtrack(&mut self, trail: &Trail) {
+        self.current = None;
+
+        for search in self.searches.iter_mut() {
+            search.on_backtrack(trail);
+        }
+    }
+
+    fn on_variable_assigned(&mut self, lit: Lit) {
+        for search in self.searches.iter_mut() {
+            search.on_variable_assigned(lit);
+        }
+    }
+
+    fn on_variable_unassigned(&mut self, lit: Lit) {
+        for search in self.searches.iter_mut() {
+            search.on_variable_unassigned(lit);
+        }
+    }
+
+    fn on_restart(&mut self) {
+        for search in self.searches.iter_mut() {
+            search.on_restart();
+        }
+    }
+
+    fn on_mode_switch(&mut self, mode: SearchMode) {
+        for search in self.searches.iter_mut() {
+            search.on_mode_switch(mode);
+        }
+    }
+
+    fn next_decision(&mut self, assignment: &Assignment) -> Option<Lit> {
+        if let Some(current) = self.current {
+            if let Some(decision) = self.searches[current].next_decision(assignment) {
+                return Some(decision);
+            }
+        }
+
+        let mut scored = self
+            .controls
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, control)| {
+                let (min, max) = (control.min(assignment), control.max(assignment));
+                let score = self
+                    .variable_selection
+                    .score(min, max, self.weights.weight(idx));
+
+                (min < max).then_some((idx, score))
+            })
+            .collect::<Vec<_>>();
+
+        // The sort is stable, so ties are broken by the order of the searches.
+        scored.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        self.current = None;
+        for (idx, _) in scored {
+            if let Some(decision) = self.searches[idx].next_decision(assignment) {
+                self.current = Some(idx);
+                return Some(decision);
+            }
+        }
+
+        None
+    }
+}
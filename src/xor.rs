@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+use limiga_core::lit::{Lit, Var};
+
+/// The number of variables of the shortest XOR constraint which is detected. Shorter XOR
+/// constraints are propagated well enough by their clauses.
+const MIN_XOR_LEN: usize = 3;
+/// The number of variables of the longest XOR constraint which is detected. An XOR constraint
+/// over `n` variables is encoded by `2^(n - 1)` clauses.
+const MAX_XOR_LEN: usize = 6;
+/// The XOR constraints are only used if their clauses make up at least this fraction of the
+/// clauses. Otherwise propagating them is mostly overhead, and the propagator keeps the formula
+/// from being simplified.
+const MIN_XOR_CLAUSE_FRACTION: f64 = 0.5;
+
+/// Finds the XOR constraints which are encoded as clauses. An XOR constraint over `n` variables
+/// is encoded by the `2^(n - 1)` clauses over exactly those variables which exclude the
+/// assignments with the wrong parity.
+#[derive(Default)]
+pub struct XorDetector {
+    /// For every set of variables, in increasing order, the clauses over exactly those variables.
+    /// A clause is stored as the bitset of the positions of its negative literals.
+    clauses: BTreeMap<Box<[u32]>, Vec<u32>>,
+    /// The number of clauses which were added, including the ones which are not stored.
+    num_clauses: usize,
+}
+
+impl XorDetector {
+    pub fn add_clause(&mut self, lits: &[Lit]) {
+        self.num_clauses += 1;
+
+        if !(MIN_XOR_LEN..=MAX_XOR_LEN).contains(&lits.len()) {
+            return;
+        }
+
+        let mut lits = lits.to_vec();
+        lits.sort_by_key(|lit| lit.var());
+
+        // Clauses with a repeated variable do not take part in an encoding.
+        if lits.windows(2).any(|pair| pair[0].var() == pair[1].var()) {
+            return;
+        }
+
+        let vars = lits.iter().map(|lit| lit.var().code()).collect();
+        let negative_lits = lits
+            .iter()
+            .enumerate()
+            .filter(|(_, lit)| !lit.is_positive())
+            .fold(0, |bits, (idx, _)| bits | 1 << idx);
+
+        self.clauses.entry(vars).or_default().push(negative_lits);
+    }
+
+    /// The XOR constraints of which all clauses were added. Every constraint states that an odd
+    /// number of its literals is true. If the XOR constraints are only a small part of the
+    /// formula, none are returned.
+    pub fn into_xors(self) -> Vec<Box<[Lit]>> {
+        let mut xors = vec![];
+        let mut num_xor_clauses = 0;
+
+        for (vars, mut clauses) in self.clauses {
+            clauses.sort_unstable();
+            clauses.dedup();
+
+            // A clause excludes the assignment which makes its negative literals true and its
+            // positive literals false. The parity of that assignment is the parity of the number
+            // of negative literals.
+            for excluded_parity in [0, 1] {
+                let num_clauses = clauses
+                    .iter()
+                    .filter(|clause| clause.count_ones() % 2 == excluded_parity)
+                    .count();
+
+                if num_clauses < 1 << (vars.len() - 1) {
+                    continue;
+                }
+
+                let mut xor = vars
+                    .iter()
+                    .map(|&code| Lit::positive(Var::try_from(code).expect("valid variable")))
+                    .collect::<Box<[_]>>();
+
+                // If the assignments with an odd parity are excluded, an even number of the
+                // variables is true, so an odd number of the literals is true after negating one
+                // of them.
+                if excluded_parity == 1 {
+                    xor[0] = !xor[0];
+                }
+
+                xors.push(xor);
+                num_xor_clauses += 1 << (vars.len() - 1);
+            }
+        }
+
+        if (num_xor_clauses as f64) < MIN_XOR_CLAUSE_FRACTION * self.num_clauses as f64 {
+            return vec![];
+        }
+
+        xors
+    }
+}
@@ -0,0 +1,66 @@
+use std::{fs::File, num::NonZeroI32, path::PathBuf, time::Duration};
+
+use limiga::sat::Conclusion;
+use limiga_dimacs::DimacsSink;
+
+const TEST_TIME_BUDGET: Duration = Duration::from_secs(30);
+
+fn instance_path(name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}/instances/cardinality/{name}.cnf",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+}
+
+fn run(name: &str) -> Conclusion {
+    limiga::sat::run_solver(instance_path(name), Some(TEST_TIME_BUDGET), None)
+        .expect("failed to run solver")
+        .0
+}
+
+/// Check the assignment against the clauses of the instance, including the clauses which were
+/// replaced by a cardinality constraint.
+fn assert_satisfied(name: &str, assignment: limiga::sat::Assignment) {
+    let file = File::open(instance_path(name)).expect("could not open instance file");
+    let clauses = limiga_dimacs::parse_cnf(file, |_| Clauses::default()).expect("valid dimacs");
+
+    for clause in clauses.0.iter() {
+        assert!(
+            clause.iter().any(|&lit| assignment.value(lit)),
+            "unsatisfied clause {clause:?}"
+        );
+    }
+}
+
+#[derive(Default)]
+struct Clauses(Vec<Box<[NonZeroI32]>>);
+
+impl DimacsSink for Clauses {
+    fn add_clause(&mut self, clause: &[NonZeroI32]) {
+        self.0.push(clause.into());
+    }
+}
+
+#[test]
+fn pairwise_at_most_one_constraints_are_solved() {
+    match run("queens-pairwise") {
+        Conclusion::Satisfiable(assignment) => assert_satisfied("queens-pairwise", assignment),
+        _ => panic!("expected a solution"),
+    }
+}
+
+#[test]
+fn sequential_counter_at_most_one_constraints_are_refuted() {
+    assert!(matches!(
+        run("pigeons-sequential"),
+        Conclusion::Unsatisfiable
+    ));
+}
+
+#[test]
+fn binomial_at_most_two_constraints_are_solved() {
+    match run("at-most-two-binomial") {
+        Conclusion::Satisfiable(assignment) => assert_satisfied("at-most-two-binomial", assignment),
+        _ => panic!("expected a solution"),
+    }
+}
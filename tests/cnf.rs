@@ -1,7 +1,18 @@
-use std::{fs::File, num::NonZeroI32, path::PathBuf, time::Duration};
+use std::{
+    collections::HashSet,
+    fs::File,
+    num::NonZeroI32,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
 
-use limiga::sat::Conclusion;
-use limiga_dimacs::DimacsSink;
+use limiga::{
+    heuristic::Heuristic,
+    sat::{Conclusion, ProofFormat, ProofOutput, SearchOptions},
+};
+use limiga_core::proof::ProofEncoding;
+use limiga_dimacs::{DimacsSink, NewVariables};
 
 const TEST_TIME_BUDGET: Duration = Duration::from_secs(30);
 
@@ -9,17 +20,15 @@ macro_rules! cnf_instance {
     ($name:ident) => {
         #[test]
         fn $name() {
-            let file = PathBuf::from(format!(
-                "{}/instances/cnf/{}.cnf",
-                env!("CARGO_MANIFEST_DIR"),
-                stringify!($name),
-            ));
+            let file = instance_path(stringify!($name));
 
-            let result = limiga::sat::run_solver(&file, Some(TEST_TIME_BUDGET))
-                .expect("failed to run solver");
+            let checker = ProofChecker::from_env();
+            let format = checker.map_or(ProofFormat::Lrat, ProofChecker::format);
+            let proof = proof_path(stringify!($name), format);
+            let result = run_with_proof(&file, &proof, format);
 
             let instance = limiga_dimacs::parse_cnf(
-                File::open(file).expect("could not open instance file for checking"),
+                File::open(&file).expect("could not open instance file for checking"),
                 |_| SatInstance::default(),
             )
             .expect("valid dimacs");
@@ -27,8 +36,17 @@ macro_rules! cnf_instance {
             match result {
                 Conclusion::Satisfiable(assignment) => instance.assert_satisfied(assignment),
                 Conclusion::Unsatisfiable => {
-                    // TODO: Verify unsat through certificates when we have enabled proof logging.
-                    // For now these show up as passing.
+                    if format == ProofFormat::Lrat {
+                        assert_hints_are_live(instance.clauses.len(), &proof);
+                    }
+
+                    match checker {
+                        Some(checker) => checker.assert_verified(&file, &proof),
+                        None => eprintln!(
+                            "skipped checking the proof of {}: PROOF_CHECKER is not set",
+                            stringify!($name)
+                        ),
+                    }
                 }
                 Conclusion::Unknown => panic!(
                     "instance timed out after {} seconds",
@@ -39,6 +57,263 @@ macro_rules! cnf_instance {
     };
 }
 
+/// The external checker for the proofs of unsatisfiable instances, picked with the environment
+/// variable `PROOF_CHECKER`. The checker has to be on the `PATH`. Without it, the proofs are still
+/// logged as LRAT but only their hints are checked, and the skipped check is reported on stderr.
+#[derive(Clone, Copy)]
+enum ProofChecker {
+    /// `PROOF_CHECKER=drat-trim`, which checks DRAT proofs.
+    DratTrim,
+    /// `PROOF_CHECKER=cake_lpr`, which checks LRAT proofs.
+    CakeLpr,
+}
+
+impl ProofChecker {
+    fn from_env() -> Option<ProofChecker> {
+        match std::env::var("PROOF_CHECKER").ok()?.as_str() {
+            "drat-trim" => Some(ProofChecker::DratTrim),
+            "cake_lpr" => Some(ProofChecker::CakeLpr),
+            checker => panic!("unknown proof checker {checker}"),
+        }
+    }
+
+    fn command(self) -> &'static str {
+        match self {
+            ProofChecker::DratTrim => "drat-trim",
+            ProofChecker::CakeLpr => "cake_lpr",
+        }
+    }
+
+    fn format(self) -> ProofFormat {
+        match self {
+            ProofChecker::DratTrim => ProofFormat::Drat,
+            ProofChecker::CakeLpr => ProofFormat::Lrat,
+        }
+    }
+
+    /// Whether the checker can be run.
+    fn is_installed(self) -> bool {
+        Command::new(self.command())
+            .stdin(Stdio::null())
+            .output()
+            .is_ok()
+    }
+
+    /// Check that the proof at the given path refutes the instance.
+    fn assert_verified(self, instance: &Path, proof: &Path) {
+        let output = Command::new(self.command())
+            .arg(instance)
+            .arg(proof)
+            .output()
+            .unwrap_or_else(|_| panic!("could not run {}", self.command()));
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.lines().any(|line| line.starts_with("s VERIFIED")),
+            "the proof was rejected:\n{stdout}"
+        );
+    }
+}
+
+/// Check that every hint of a text LRAT proof refers to a clause which exists at that step: a
+/// clause of the formula, whose IDs are 1 up to the number of clauses, or a clause which was added
+/// before. Neither may have been deleted. Also checks that only existing clauses are deleted, and
+/// that the IDs of added clauses are not in use.
+fn assert_hints_are_live(num_clauses: usize, proof: &Path) {
+    let proof = std::fs::read_to_string(proof).expect("could not read the proof");
+    let mut live = (1..=num_clauses as u64).collect::<HashSet<_>>();
+
+    for step in proof.lines() {
+        let mut numbers = step.split_whitespace();
+        let id = numbers.next().expect("every step starts with an ID");
+
+        if numbers.clone().next() == Some("d") {
+            for deleted in numbers.skip(1).take_while(|&number| number != "0") {
+                let deleted = deleted.parse::<u64>().expect("clause ID");
+                assert!(
+                    live.remove(&deleted),
+                    "deleted clause {deleted} does not exist"
+                );
+            }
+
+            continue;
+        }
+
+        let id = id.parse::<u64>().expect("clause ID");
+        let hints = numbers
+            .skip_while(|&number| number != "0")
+            .skip(1)
+            .take_while(|&number| number != "0");
+
+        for hint in hints {
+            let hint = hint.parse::<u64>().expect("hint of a RUP step");
+            assert!(
+                live.contains(&hint),
+                "clause {id} has the hint {hint}, which does not exist"
+            );
+        }
+
+        assert!(
+            live.insert(id),
+            "clause {id} is added while its ID is in use"
+        );
+    }
+}
+
+fn instance_path(name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}/instances/cnf/{name}.cnf",
+        env!("CARGO_MANIFEST_DIR"),
+    ))
+}
+
+fn proof_path(instance: &str, format: ProofFormat) -> PathBuf {
+    let extension = match format {
+        ProofFormat::Drat => "drat",
+        ProofFormat::Lrat => "lrat",
+    };
+
+    PathBuf::from(format!(
+        "{}/{instance}.{extension}",
+        env!("CARGO_TARGET_TMPDIR")
+    ))
+}
+
+fn run_with_proof(file: &Path, proof: &Path, format: ProofFormat) -> Conclusion {
+    run_with_encoded_proof(file, proof, format, ProofEncoding::Text)
+}
+
+fn run_with_encoded_proof(
+    file: &Path,
+    proof: &Path,
+    format: ProofFormat,
+    encoding: ProofEncoding,
+) -> Conclusion {
+    limiga::sat::run_solver(
+        file,
+        Some(TEST_TIME_BUDGET),
+        Some(ProofOutput {
+            path: proof.to_path_buf(),
+            format,
+            encoding,
+        }),
+    )
+    .expect("failed to run solver")
+    .0
+}
+
+fn run_with_heuristic(name: &str, heuristic: Heuristic) -> (Conclusion, SatInstance) {
+    let file = instance_path(name);
+
+    let options = SearchOptions {
+        heuristic,
+        ..SearchOptions::default()
+    };
+    let conclusion =
+        limiga::sat::run_solver_with_options(&file, Some(TEST_TIME_BUDGET), None, options)
+            .expect("failed to run solver")
+            .0;
+
+    let instance = limiga_dimacs::parse_cnf(
+        File::open(&file).expect("could not open instance file for checking"),
+        |_| SatInstance::default(),
+    )
+    .expect("valid dimacs");
+
+    (conclusion, instance)
+}
+
+/// Runs drat-trim on the text and binary DRAT proofs of some unsatisfiable instances, regardless
+/// of `PROOF_CHECKER`. Run it with `cargo test -- --ignored`.
+#[test]
+#[ignore = "needs drat-trim on the PATH"]
+fn drat_proofs_are_verified_by_drat_trim() {
+    let checker = ProofChecker::DratTrim;
+    if !checker.is_installed() {
+        eprintln!("skipped checking DRAT proofs: drat-trim is not on the PATH");
+        return;
+    }
+
+    for name in ["full5", "ph5", "add32"] {
+        for encoding in [ProofEncoding::Text, ProofEncoding::Binary] {
+            let file = instance_path(name);
+            let proof = PathBuf::from(format!(
+                "{}/{name}-{encoding:?}.drat",
+                env!("CARGO_TARGET_TMPDIR")
+            ));
+
+            let conclusion = run_with_encoded_proof(&file, &proof, ProofFormat::Drat, encoding);
+            assert!(matches!(conclusion, Conclusion::Unsatisfiable));
+
+            checker.assert_verified(&file, &proof);
+        }
+    }
+}
+
+#[test]
+fn vmtf_solves_satisfiable_instances() {
+    for name in ["prime4", "factor2708413pos", "sqrt10201"] {
+        match run_with_heuristic(name, Heuristic::Vmtf) {
+            (Conclusion::Satisfiable(assignment), instance) => {
+                instance.assert_satisfied(assignment)
+            }
+            _ => panic!("expected a solution for {name}"),
+        }
+    }
+}
+
+#[test]
+fn vmtf_refutes_unsatisfiable_instances() {
+    for name in ["full5", "ph5", "add32"] {
+        let (conclusion, _) = run_with_heuristic(name, Heuristic::Vmtf);
+        assert!(
+            matches!(conclusion, Conclusion::Unsatisfiable),
+            "expected {name} to be refuted"
+        );
+    }
+}
+
+#[test]
+fn lrb_solves_satisfiable_instances() {
+    for name in ["prime4", "factor2708413pos", "sqrt10201"] {
+        match run_with_heuristic(name, Heuristic::Lrb) {
+            (Conclusion::Satisfiable(assignment), instance) => {
+                instance.assert_satisfied(assignment)
+            }
+            _ => panic!("expected a solution for {name}"),
+        }
+    }
+}
+
+#[test]
+fn lrb_refutes_unsatisfiable_instances() {
+    for name in ["full5", "ph5", "add32"] {
+        let (conclusion, _) = run_with_heuristic(name, Heuristic::Lrb);
+        assert!(
+            matches!(conclusion, Conclusion::Unsatisfiable),
+            "expected {name} to be refuted"
+        );
+    }
+}
+
+#[test]
+fn mode_switching_is_only_used_when_enabled() {
+    let file = instance_path("add128");
+
+    for mode_switching in [false, true] {
+        let options = SearchOptions {
+            mode_switching,
+            ..SearchOptions::default()
+        };
+        let (conclusion, statistics) =
+            limiga::sat::run_solver_with_options(&file, Some(TEST_TIME_BUDGET), None, options)
+                .expect("failed to run solver");
+
+        assert!(matches!(conclusion, Conclusion::Unsatisfiable));
+        assert_eq!(mode_switching, statistics.mode_switches > 0);
+    }
+}
+
 cnf_instance!(add4);
 cnf_instance!(add8);
 cnf_instance!(add16);
@@ -125,15 +400,21 @@ cnf_instance!(unit5);
 cnf_instance!(unit6);
 cnf_instance!(unit7);
 
+/// The clauses and XOR constraints of an instance, to check solutions against.
 #[derive(Default)]
 struct SatInstance {
     clauses: Vec<Box<[NonZeroI32]>>,
+    xors: Vec<Box<[NonZeroI32]>>,
 }
 
 impl DimacsSink for SatInstance {
     fn add_clause(&mut self, clause: &[NonZeroI32]) {
         self.clauses.push(clause.into());
     }
+
+    fn add_xor(&mut self, lits: &[NonZeroI32], _: &mut NewVariables) {
+        self.xors.push(lits.into());
+    }
 }
 
 impl SatInstance {
@@ -143,5 +424,11 @@ impl SatInstance {
                 panic!("unsatisfied clause");
             }
         }
+
+        for xor in &self.xors {
+            if xor.iter().filter(|&&lit| assignment.value(lit)).count() % 2 == 0 {
+                panic!("unsatisfied xor");
+            }
+        }
     }
 }
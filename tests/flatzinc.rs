@@ -0,0 +1,140 @@
+use std::{collections::HashMap, path::PathBuf, process::Command};
+
+/// Solve a model of `x1 + x2 + x3 <= 5` with the given solve annotations, and get the values of
+/// the variables in the solution.
+fn solve(name: &str, annotations: &str, args: &[&str]) -> HashMap<String, i64> {
+    let model = format!(
+        r#"{{
+            "variables": {{
+                "x1": {{ "type": "int", "domain": [[1, 3]] }},
+                "x2": {{ "type": "int", "domain": [[1, 3]] }},
+                "x3": {{ "type": "int", "domain": [[1, 2]] }},
+                "b": {{ "type": "bool" }}
+            }},
+            "arrays": {{
+                "xs": {{ "a": ["x1", "x2", "x3"] }}
+            }},
+            "constraints": [
+                {{ "id": "int_lin_le", "args": [[1, 1, 1], "xs", 5] }}
+            ],
+            "output": ["xs", "b"],
+            "solve": {{ "method": "satisfy", "ann": {annotations} }},
+            "version": "1.0"
+        }}"#
+    );
+
+    let path = PathBuf::from(format!(
+        "{}/flatzinc_{name}.json",
+        env!("CARGO_TARGET_TMPDIR")
+    ));
+    std::fs::write(&path, model).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_limiga"))
+        .arg(&path)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.strip_suffix(';')?.split_once(" = "))
+        .map(|(name, value)| {
+            let value = match value {
+                "true" => 1,
+                "false" => 0,
+                value => value.parse().unwrap(),
+            };
+
+            (name.to_owned(), value)
+        })
+        .collect()
+}
+
+fn values(solution: &HashMap<String, i64>) -> [i64; 4] {
+    ["x1", "x2", "x3", "b"].map(|name| solution[name])
+}
+
+#[test]
+fn int_search_assigns_the_selected_values() {
+    let solution = solve(
+        "int_search",
+        r#"[{ "id": "int_search", "args": ["xs", "input_order", "indomain_max", "complete"] }]"#,
+        &[],
+    );
+
+    // Once `x1 = 3`, the other variables can only take their smallest values.
+    assert_eq!([3, 1, 1], values(&solution)[..3]);
+}
+
+#[test]
+fn seq_search_follows_the_searches_in_order() {
+    let solution = solve(
+        "seq_search",
+        r#"[{ "id": "seq_search", "args": [[
+            { "id": "int_search", "args": [["x3"], "input_order", "indomain_max", "complete"] },
+            { "id": "int_search", "args": ["xs", "input_order", "indomain_max", "complete"] },
+            { "id": "bool_search", "args": [["b"], "input_order", "indomain_max", "complete"] }
+        ]] }]"#,
+        &[],
+    );
+
+    assert_eq!([2, 1, 2, 1], values(&solution));
+}
+
+#[test]
+fn priority_search_follows_the_search_of_the_selected_control_variable() {
+    let solution = solve(
+        "priority_search",
+        r#"[{ "id": "priority_search", "args": ["xs", [
+            { "id": "int_search", "args": [["x1"], "input_order", "indomain_max", "complete"] },
+            { "id": "int_search", "args": [["x2"], "input_order", "indomain_max", "complete"] },
+            { "id": "int_search", "args": [["x3"], "input_order", "indomain_max", "complete"] }
+        ], "first_fail", "complete"] }]"#,
+        &[],
+    );
+
+    // The domain of `x3` is the smallest, after which `x1` and `x2` both have two values left.
+    assert_eq!([2, 1, 2], values(&solution)[..3]);
+}
+
+#[test]
+fn priority_search_skips_the_searches_of_fixed_control_variables() {
+    let solution = solve(
+        "priority_search_fixed",
+        r#"[{ "id": "priority_search", "args": ["xs", [
+            { "id": "bool_search", "args": [["b"], "input_order", "indomain_min", "complete"] },
+            { "id": "bool_search", "args": [["b"], "input_order", "indomain_min", "complete"] },
+            { "id": "int_search", "args": [["x1"], "input_order", "indomain_max", "complete"] }
+        ], "first_fail", "complete"] }]"#,
+        &[],
+    );
+
+    // The domain of `x3` is the smallest. Once `x1 = 3` every control variable is fixed, so `b` is
+    // not set by the searches of `x1` and `x2` but left to the default heuristic.
+    assert_eq!([3, 1, 1, 1], values(&solution));
+}
+
+#[test]
+fn unsupported_search_annotations_are_ignored() {
+    let solution = solve(
+        "unsupported_search",
+        r#"[{ "id": "int_search", "args": ["xs", "occurrence", "indomain_max", "complete"] }]"#,
+        &[],
+    );
+
+    assert!(values(&solution)[..3].iter().sum::<i64>() <= 5);
+}
+
+#[test]
+fn free_search_ignores_the_search_annotations() {
+    let annotations =
+        r#"[{ "id": "int_search", "args": ["xs", "input_order", "indomain_max", "complete"] }]"#;
+
+    let solution = solve("free_search", annotations, &["-f"]);
+    let free_values = values(&solution);
+
+    let solution = solve("free_search_heuristic", "[]", &[]);
+    assert_eq!(values(&solution), free_values);
+}
@@ -0,0 +1,298 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fs::File,
+    path::PathBuf,
+    process::Command,
+};
+
+use limiga_constraints::{bool_lin_leq, linear_leq};
+use limiga_core::{
+    brancher::VsidsBrancher,
+    domains::{DomainId, TypedDomainStore},
+    integer::{interval_domain::IntInterval, Int, IntEvent},
+    lit::Lit,
+    proof::VeriPbProof,
+    propagation::{LitEvent, SDomainEvent},
+    random::Random,
+    restart::LubyRestarts,
+    solver::{SolveResult, Solver},
+    storage::{Indexer, StaticIndexer},
+    termination::Indefinite,
+};
+
+// The proofs are only validated by VeriPB, which is not run by default. The tests which run by
+// default check the solutions, and that the proofs conclude what the solver found.
+
+#[test]
+fn pigeonhole_is_refuted() {
+    refute_pigeonhole("pigeonhole");
+    assert!(concludes_unsat("pigeonhole"));
+}
+
+#[test]
+fn random_models_are_solved() {
+    for (name, is_refuted) in solve_random_models("random") {
+        assert_eq!(is_refuted, concludes_unsat(&name), "{name}");
+    }
+}
+
+/// Run it with `VERIPB=veripb cargo test -- --ignored`.
+#[test]
+#[ignore = "needs the command to run VeriPB in VERIPB"]
+fn pigeonhole_proof_is_verified_by_veripb() {
+    let Some(veripb) = veripb() else {
+        return;
+    };
+
+    refute_pigeonhole("pigeonhole_verified");
+    assert_verified(&veripb, "pigeonhole_verified");
+}
+
+/// Run it with `VERIPB=veripb cargo test -- --ignored`.
+#[test]
+#[ignore = "needs the command to run VeriPB in VERIPB"]
+fn random_model_proofs_are_verified_by_veripb() {
+    let Some(veripb) = veripb() else {
+        return;
+    };
+
+    for (name, _) in solve_random_models("random_verified") {
+        assert_verified(&veripb, &name);
+    }
+}
+
+/// Refute the pigeonhole problem with one more pigeon than holes, where the capacity of the holes
+/// is enforced by propagators.
+fn refute_pigeonhole(name: &str) {
+    let mut solver = new_solver(name);
+    let holes = 5;
+
+    let pigeons = (0..holes + 1)
+        .map(|_| solver.new_lits().take(holes).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    for pigeon in pigeons.iter() {
+        solver.add_clause(pigeon.iter().copied());
+    }
+
+    for hole in 0..holes {
+        let capacity = solver.new_domain(IntInterval::factory(0, 1));
+        let occupants = pigeons.iter().map(|pigeon| pigeon[hole]).collect();
+
+        bool_lin_leq(&mut solver, occupants, capacity);
+    }
+
+    let result = solver.solve(Indefinite, VsidsBrancher::new(0.95), LubyRestarts::new(100));
+    assert!(matches!(result, SolveResult::Unsatisfiable));
+
+    solver.take_proof().unwrap().finish().unwrap();
+}
+
+/// Solve random models and check their solutions. Returns the names of the proofs, and whether
+/// their models were refuted.
+fn solve_random_models(prefix: &str) -> Vec<(String, bool)> {
+    let mut outcomes = vec![];
+
+    for seed in 0..40 {
+        let name = format!("{prefix}{seed}");
+        let mut solver = new_solver(&name);
+        let model = RandomModel::post(&mut solver, seed);
+
+        let result = solver.solve(Indefinite, VsidsBrancher::new(0.95), LubyRestarts::new(100));
+
+        let is_refuted = match result {
+            SolveResult::Satisfiable(solution) => {
+                let value = |lit: Lit| solution.value(lit.var()) == lit.is_positive();
+
+                for clause in model.clauses.iter() {
+                    assert!(clause.iter().any(|&lit| value(lit)), "{name}: {clause:?}");
+                }
+
+                let mut total = 0;
+                for (group, bound) in model.groups.iter() {
+                    let bound = solution.domain_value(bound.clone());
+                    let count = group.iter().filter(|&&lit| value(lit)).count() as Int;
+
+                    assert!(count <= bound, "{name}: {count} > {bound}");
+                    total += bound;
+                }
+
+                assert!(total <= model.limit, "{name}: {total} > {}", model.limit);
+                false
+            }
+            SolveResult::Unsatisfiable => true,
+            SolveResult::UnsatisfiableUnderAssumptions(_) | SolveResult::Unknown => {
+                unreachable!()
+            }
+        };
+
+        solver.take_proof().unwrap().finish().unwrap();
+        outcomes.push((name, is_refuted));
+    }
+
+    let num_refuted = outcomes
+        .iter()
+        .filter(|(_, is_refuted)| *is_refuted)
+        .count();
+    assert!(
+        (1..40).contains(&num_refuted),
+        "both outcomes should be covered, {num_refuted} models were refuted"
+    );
+
+    outcomes
+}
+
+type TestSolver = Solver<TypedDomainStore<IntInterval>, SolverEvent>;
+
+fn new_solver(name: &str) -> TestSolver {
+    let mut solver = TestSolver::default();
+    solver.log_proof(VeriPbProof::new(
+        File::create(proof_path(name, "opb")).unwrap(),
+        File::create(proof_path(name, "pbp")).unwrap(),
+    ));
+
+    solver
+}
+
+fn proof_path(name: &str, extension: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}/veripb_{name}.{extension}",
+        env!("CARGO_TARGET_TMPDIR")
+    ))
+}
+
+/// Random clauses over a set of literals, where the number of true literals in several groups is
+/// at most an integer. The sum of those integers is bounded by a constant.
+struct RandomModel {
+    clauses: Vec<Vec<Lit>>,
+    groups: Vec<(Vec<Lit>, DomainId<IntInterval>)>,
+    limit: Int,
+}
+
+impl RandomModel {
+    fn post(solver: &mut TestSolver, seed: u64) -> RandomModel {
+        let mut random = Random::new(seed);
+        let lits = solver.new_lits().take(24).collect::<Vec<_>>();
+        let random_lit = |random: &mut Random| {
+            let lit = lits[random.next_below(lits.len())];
+            if random.next_bool() {
+                lit
+            } else {
+                !lit
+            }
+        };
+
+        let clauses = (0..40)
+            .map(|_| (0..3).map(|_| random_lit(&mut random)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        for clause in clauses.iter() {
+            solver.add_clause(clause.iter().copied());
+        }
+
+        let groups = (0..4)
+            .map(|_| {
+                let group = (0..6).map(|_| random_lit(&mut random)).collect::<Vec<_>>();
+                let bound = solver.new_domain(IntInterval::factory(0, 3));
+                bool_lin_leq(solver, group.clone().into(), bound.clone());
+
+                (group, bound)
+            })
+            .collect::<Vec<_>>();
+
+        let limit = 2 + random.next_below(6) as Int;
+        let bounds = groups
+            .iter()
+            .map(|(_, bound)| bound.clone())
+            .collect::<Vec<_>>();
+        linear_leq(solver, bounds, limit);
+
+        RandomModel {
+            clauses,
+            groups,
+            limit,
+        }
+    }
+}
+
+/// The command to run VeriPB, from the environment variable `VERIPB`. If it is not set, the skip is
+/// reported.
+fn veripb() -> Option<OsString> {
+    let veripb = std::env::var_os("VERIPB");
+    if veripb.is_none() {
+        eprintln!("skipped checking VeriPB proofs: VERIPB is not set");
+    }
+
+    veripb
+}
+
+/// Check the proof written for the given name against its model with VeriPB.
+fn assert_verified(veripb: &OsStr, name: &str) {
+    let output = Command::new(veripb)
+        .arg(proof_path(name, "opb"))
+        .arg(proof_path(name, "pbp"))
+        .output()
+        .expect("could not run veripb");
+
+    assert!(
+        output.status.success(),
+        "{name}: the proof was rejected:\n{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+/// Whether the proof written for the given name concludes that its model is unsatisfiable. This
+/// does not check the steps of the proof.
+fn concludes_unsat(name: &str) -> bool {
+    std::fs::read_to_string(proof_path(name, "pbp"))
+        .unwrap()
+        .lines()
+        .any(|line| line.starts_with("conclusion UNSAT"))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SolverEvent {
+    LitEvent(LitEvent),
+    IntEvent(IntEvent),
+}
+
+impl From<LitEvent> for SolverEvent {
+    fn from(value: LitEvent) -> Self {
+        SolverEvent::LitEvent(value)
+    }
+}
+
+impl From<IntEvent> for SolverEvent {
+    fn from(value: IntEvent) -> Self {
+        SolverEvent::IntEvent(value)
+    }
+}
+
+impl Indexer for SolverEvent {
+    fn index(&self) -> usize {
+        match *self {
+            SolverEvent::LitEvent(LitEvent::FixedTrue) => 0,
+            SolverEvent::LitEvent(LitEvent::FixedFalse) => 1,
+            SolverEvent::IntEvent(IntEvent::LowerBound) => 2,
+            SolverEvent::IntEvent(IntEvent::UpperBound) => 3,
+        }
+    }
+}
+
+impl SDomainEvent<LitEvent> for SolverEvent {
+    fn is(self, event: LitEvent) -> bool {
+        matches!(self, SolverEvent::LitEvent(e) if e == event)
+    }
+}
+
+impl SDomainEvent<IntEvent> for SolverEvent {
+    fn is(self, event: IntEvent) -> bool {
+        matches!(self, SolverEvent::IntEvent(e) if e == event)
+    }
+}
+
+impl StaticIndexer for SolverEvent {
+    fn get_len() -> usize {
+        4
+    }
+}
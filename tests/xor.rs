@@ -0,0 +1,97 @@
+use std::{fs::File, num::NonZeroI32, path::PathBuf, time::Duration};
+
+use limiga::sat::{Conclusion, ProofFormat, ProofOutput};
+use limiga_core::proof::ProofEncoding;
+use limiga_dimacs::{DimacsSink, NewVariables};
+
+const TEST_TIME_BUDGET: Duration = Duration::from_secs(30);
+
+fn instance_path(name: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}/instances/xor/{name}.cnf",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+}
+
+fn run(name: &str) -> Conclusion {
+    limiga::sat::run_solver(instance_path(name), Some(TEST_TIME_BUDGET), None)
+        .expect("failed to run solver")
+        .0
+}
+
+/// Check the assignment against the clauses and the XOR constraints of the instance.
+fn assert_satisfied(name: &str, assignment: limiga::sat::Assignment) {
+    let file = File::open(instance_path(name)).expect("could not open instance file");
+    let instance = limiga_dimacs::parse_cnf(file, |_| Instance::default()).expect("valid dimacs");
+
+    for clause in instance.clauses.iter() {
+        assert!(
+            clause.iter().any(|&lit| assignment.value(lit)),
+            "unsatisfied clause {clause:?}"
+        );
+    }
+
+    for xor in instance.xors.iter() {
+        let num_true = xor.iter().filter(|&&lit| assignment.value(lit)).count();
+        assert!(num_true % 2 == 1, "unsatisfied xor {xor:?}");
+    }
+}
+
+/// The XOR constraints are kept as they are, so the assignment does not need values for the new
+/// variables of their encoding.
+#[derive(Default)]
+struct Instance {
+    clauses: Vec<Box<[NonZeroI32]>>,
+    xors: Vec<Box<[NonZeroI32]>>,
+}
+
+impl DimacsSink for Instance {
+    fn add_clause(&mut self, clause: &[NonZeroI32]) {
+        self.clauses.push(clause.into());
+    }
+
+    fn add_xor(&mut self, lits: &[NonZeroI32], _: &mut NewVariables) {
+        self.xors.push(lits.into());
+    }
+}
+
+#[test]
+fn xors_encoded_as_clauses_are_solved() {
+    match run("parity-sat") {
+        Conclusion::Satisfiable(assignment) => assert_satisfied("parity-sat", assignment),
+        _ => panic!("expected a solution"),
+    }
+}
+
+#[test]
+fn inconsistent_xors_encoded_as_clauses_are_refuted() {
+    assert!(matches!(run("parity-unsat"), Conclusion::Unsatisfiable));
+}
+
+#[test]
+fn xor_lines_are_solved() {
+    match run("parity-xor-lines") {
+        Conclusion::Satisfiable(assignment) => assert_satisfied("parity-xor-lines", assignment),
+        _ => panic!("expected a solution"),
+    }
+}
+
+#[test]
+fn xor_lines_are_rejected_when_logging_a_proof() {
+    let proof = ProofOutput {
+        path: PathBuf::from(format!(
+            "{}/parity-xor-lines.drat",
+            env!("CARGO_TARGET_TMPDIR")
+        )),
+        format: ProofFormat::Drat,
+        encoding: ProofEncoding::Text,
+    };
+
+    let result = limiga::sat::run_solver(
+        instance_path("parity-xor-lines"),
+        Some(TEST_TIME_BUDGET),
+        Some(proof),
+    );
+
+    assert!(result.is_err());
+}